@@ -1,10 +1,25 @@
 use thiserror::Error;
 
+use crate::header::Bk2HeaderError;
+use crate::input::Bk2InputLogError;
+
 #[derive(Error, Debug)]
 pub enum Bk2Error {
     #[error("IO error: {0}")]
-    IoError(std::io::Error),
+    IoError(#[from] std::io::Error),
 
     #[error("Zip error: {0}")]
-    ZipError(zip::result::ZipError),
+    ZipError(#[from] zip::result::ZipError),
+
+    #[error("Missing file in BK2 archive: {0}")]
+    MissingFile(&'static str),
+
+    #[error("Invalid header: {0}")]
+    HeaderError(#[from] Bk2HeaderError),
+
+    #[error("Invalid input log: {0}")]
+    InputLogError(#[from] Bk2InputLogError),
+
+    #[error("Invalid subtitle line: {0}")]
+    InvalidSubtitle(String),
 }
@@ -1,26 +1,102 @@
+use std::collections::BTreeMap;
+
 use thiserror::Error;
 
 /// Errors that can occur when reading a BK2 header file.
 #[derive(Error, Debug)]
-pub enum Bk2HeaderError {}
+pub enum Bk2HeaderError {
+    #[error("Missing header key: {0}")]
+    MissingKey(&'static str),
+
+    #[error("Invalid rerecord count: {0}")]
+    InvalidRerecordCount(std::num::ParseIntError),
+}
 
+/// The content of the `Header.txt` file of a BK2 movie. Every line is a
+/// key followed by a space and its value.
+#[derive(Debug, Clone)]
 pub struct Bk2Header {
-    movie_version: String,
-    version: String,
-    rerecord_count: usize,
-    author: String,
-    platform: String,
-    game_name: String,
-    sha1: String,
-    core: String,
+    pub movie_version: String,
+    pub version: String,
+    pub rerecord_count: usize,
+    pub author: String,
+    pub platform: String,
+    pub game_name: String,
+    pub sha1: String,
+    pub core: String,
+
+    /// All key/values of the header, including the ones above.
+    values: BTreeMap<String, String>,
 }
 
 impl TryFrom<String> for Bk2Header {
     type Error = Bk2HeaderError;
 
     fn try_from(header: String) -> Result<Self, Self::Error> {
-        todo!()
+        let mut values = BTreeMap::new();
+
+        for line in header.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            // Some keys (e.g. `StartsFromSaveRam`) can have an empty value.
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            values.insert(key.to_string(), value.trim().to_string());
+        }
+
+        let movie_version = values
+            .get("MovieVersion")
+            .cloned()
+            .ok_or(Bk2HeaderError::MissingKey("MovieVersion"))?;
+        let rerecord_count = match values.get("rerecordCount") {
+            Some(v) => v.parse().map_err(Bk2HeaderError::InvalidRerecordCount)?,
+            None => 0,
+        };
+        let get = |key: &str| values.get(key).cloned().unwrap_or_default();
+
+        Ok(Self {
+            movie_version,
+            version: get("emuVersion"),
+            rerecord_count,
+            author: get("Author"),
+            platform: get("Platform"),
+            game_name: get("GameName"),
+            sha1: get("SHA1"),
+            core: get("Core"),
+            values,
+        })
     }
 }
 
-impl Bk2Header {}
+impl Bk2Header {
+    /// Get the raw value of a header key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// Whether the movie is recorded on a PAL system.
+    pub fn pal(&self) -> bool {
+        self.get("PAL")
+            .is_some_and(|v| v.eq_ignore_ascii_case("true"))
+    }
+
+    /// Whether the movie starts from a savestate (stored in `Core.bin`)
+    /// instead of power on.
+    pub fn starts_from_savestate(&self) -> bool {
+        self.get("StartsFromSavestate")
+            .is_some_and(|v| v.eq_ignore_ascii_case("true"))
+    }
+
+    /// Whether the movie starts with a SaveRAM (stored in `SaveRam`).
+    pub fn starts_from_saveram(&self) -> bool {
+        self.get("StartsFromSaveRam")
+            .is_some_and(|v| v.eq_ignore_ascii_case("true"))
+    }
+
+    /// Iterate over all the key/values of the header.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
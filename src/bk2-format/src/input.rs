@@ -0,0 +1,249 @@
+use thiserror::Error;
+
+/// Errors that can occur when reading the `Input Log.txt` file of a BK2 movie.
+#[derive(Error, Debug)]
+pub enum Bk2InputLogError {
+    #[error("Missing LogKey line")]
+    MissingLogKey,
+
+    #[error("Input line found before the LogKey line")]
+    InputBeforeLogKey,
+
+    #[error("Invalid input line for frame {0}: {1}")]
+    InvalidFrame(usize, String),
+}
+
+/// A single input value in a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bk2Input {
+    /// A button, pressed or not.
+    Button(bool),
+
+    /// An analog value (e.g. a stick axis or a paddle position).
+    Axis(i32),
+}
+
+impl Bk2Input {
+    /// Whether this is a button that is pressed. Axis are never pressed.
+    pub fn is_pressed(&self) -> bool {
+        matches!(self, Bk2Input::Button(true))
+    }
+
+    /// The analog value of this input, if it is an axis.
+    pub fn as_axis(&self) -> Option<i32> {
+        match self {
+            Bk2Input::Axis(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+/// The `LogKey` line of the input log, which names every input of a frame
+/// row. Inputs are split in groups (one per `#`), normally the console
+/// buttons first (Reset, Power), then one group per controller.
+#[derive(Debug, Clone, Default)]
+pub struct Bk2LogKey {
+    groups: Vec<Vec<String>>,
+}
+
+impl Bk2LogKey {
+    pub(crate) fn parse(line: &str) -> Self {
+        let mut groups: Vec<Vec<String>> = Vec::new();
+
+        for key in line.split('|').filter(|k| !k.is_empty()) {
+            if let Some(key) = key.strip_prefix('#') {
+                groups.push(Vec::new());
+                if key.is_empty() {
+                    continue;
+                }
+                groups.last_mut().unwrap().push(key.to_string());
+            } else if let Some(group) = groups.last_mut() {
+                group.push(key.to_string());
+            } else {
+                groups.push(vec![key.to_string()]);
+            }
+        }
+
+        Self { groups }
+    }
+
+    /// The groups of input names, in the order they appear in a frame row.
+    pub fn groups(&self) -> &[Vec<String>] {
+        &self.groups
+    }
+
+    /// All input names, in the order they appear in a frame row.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.groups.iter().flatten().map(String::as_str)
+    }
+
+    /// The number of inputs in a frame.
+    pub fn len(&self) -> usize {
+        self.groups.iter().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The index of an input by its name.
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.keys().position(|k| k == name)
+    }
+}
+
+/// Parse a single group of a frame row, e.g. `UDLRSsBA` or `   12,  -3,..`.
+fn parse_group(keys: &[String], group: &str, out: &mut Vec<Bk2Input>) -> Result<(), String> {
+    // Fast path, buttons only.
+    if group.len() == keys.len() && !group.contains(',') {
+        out.extend(group.chars().map(|c| Bk2Input::Button(c != '.')));
+        return Ok(());
+    }
+
+    let mut rest = group;
+    for key in keys {
+        let c = rest
+            .chars()
+            .next()
+            .ok_or_else(|| format!("missing value for input {key:?}"))?;
+
+        // Analog values are padded with spaces and terminated by a comma.
+        let axis = if c == ' ' || c == '-' || c.is_ascii_digit() {
+            rest.split_once(',')
+                .and_then(|(value, tail)| Some((value.trim().parse::<i32>().ok()?, tail)))
+        } else {
+            None
+        };
+
+        if let Some((value, tail)) = axis {
+            out.push(Bk2Input::Axis(value));
+            rest = tail;
+        } else {
+            out.push(Bk2Input::Button(c != '.'));
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+
+    if !rest.is_empty() {
+        return Err(format!("unexpected trailing data {rest:?}"));
+    }
+    Ok(())
+}
+
+/// The content of the `Input Log.txt` file of a BK2 movie.
+#[derive(Debug, Clone, Default)]
+pub struct Bk2InputLog {
+    key: Bk2LogKey,
+    inputs: Vec<Bk2Input>,
+}
+
+impl TryFrom<&str> for Bk2InputLog {
+    type Error = Bk2InputLogError;
+
+    fn try_from(content: &str) -> Result<Self, Self::Error> {
+        let mut key = None;
+        let mut inputs = Vec::new();
+        let mut frame = 0;
+
+        for line in content.lines() {
+            let line = line.trim_end_matches(['\r', '\n']);
+
+            if let Some(k) = line.strip_prefix("LogKey:") {
+                key = Some(Bk2LogKey::parse(k));
+            } else if let Some(row) = line.strip_prefix('|') {
+                let key = key.as_ref().ok_or(Bk2InputLogError::InputBeforeLogKey)?;
+                let row = row.strip_suffix('|').unwrap_or(row);
+                let groups = row.split('|').collect::<Vec<_>>();
+                if groups.len() != key.groups.len() {
+                    return Err(Bk2InputLogError::InvalidFrame(
+                        frame,
+                        format!(
+                            "expected {} groups, found {}",
+                            key.groups.len(),
+                            groups.len()
+                        ),
+                    ));
+                }
+
+                for (keys, group) in key.groups.iter().zip(groups) {
+                    parse_group(keys, group, &mut inputs)
+                        .map_err(|e| Bk2InputLogError::InvalidFrame(frame, e))?;
+                }
+                frame += 1;
+            }
+            // Other lines (`[Input]`, `[/Input]`, ...) are ignored.
+        }
+
+        Ok(Self {
+            key: key.ok_or(Bk2InputLogError::MissingLogKey)?,
+            inputs,
+        })
+    }
+}
+
+impl Bk2InputLog {
+    pub fn key(&self) -> &Bk2LogKey {
+        &self.key
+    }
+
+    /// Number of frames in the log.
+    pub fn len(&self) -> usize {
+        match self.key.len() {
+            0 => 0,
+            n => self.inputs.len() / n,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Get a single frame.
+    pub fn frame(&self, index: usize) -> Option<Bk2Frame<'_>> {
+        let n = self.key.len();
+        self.inputs
+            .get(index * n..(index + 1) * n)
+            .map(|inputs| Bk2Frame {
+                key: &self.key,
+                inputs,
+            })
+    }
+
+    /// Iterate over all frames.
+    pub fn frames(&self) -> impl Iterator<Item = Bk2Frame<'_>> {
+        (0..self.len()).filter_map(|i| self.frame(i))
+    }
+}
+
+/// A frame of the input log, which can be queried by input names.
+#[derive(Debug, Clone, Copy)]
+pub struct Bk2Frame<'a> {
+    key: &'a Bk2LogKey,
+    inputs: &'a [Bk2Input],
+}
+
+impl<'a> Bk2Frame<'a> {
+    /// Get an input by its name, e.g. `P1 A` or `Reset`.
+    pub fn get(&self, name: &str) -> Option<Bk2Input> {
+        self.key.index_of(name).map(|i| self.inputs[i])
+    }
+
+    /// Whether a button is pressed on this frame. Unknown names and axis
+    /// are never pressed.
+    pub fn is_pressed(&self, name: &str) -> bool {
+        self.get(name).is_some_and(|i| i.is_pressed())
+    }
+
+    /// All inputs of this frame with their names.
+    pub fn inputs(&self) -> impl Iterator<Item = (&'a str, Bk2Input)> + 'a {
+        self.key.keys().zip(self.inputs.iter().copied())
+    }
+
+    /// All inputs of a player (starting at 1), with the `P{n} ` prefix
+    /// removed from their name.
+    pub fn player(&self, player: u8) -> impl Iterator<Item = (&'a str, Bk2Input)> + 'a {
+        let prefix = format!("P{player} ");
+        self.inputs()
+            .filter_map(move |(name, input)| Some((name.strip_prefix(prefix.as_str())?, input)))
+    }
+}
@@ -1,16 +1,267 @@
+//! Reader for BizHawk movie files (`.bk2`). A BK2 file is a zip archive
+//! containing, among others, a `Header.txt`, a `SyncSettings.json` and an
+//! `Input Log.txt` with one row per frame.
 use std::io::{Read, Seek};
 
 pub use error::Bk2Error;
+pub use header::{Bk2Header, Bk2HeaderError};
+pub use input::{Bk2Frame, Bk2Input, Bk2InputLog, Bk2InputLogError, Bk2LogKey};
+pub use subtitle::Bk2Subtitle;
 
 mod error;
 mod header;
+mod input;
+#[cfg(feature = "movie")]
+mod movie;
+mod subtitle;
+
+const HEADER_FILE: &str = "Header.txt";
+const INPUT_LOG_FILE: &str = "Input Log.txt";
+const SYNC_SETTINGS_FILE: &str = "SyncSettings.json";
+const SUBTITLES_FILE: &str = "Subtitles.txt";
+const COMMENTS_FILE: &str = "Comments.txt";
+const SAVESTATE_FILE: &str = "Core.bin";
+const SAVERAM_FILE: &str = "SaveRam";
+
+/// Read a file from the archive. Returns `None` if it does not exist.
+fn read_entry<R: Read + Seek>(
+    file: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> Result<Option<Vec<u8>>, Bk2Error> {
+    match file.by_name(name) {
+        Ok(mut entry) => {
+            let mut content = Vec::with_capacity(entry.size() as usize);
+            entry.read_to_end(&mut content)?;
+            Ok(Some(content))
+        }
+        Err(zip::result::ZipError::FileNotFound) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn read_string_entry<R: Read + Seek>(
+    file: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> Result<Option<String>, Bk2Error> {
+    Ok(read_entry(file, name)?.map(|c| String::from_utf8_lossy(&c).into_owned()))
+}
 
 pub struct Bk2File<R: Read + Seek> {
     file: zip::ZipArchive<R>,
+
+    pub header: Bk2Header,
+    /// The raw JSON content of `SyncSettings.json`, if present.
+    pub sync_settings: Option<String>,
+    pub inputs: Bk2InputLog,
+    pub subtitles: Vec<Bk2Subtitle>,
+    pub comments: Vec<String>,
 }
 
 impl<R: Read + Seek> Bk2File<R> {
     pub fn load(file: R) -> Result<Self, Bk2Error> {
-        todo!()
+        let mut file = zip::ZipArchive::new(file)?;
+
+        let header =
+            read_string_entry(&mut file, HEADER_FILE)?.ok_or(Bk2Error::MissingFile(HEADER_FILE))?;
+        let header = Bk2Header::try_from(header)?;
+
+        let inputs = read_string_entry(&mut file, INPUT_LOG_FILE)?
+            .ok_or(Bk2Error::MissingFile(INPUT_LOG_FILE))?;
+        let inputs = Bk2InputLog::try_from(inputs.as_str())?;
+
+        let sync_settings = read_string_entry(&mut file, SYNC_SETTINGS_FILE)?;
+
+        let subtitles = read_string_entry(&mut file, SUBTITLES_FILE)?
+            .unwrap_or_default()
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| l.trim_end().parse())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let comments = read_string_entry(&mut file, COMMENTS_FILE)?
+            .unwrap_or_default()
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| l.trim_end().to_string())
+            .collect();
+
+        Ok(Self {
+            file,
+            header,
+            sync_settings,
+            inputs,
+            subtitles,
+            comments,
+        })
+    }
+
+    /// Iterate over all frames of the movie.
+    pub fn frames(&self) -> impl Iterator<Item = Bk2Frame<'_>> {
+        self.inputs.frames()
+    }
+
+    /// The savestate the movie starts from, if any. The format of the
+    /// savestate is specific to the BizHawk core used.
+    pub fn savestate(&mut self) -> Result<Option<Vec<u8>>, Bk2Error> {
+        read_entry(&mut self.file, SAVESTATE_FILE)
+    }
+
+    /// The SaveRAM the movie starts with, if any.
+    pub fn saveram(&mut self) -> Result<Option<Vec<u8>>, Bk2Error> {
+        read_entry(&mut self.file, SAVERAM_FILE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
+
+    use super::*;
+
+    fn create_bk2(files: &[(&str, &str)]) -> Cursor<Vec<u8>> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options =
+            zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        for (name, content) in files {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        let mut cursor = zip.finish().unwrap();
+        cursor.set_position(0);
+        cursor
+    }
+
+    const HEADER: &str = "MovieVersion BizHawk v2.0.0\n\
+        Author someone\n\
+        emuVersion Version 2.9.1\n\
+        Platform NES\n\
+        GameName Super Mario Bros.\n\
+        SHA1 EA343F4E445A9050D4B4FBAC2C77D0693B1D0922\n\
+        Core NesHawk\n\
+        rerecordCount 1234\n\
+        StartsFromSaveRam\n";
+
+    #[test]
+    fn load_nes() {
+        let input = "[Input]\n\
+            LogKey:#Reset|Power|#P1 Up|P1 Down|P1 Left|P1 Right|P1 Start|P1 Select|P1 B|P1 A|#P2 Up|P2 Down|P2 Left|P2 Right|P2 Start|P2 Select|P2 B|P2 A|\n\
+            |..|........|........|\n\
+            |r.|...RS..A|U.......|\n\
+            [/Input]\n";
+        let bk2 = Bk2File::load(create_bk2(&[
+            ("Header.txt", HEADER),
+            ("Input Log.txt", input),
+            (
+                "Subtitles.txt",
+                "subtitle 10 0 0 120 FFFFFFFF Hello World\n",
+            ),
+            ("Comments.txt", "comment A comment.\n"),
+        ]))
+        .unwrap();
+
+        assert_eq!(bk2.header.movie_version, "BizHawk v2.0.0");
+        assert_eq!(bk2.header.platform, "NES");
+        assert_eq!(bk2.header.rerecord_count, 1234);
+        assert_eq!(bk2.header.get("StartsFromSaveRam"), Some(""));
+        assert!(!bk2.header.starts_from_savestate());
+        assert_eq!(bk2.sync_settings, None);
+        assert_eq!(bk2.subtitles[0].frame, 10);
+        assert_eq!(bk2.subtitles[0].message, "Hello World");
+        assert_eq!(bk2.comments, vec!["comment A comment.".to_string()]);
+
+        let frames = bk2.frames().collect::<Vec<_>>();
+        assert_eq!(frames.len(), 2);
+        assert!(frames[0].inputs().all(|(_, i)| !i.is_pressed()));
+        assert!(frames[1].is_pressed("Reset"));
+        assert!(!frames[1].is_pressed("Power"));
+        assert_eq!(
+            frames[1]
+                .player(1)
+                .filter(|(_, i)| i.is_pressed())
+                .map(|(n, _)| n)
+                .collect::<Vec<_>>(),
+            vec!["Right", "Start", "A"]
+        );
+        assert!(frames[1].is_pressed("P2 Up"));
+    }
+
+    #[cfg(feature = "movie")]
+    #[test]
+    fn movie() {
+        use one_fpga::inputs::Button;
+        use one_fpga::movie::{Movie, MovieCommand, RomChecksum};
+
+        let input = "LogKey:#Reset|Power|#P1 Up|P1 Down|P1 Left|P1 Right|P1 Select|P1 Start|P1 Y|P1 B|P1 X|P1 A|P1 L|P1 R|#P2 Up|P2 Down|P2 Left|P2 Right|P2 Select|P2 Start|P2 Y|P2 B|P2 X|P2 A|P2 L|P2 R|\n\
+            |.P|U..........R|............|\n\
+            |..|....s...X...|...r........|\n";
+        let bk2 = Bk2File::load(create_bk2(&[
+            ("Header.txt", HEADER),
+            ("Input Log.txt", input),
+        ]))
+        .unwrap();
+        let movie: &dyn Movie = &bk2;
+
+        assert_eq!(movie.ports(), 2);
+        assert_eq!(movie.len(), 2);
+        let metadata = movie.metadata();
+        assert_eq!(metadata.system.as_deref(), Some("NES"));
+        assert!(matches!(metadata.rom_checksum, Some(RomChecksum::Sha1(sha1)) if sha1[0] == 0xEA));
+
+        let frames = movie.frames().collect::<Vec<_>>();
+        assert!(frames[0].has(MovieCommand::PowerCycle));
+        let p1 = frames[0].ports[0].unwrap();
+        assert!(p1.contains(Button::DPadUp));
+        assert!(p1.contains(Button::RightShoulder));
+        assert!(!p1.contains(Button::A));
+
+        assert!(frames[1].commands.is_empty());
+        let p1 = frames[1].ports[0].unwrap();
+        assert!(p1.contains(Button::Back));
+        assert!(p1.contains(Button::X));
+        let p2 = frames[1].ports[1].unwrap();
+        assert!(p2.contains(Button::DPadRight));
+    }
+
+    #[test]
+    fn load_axis() {
+        let input = "LogKey:#P1 X Axis|P1 Y Axis|P1 A|P1 B|\n\
+            |    0,  -12,A.|\n\
+            |  127, -128,.B|\n";
+        let bk2 = Bk2File::load(create_bk2(&[
+            ("Header.txt", HEADER),
+            ("Input Log.txt", input),
+        ]))
+        .unwrap();
+
+        let frames = bk2.frames().collect::<Vec<_>>();
+        assert_eq!(frames[0].get("P1 X Axis"), Some(Bk2Input::Axis(0)));
+        assert_eq!(frames[0].get("P1 Y Axis"), Some(Bk2Input::Axis(-12)));
+        assert!(frames[0].is_pressed("P1 A"));
+        assert_eq!(frames[1].get("P1 X Axis"), Some(Bk2Input::Axis(127)));
+        assert_eq!(frames[1].get("P1 Y Axis"), Some(Bk2Input::Axis(-128)));
+        assert!(frames[1].is_pressed("P1 B"));
+    }
+
+    #[test]
+    fn invalid_frame() {
+        let input = "LogKey:#Reset|Power|#P1 A|\n|..|A.|\n";
+        let result = Bk2File::load(create_bk2(&[
+            ("Header.txt", HEADER),
+            ("Input Log.txt", input),
+        ]));
+
+        assert!(matches!(
+            result,
+            Err(Bk2Error::InputLogError(Bk2InputLogError::InvalidFrame(
+                0,
+                _
+            )))
+        ));
+    }
+
+    #[test]
+    fn missing_header() {
+        let result = Bk2File::load(create_bk2(&[("Input Log.txt", "LogKey:#P1 A|\n")]));
+        assert!(matches!(result, Err(Bk2Error::MissingFile("Header.txt"))));
     }
 }
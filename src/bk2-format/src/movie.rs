@@ -0,0 +1,118 @@
+//! Implementation of the generic [`Movie`] trait for BK2 files.
+use std::io::{Read, Seek};
+
+use one_fpga::inputs::gamepad::ButtonSet;
+use one_fpga::inputs::Button;
+use one_fpga::movie::{Movie, MovieCommand, MovieFrame, MovieMetadata, RomChecksum};
+
+use crate::{Bk2File, Bk2Frame};
+
+/// Map a BizHawk button name (without the player prefix) to a gamepad
+/// button. Names are shared between the NES, SNES and Genesis cores. Buttons
+/// that have no equivalent on a SNES-style gamepad are mapped to the
+/// shoulder buttons (Genesis C and Z).
+fn bk2_button(name: &str) -> Option<Button> {
+    match name {
+        "Up" => Some(Button::DPadUp),
+        "Down" => Some(Button::DPadDown),
+        "Left" => Some(Button::DPadLeft),
+        "Right" => Some(Button::DPadRight),
+        "Start" => Some(Button::Start),
+        "Select" | "Mode" => Some(Button::Back),
+        "A" => Some(Button::A),
+        "B" => Some(Button::B),
+        "X" => Some(Button::X),
+        "Y" => Some(Button::Y),
+        "L" | "Z" => Some(Button::LeftShoulder),
+        "R" | "C" => Some(Button::RightShoulder),
+        _ => None,
+    }
+}
+
+fn bk2_command(name: &str) -> Option<MovieCommand> {
+    match name {
+        "Reset" => Some(MovieCommand::SoftReset),
+        "Power" => Some(MovieCommand::PowerCycle),
+        "FDS Eject" => Some(MovieCommand::FdsDiskSelect),
+        n if n.starts_with("FDS Insert ") => Some(MovieCommand::FdsDiskInsert),
+        n if n.starts_with("Insert Coin ") => Some(MovieCommand::VsInsertCoin),
+        _ => None,
+    }
+}
+
+fn parse_sha1(sha1: &str) -> Option<[u8; 20]> {
+    if sha1.len() != 40 {
+        return None;
+    }
+
+    let mut out = [0; 20];
+    for (i, byte) in out.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(sha1.get(i * 2..i * 2 + 2)?, 16).ok()?;
+    }
+    Some(out)
+}
+
+impl<R: Read + Seek> Bk2File<R> {
+    fn movie_frame(&self, frame: Bk2Frame<'_>) -> MovieFrame {
+        let commands = frame
+            .inputs()
+            .filter(|(_, input)| input.is_pressed())
+            .filter_map(|(name, _)| bk2_command(name))
+            .collect();
+
+        let ports = (1..=self.ports())
+            .map(|player| {
+                let mut set = ButtonSet::new();
+                for (name, input) in frame.player(player as u8) {
+                    if let Some(button) = bk2_button(name).filter(|_| input.is_pressed()) {
+                        set.insert(button);
+                    }
+                }
+                Some(set)
+            })
+            .collect();
+
+        MovieFrame { commands, ports }
+    }
+}
+
+impl<R: Read + Seek> Movie for Bk2File<R> {
+    fn metadata(&self) -> MovieMetadata {
+        let header = &self.header;
+        let non_empty = |s: &str| Some(s.to_string()).filter(|s| !s.is_empty());
+
+        MovieMetadata {
+            system: non_empty(&header.platform),
+            emulator: Some(format!("{} ({})", header.version, header.core)),
+            author: non_empty(&header.author),
+            rom_filename: non_empty(&header.game_name),
+            rom_checksum: parse_sha1(&header.sha1).map(RomChecksum::Sha1),
+            rerecord_count: Some(header.rerecord_count as u64),
+            pal: header.pal(),
+        }
+    }
+
+    /// The number of players is the highest `P{n}` prefix in the log key.
+    fn ports(&self) -> usize {
+        self.inputs
+            .key()
+            .keys()
+            .filter_map(|k| {
+                k.strip_prefix('P')?
+                    .split_once(' ')?
+                    .0
+                    .parse::<usize>()
+                    .ok()
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    fn frames(&self) -> Box<dyn Iterator<Item = MovieFrame> + '_> {
+        Box::new(self.inputs.frames().map(|f| self.movie_frame(f)))
+    }
+}
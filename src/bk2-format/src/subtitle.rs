@@ -0,0 +1,37 @@
+use std::str::FromStr;
+
+use crate::Bk2Error;
+
+/// A subtitle line of the `Subtitles.txt` file, in the form
+/// `subtitle {frame} {x} {y} {duration} {color} {message}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bk2Subtitle {
+    pub frame: u32,
+    pub x: i32,
+    pub y: i32,
+    pub duration: u32,
+    /// ARGB color.
+    pub color: u32,
+    pub message: String,
+}
+
+impl FromStr for Bk2Subtitle {
+    type Err = Bk2Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || Bk2Error::InvalidSubtitle(s.to_string());
+
+        let rest = s.strip_prefix("subtitle ").ok_or_else(err)?;
+        let mut parts = rest.splitn(6, ' ');
+        let mut next = || parts.next().ok_or_else(err);
+
+        Ok(Self {
+            frame: next()?.parse().map_err(|_| err())?,
+            x: next()?.parse().map_err(|_| err())?,
+            y: next()?.parse().map_err(|_| err())?,
+            duration: next()?.parse().map_err(|_| err())?,
+            color: u32::from_str_radix(next()?, 16).map_err(|_| err())?,
+            message: next().unwrap_or_default().to_string(),
+        })
+    }
+}
@@ -41,3 +41,9 @@ bitfield! {
     /// Reading this provides the value of nSTATUS.
     pub ns, _: 0;
 }
+
+impl From<u32> for GpioExtPortA {
+    fn from(value: u32) -> Self {
+        GpioExtPortA(value)
+    }
+}
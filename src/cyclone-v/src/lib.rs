@@ -163,3 +163,14 @@ impl SocFpga<memory::BufferMemoryMapper> {
         Self::new(memory)
     }
 }
+
+#[cfg(feature = "std")]
+impl SocFpga<memory::SimulatedMemoryMapper> {
+    /// Create a SoC whose FPGA Manager is simulated in a background thread.
+    /// The `handler` plays the role of the core on the GPO/GPI registers.
+    pub fn simulated(handler: impl memory::GpioHandler + 'static) -> Self {
+        let memory = memory::SimulatedMemoryMapper::new(sizes::BASE, offsets::FPGAMGRREGS, handler);
+
+        Self::new(memory)
+    }
+}
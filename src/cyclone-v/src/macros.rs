@@ -70,6 +70,20 @@ macro_rules! declare_volatile_struct {
         impl $sname {
             $(
                 $crate::declare_field_accessors!($(#[$fattr])* $fname, $ftype [$($($tags)*)?]);
+
+                paste::paste! {
+                    /// A raw pointer to the field, for code that cannot hold a
+                    /// reference to the struct (e.g. when another thread also
+                    /// accesses it).
+                    ///
+                    /// # Safety
+                    /// `this` must point to a valid, properly aligned struct.
+                    #[allow(dead_code)]
+                    #[inline]
+                    pub(crate) unsafe fn [<$fname _raw>](this: *mut Self) -> *mut $ftype {
+                        core::ptr::addr_of_mut!((*this).$fname)
+                    }
+                }
             )*
         }
     };
@@ -8,6 +8,10 @@ pub mod buffer;
 #[cfg(feature = "std")]
 pub use buffer::*;
 
+pub mod simulated;
+#[cfg(feature = "std")]
+pub use simulated::*;
+
 fn clamp_range(range: impl RangeBounds<usize>, max: usize) -> (usize, usize) {
     let start = match range.start_bound() {
         Bound::Included(start) => *start,
@@ -61,6 +65,11 @@ pub trait MemoryMapper {
         let (start, len) = clamp_range(range, self.len());
         unsafe { std::slice::from_raw_parts_mut(self.as_mut_ptr::<u8>().add(start), len) }
     }
+
+    /// Wait until the device behind the mapped memory has seen all the writes
+    /// done so far. Hardware sees writes right away, so by default this does
+    /// nothing.
+    fn sync(&self) {}
 }
 
 pub struct RegionMemoryMapper<'a> {
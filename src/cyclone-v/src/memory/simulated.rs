@@ -0,0 +1,299 @@
+#![cfg(feature = "std")]
+
+use crate::fpgamgrregs::ctrl::{FpgaConfigurationControl, FpgaCtrlEn};
+use crate::fpgamgrregs::gpio_ext_porta::GpioExtPortA;
+use crate::fpgamgrregs::stat::{StatusRegister, StatusRegisterMode};
+use crate::fpgamgrregs::FpgaManagerRegs;
+use crate::memory::{BufferMemoryMapper, MemoryMapper};
+use core::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// The FPGA side of the GPO/GPI registers. This is what a core would
+/// implement in hardware.
+pub trait GpioHandler: Send {
+    /// Called every time the HPS writes a new value in the GPO register.
+    /// Returns the new value of the GPI register.
+    fn gpo_changed(&mut self, gpo: u32) -> u32;
+}
+
+impl<F: FnMut(u32) -> u32 + Send> GpioHandler for F {
+    fn gpo_changed(&mut self, gpo: u32) -> u32 {
+        self(gpo)
+    }
+}
+
+/// The simulated FPGA Manager registers. The HPS side uses the same memory
+/// from another thread, so the simulation never creates a reference to them
+/// and only does volatile reads and writes through raw pointers.
+#[derive(Clone, Copy)]
+struct SimulatedRegs(*mut FpgaManagerRegs);
+
+// SAFETY: The region outlives the thread, which is joined on drop.
+unsafe impl Send for SimulatedRegs {}
+
+impl SimulatedRegs {
+    fn stat(self) -> StatusRegister {
+        unsafe { FpgaManagerRegs::stat_raw(self.0).read_volatile() }
+    }
+
+    fn set_stat(self, value: StatusRegister) {
+        unsafe { FpgaManagerRegs::stat_raw(self.0).write_volatile(value) }
+    }
+
+    fn ctrl(self) -> FpgaConfigurationControl {
+        unsafe { FpgaManagerRegs::ctrl_raw(self.0).read_volatile() }
+    }
+
+    fn dclkcnt(self) -> u32 {
+        unsafe { FpgaManagerRegs::dclkcnt_raw(self.0).read_volatile() }
+    }
+
+    fn set_dclkcnt(self, value: u32) {
+        unsafe { FpgaManagerRegs::dclkcnt_raw(self.0).write_volatile(value) }
+    }
+
+    fn dclkstat(self) -> u32 {
+        unsafe { FpgaManagerRegs::dclkstat_raw(self.0).read_volatile() }
+    }
+
+    fn set_dclkstat(self, value: u32) {
+        unsafe { FpgaManagerRegs::dclkstat_raw(self.0).write_volatile(value) }
+    }
+
+    fn gpo(self) -> u32 {
+        unsafe { FpgaManagerRegs::gpo_raw(self.0).read_volatile() }
+    }
+
+    fn set_gpi(self, value: u32) {
+        unsafe { FpgaManagerRegs::gpi_raw(self.0).write_volatile(value) }
+    }
+
+    fn set_gpio_ext_porta(self, value: GpioExtPortA) {
+        unsafe { FpgaManagerRegs::gpio_ext_porta_raw(self.0).write_volatile(value) }
+    }
+
+    fn set_mode(self, mode: StatusRegisterMode) {
+        let mut stat = self.stat();
+        stat.set_mode(mode);
+        self.set_stat(stat);
+    }
+}
+
+/// Maps a region of memory over a vector, with a thread that simulates the
+/// FPGA Manager registers the way the hardware would respond to them.
+/// Useful for testing code that talks to a core.
+pub struct SimulatedMemoryMapper {
+    buffer: BufferMemoryMapper,
+    running: Arc<AtomicBool>,
+    ticks: Arc<AtomicU64>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl fmt::Debug for SimulatedMemoryMapper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SimulatedMemoryMapper")
+            .field("len", &self.buffer.len())
+            .field("running", &self.running.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+impl SimulatedMemoryMapper {
+    /// Create a simulated region of `size` bytes, with the FPGA Manager
+    /// registers at `regs_offset`. The FPGA starts in user mode.
+    pub fn new(size: usize, regs_offset: usize, mut handler: impl GpioHandler + 'static) -> Self {
+        assert!(regs_offset + core::mem::size_of::<FpgaManagerRegs>() <= size);
+
+        let mut buffer = BufferMemoryMapper::new(size);
+        let ptr = unsafe { buffer.as_mut_ptr::<u8>().add(regs_offset) } as *mut FpgaManagerRegs;
+
+        // Set the initial state before the thread starts, so the HPS never
+        // sees a half initialized FPGA.
+        let regs = SimulatedRegs(ptr);
+        regs.set_mode(StatusRegisterMode::UserMode);
+        update_ext_porta(regs, StatusRegisterMode::UserMode);
+        let gpo = regs.gpo();
+        regs.set_gpi(handler.gpo_changed(gpo));
+
+        let running = Arc::new(AtomicBool::new(true));
+        let ticks = Arc::new(AtomicU64::new(0));
+        let thread = std::thread::spawn({
+            let running = running.clone();
+            let ticks = ticks.clone();
+            move || simulate(regs, gpo, &running, &ticks, handler)
+        });
+
+        Self {
+            buffer,
+            running,
+            ticks,
+            thread: Some(thread),
+        }
+    }
+}
+
+impl Drop for SimulatedMemoryMapper {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl MemoryMapper for SimulatedMemoryMapper {
+    /// Creates a plain memory region, without any simulation. This is used
+    /// for regions other than the SoC registers (e.g. framebuffer or DDR).
+    fn create(_address: usize, size: usize) -> Result<Self, &'static str>
+    where
+        Self: Sized,
+    {
+        Ok(Self {
+            buffer: BufferMemoryMapper::new(size),
+            running: Arc::new(AtomicBool::new(false)),
+            ticks: Arc::new(AtomicU64::new(0)),
+            thread: None,
+        })
+    }
+
+    fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    fn as_ptr<T>(&self) -> *const T {
+        self.buffer.as_ptr()
+    }
+
+    fn as_mut_ptr<T>(&mut self) -> *mut T {
+        self.buffer.as_mut_ptr()
+    }
+
+    /// Wait for the simulation to go through a full step, which means it
+    /// has seen all writes done before this call.
+    fn sync(&self) {
+        if self.thread.is_none() {
+            return;
+        }
+
+        let start = self.ticks.load(Ordering::SeqCst);
+        while self.ticks.load(Ordering::SeqCst) < start + 2 {
+            std::thread::yield_now();
+        }
+    }
+}
+
+fn update_ext_porta(regs: SimulatedRegs, mode: StatusRegisterMode) {
+    // nSTATUS, CONF_DONE and INIT_DONE.
+    let porta = match mode {
+        StatusRegisterMode::PoweredOff | StatusRegisterMode::ResetPhase => 0b000,
+        StatusRegisterMode::ConfigPhase | StatusRegisterMode::Undetermined => 0b001,
+        StatusRegisterMode::InitPhase => 0b011,
+        StatusRegisterMode::UserMode => 0b111,
+    };
+    regs.set_gpio_ext_porta(GpioExtPortA::from(porta));
+}
+
+/// Step the FPGA Manager state machine once.
+fn step_fpga_manager(regs: SimulatedRegs) {
+    let ctrl = regs.ctrl();
+    let mut mode = regs.stat().mode();
+
+    if ctrl.en() == FpgaCtrlEn::FpgaManager {
+        if ctrl.nconfigpull() {
+            mode = StatusRegisterMode::ResetPhase;
+        } else if mode == StatusRegisterMode::ResetPhase {
+            mode = StatusRegisterMode::ConfigPhase;
+        }
+    }
+
+    // Data clocks move the configuration forward.
+    if regs.dclkcnt() != 0 {
+        regs.set_dclkcnt(0);
+        mode = match mode {
+            StatusRegisterMode::ConfigPhase => StatusRegisterMode::InitPhase,
+            StatusRegisterMode::InitPhase => StatusRegisterMode::UserMode,
+            m => m,
+        };
+    }
+
+    // The done status is cleared by writing 1.
+    if regs.dclkstat() != 0 {
+        regs.set_dclkstat(0);
+    }
+
+    if regs.stat().mode() != mode {
+        regs.set_mode(mode);
+        update_ext_porta(regs, mode);
+    }
+}
+
+fn simulate(
+    regs: SimulatedRegs,
+    mut last_gpo: u32,
+    running: &AtomicBool,
+    ticks: &AtomicU64,
+    mut handler: impl GpioHandler,
+) {
+    while running.load(Ordering::Relaxed) {
+        step_fpga_manager(regs);
+
+        let gpo = regs.gpo();
+        if gpo != last_gpo {
+            last_gpo = gpo;
+            regs.set_gpi(handler.gpo_changed(gpo));
+        }
+
+        ticks.fetch_add(1, Ordering::SeqCst);
+        std::thread::yield_now();
+    }
+}
+
+#[cfg(test)]
+fn wait_until(mut f: impl FnMut() -> bool) {
+    let start = std::time::Instant::now();
+    while !f() {
+        assert!(
+            start.elapsed() < std::time::Duration::from_secs(5),
+            "Timed out"
+        );
+        std::thread::yield_now();
+    }
+}
+
+#[test]
+fn gpio_handler_works() {
+    let mut soc = crate::SocFpga::simulated(|gpo: u32| !gpo);
+    assert_eq!(soc.regs().gpi(), 0xFFFF_FFFF);
+
+    soc.regs_mut().set_gpo(0x1234_5678);
+    soc.memory.sync();
+    assert_eq!(soc.regs().gpi(), 0xEDCB_A987);
+}
+
+#[test]
+fn fpga_manager_works() {
+    let mut soc = crate::SocFpga::simulated(|gpo: u32| gpo);
+    let regs = soc.regs_mut();
+    assert_eq!(regs.stat().mode(), StatusRegisterMode::UserMode);
+    assert!(regs.gpio_ext_porta().id());
+
+    regs.update_ctrl(|ctrl| {
+        ctrl.set_en(FpgaCtrlEn::FpgaManager);
+        ctrl.set_nconfigpull(true);
+    });
+    wait_until(|| soc.regs().stat().mode() == StatusRegisterMode::ResetPhase);
+    assert!(!soc.regs().gpio_ext_porta().ns());
+
+    soc.regs_mut()
+        .update_ctrl(|ctrl| ctrl.set_nconfigpull(false));
+    wait_until(|| soc.regs().stat().mode() == StatusRegisterMode::ConfigPhase);
+    assert!(soc.regs().gpio_ext_porta().ns());
+
+    soc.regs_mut().set_dclkcnt(4);
+    wait_until(|| soc.regs().stat().mode() == StatusRegisterMode::InitPhase);
+    soc.regs_mut().set_dclkcnt(0x5000);
+    wait_until(|| soc.regs().stat().mode() == StatusRegisterMode::UserMode);
+    assert!(soc.regs().gpio_ext_porta().id());
+}
@@ -6,6 +6,12 @@ use std::str::FromStr;
 use base64::prelude::*;
 use thiserror::Error;
 
+pub use writer::FceRecorder;
+
+#[cfg(feature = "movie")]
+mod movie;
+mod writer;
+
 #[derive(Debug, Error)]
 pub enum FceError {
     #[error("IO Error: {0}")]
@@ -64,6 +70,15 @@ pub enum FceError {
 
     #[error("Invalid input line for Gamepad: {0}")]
     InvalidGamepadInputLine(String),
+
+    #[error("Invalid input line for Zapper: {0}")]
+    InvalidZapperInputLine(String),
+
+    #[error("Truncated binary input record at frame {0}")]
+    TruncatedBinaryRecord(usize),
+
+    #[error("Port {0} cannot be decoded from a binary input log")]
+    UnsupportedPort(u8),
 }
 
 #[derive(Debug, Clone)]
@@ -90,8 +105,14 @@ pub struct FceHeader {
     pub subtitles: BTreeMap<u32, String>,
 }
 
+impl Default for FceHeader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FceHeader {
-    pub(crate) fn new() -> Self {
+    pub fn new() -> Self {
         Self {
             version: 0,
             emu_version: String::new(),
@@ -208,13 +229,50 @@ impl FceInputGamepad {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Zapper (light gun) input state. In text format, this is `XXX YYY B Q Z`.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct FceInputZapper {
-    x: u16,
-    y: u16,
-    mouse: bool,
-    internal: u8,
-    z: u32,
+    /// Screen coordinates the zapper points at.
+    pub x: u16,
+    pub y: u16,
+    /// Whether the trigger is pressed.
+    pub trigger: bool,
+    /// Internal light sensing state of the emulator ("bogo").
+    pub hit: u8,
+    /// Internal value of the emulator ("zaphit"), the last frame the
+    /// zapper detected light.
+    pub z: u64,
+}
+
+impl FromStr for FceInputZapper {
+    type Err = FceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || FceError::InvalidZapperInputLine(s.to_string());
+        let mut parts = s.split_whitespace();
+        let mut next = || parts.next().ok_or_else(err);
+
+        Ok(Self {
+            x: next()?.parse().map_err(|_| err())?,
+            y: next()?.parse().map_err(|_| err())?,
+            trigger: next()?.parse::<u8>().map_err(|_| err())? != 0,
+            hit: next()?.parse().map_err(|_| err())?,
+            z: next()?.parse().map_err(|_| err())?,
+        })
+    }
+}
+
+impl FceInputZapper {
+    /// Decode a zapper from its binary representation (12 bytes).
+    fn from_binary(record: &[u8]) -> Self {
+        Self {
+            x: record[0] as u16,
+            y: record[1] as u16,
+            trigger: record[2] != 0,
+            hit: record[3],
+            z: u64::from_le_bytes(record[4..12].try_into().unwrap()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -229,13 +287,7 @@ impl FceInputPort {
         match ty {
             FceInputPortType::None => FceInputPort::None,
             FceInputPortType::Gamepad => FceInputPort::Gamepad(FceInputGamepad::new()),
-            FceInputPortType::Zapper => FceInputPort::Zapper(FceInputZapper {
-                x: 0,
-                y: 0,
-                mouse: false,
-                internal: 0,
-                z: 0,
-            }),
+            FceInputPortType::Zapper => FceInputPort::Zapper(FceInputZapper::default()),
         }
     }
 
@@ -249,6 +301,13 @@ impl FceInputPort {
             _ => None,
         }
     }
+
+    pub fn as_zapper(&self) -> Option<&FceInputZapper> {
+        match self {
+            FceInputPort::Zapper(zapper) => Some(zapper),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -337,25 +396,48 @@ impl FceFrameCommandSet {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct FceFrame {
     pub commands: FceFrameCommandSet,
     pub port0: Option<FceInputPort>,
     pub port1: Option<FceInputPort>,
     pub port2: Option<FceInputPort>,
+    /// Gamepads of the 3rd and 4th players when a Four Score is used. The
+    /// first two players are on `port0` and `port1`.
+    pub port3: Option<FceInputPort>,
+    pub port4: Option<FceInputPort>,
 }
 
 impl FceFrame {
     pub fn empty(fce_header: &FceHeader) -> Self {
-        let port0 = FceInputPort::empty(fce_header.port0);
-        let port1 = FceInputPort::empty(fce_header.port1);
-        let port2 = FceInputPort::empty(fce_header.port2);
+        let port = |ty| Some(FceInputPort::empty(ty)).filter(|p| !p.is_none());
+        let fourscore = |ty| {
+            if fce_header.fourscore {
+                port(FceInputPortType::Gamepad)
+            } else {
+                port(ty)
+            }
+        };
 
         Self {
             commands: FceFrameCommandSet::new(),
-            port0: if port0.is_none() { None } else { Some(port0) },
-            port1: if port1.is_none() { None } else { Some(port1) },
-            port2: if port2.is_none() { None } else { Some(port2) },
+            port0: fourscore(fce_header.port0),
+            port1: fourscore(fce_header.port1),
+            port2: port(fce_header.port2),
+            port3: fourscore(FceInputPortType::None),
+            port4: fourscore(FceInputPortType::None),
+        }
+    }
+
+    /// The input of a player (starting at 0), regardless of whether a
+    /// Four Score is used.
+    pub fn player(&self, player: usize) -> Option<&FceInputPort> {
+        match player {
+            0 => self.port0.as_ref(),
+            1 => self.port1.as_ref(),
+            2 => self.port3.as_ref(),
+            3 => self.port4.as_ref(),
+            _ => None,
         }
     }
 }
@@ -455,7 +537,7 @@ fn parse_header_(header: &mut FceHeader, line: String) -> Result<(), FceError> {
             "subtitle" => {
                 let (frame, subtitle) = value
                     .split_once(' ')
-                    .ok_or_else(|| FceError::MissingSubtitleFrameNumber)?;
+                    .ok_or(FceError::MissingSubtitleFrameNumber)?;
                 header.subtitles.insert(
                     frame.parse().map_err(FceError::InvalidFrameNumber)?,
                     subtitle.to_string(),
@@ -483,7 +565,7 @@ fn parse_port(ty: FceInputPortType, inner: &str) -> Result<Option<FceInputPort>,
     match ty {
         FceInputPortType::None => Ok(None),
         FceInputPortType::Gamepad => Ok(Some(FceInputPort::Gamepad(inner.parse()?))),
-        FceInputPortType::Zapper => todo!("Zapper input not supported yet"),
+        FceInputPortType::Zapper => Ok(Some(FceInputPort::Zapper(inner.parse()?))),
     }
 }
 
@@ -493,19 +575,101 @@ fn parse_input_line(header: &FceHeader, line: &str) -> Result<FceFrame, FceError
         parts.next();
     }
 
-    let commands = parts.next().unwrap();
-    let port0 = parse_port(header.port0, parts.next().unwrap())?;
-    let port1 = parse_port(header.port1, parts.next().unwrap())?;
-    let port2 = parse_port(header.port2, parts.next().unwrap())?;
+    let mut next = || {
+        parts
+            .next()
+            .ok_or_else(|| FceError::InvalidInputLine(line.to_string()))
+    };
+
+    let commands = next()?
+        .parse::<u8>()
+        .map_err(|_| FceError::InvalidInputLine(line.to_string()))?;
+
+    // With a Four Score, port0 and port1 are replaced by four gamepads.
+    let (port0, port1, port3, port4) = if header.fourscore {
+        (
+            parse_port(FceInputPortType::Gamepad, next()?)?,
+            parse_port(FceInputPortType::Gamepad, next()?)?,
+            parse_port(FceInputPortType::Gamepad, next()?)?,
+            parse_port(FceInputPortType::Gamepad, next()?)?,
+        )
+    } else {
+        (
+            parse_port(header.port0, next()?)?,
+            parse_port(header.port1, next()?)?,
+            None,
+            None,
+        )
+    };
+    let port2 = parse_port(header.port2, next()?)?;
 
     Ok(FceFrame {
-        commands: FceFrameCommandSet(commands.parse::<u8>().unwrap()),
+        commands: FceFrameCommandSet(commands),
         port0,
         port1,
         port2,
+        port3,
+        port4,
     })
 }
 
+/// Size in bytes of a port in the binary input log.
+fn binary_port_size(ty: FceInputPortType) -> usize {
+    match ty {
+        FceInputPortType::None => 0,
+        FceInputPortType::Gamepad => 1,
+        // x, y, button, bogo (1 byte each) and zaphit (8 bytes).
+        FceInputPortType::Zapper => 12,
+    }
+}
+
+/// Size in bytes of a single record of the binary input log.
+fn binary_record_size(header: &FceHeader) -> usize {
+    if header.fourscore {
+        1 + 4
+    } else {
+        1 + binary_port_size(header.port0) + binary_port_size(header.port1)
+    }
+}
+
+fn parse_binary_port(ty: FceInputPortType, record: &[u8]) -> Option<FceInputPort> {
+    match ty {
+        FceInputPortType::None => None,
+        FceInputPortType::Gamepad => Some(FceInputPort::Gamepad(FceInputGamepad(record[0]))),
+        FceInputPortType::Zapper => Some(FceInputPort::Zapper(FceInputZapper::from_binary(record))),
+    }
+}
+
+/// Parse a record of the binary input log. The record is the commands byte,
+/// then either the four Four Score joysticks, or the data of port 0 and 1.
+/// Port 2 is never stored in the binary format.
+fn parse_binary_record(header: &FceHeader, record: &[u8]) -> FceFrame {
+    let commands = FceFrameCommandSet(record[0]);
+    let record = &record[1..];
+
+    if header.fourscore {
+        let gamepad = |i: usize| Some(FceInputPort::Gamepad(FceInputGamepad(record[i])));
+        FceFrame {
+            commands,
+            port0: gamepad(0),
+            port1: gamepad(1),
+            port2: None,
+            port3: gamepad(2),
+            port4: gamepad(3),
+        }
+    } else {
+        FceFrame {
+            commands,
+            port0: parse_binary_port(header.port0, record),
+            port1: parse_binary_port(header.port1, &record[binary_port_size(header.port0)..]),
+            port2: None,
+            port3: None,
+            port4: None,
+        }
+    }
+}
+
+#[derive(Default)]
 pub struct FceFrameInputs(Vec<FceFrame>);
 
 impl FceFrameInputs {
@@ -533,7 +697,22 @@ impl FceFile {
         let mut inputs = Vec::with_capacity(1024);
 
         if header.binary {
-            todo!("Binary FCE format not supported yet");
+            // The binary format has no room for the expansion port.
+            if header.port2 != FceInputPortType::None {
+                return Err(FceError::UnsupportedPort(2));
+            }
+
+            let mut data = Vec::new();
+            input.read_to_end(&mut data)?;
+
+            let record_size = binary_record_size(&header);
+            let chunks = data.chunks(record_size);
+            for (frame, record) in chunks.enumerate() {
+                if record.len() != record_size {
+                    return Err(FceError::TruncatedBinaryRecord(frame));
+                }
+                inputs.push(parse_binary_record(&header, record));
+            }
         } else {
             for line in input.lines() {
                 let line = line?;
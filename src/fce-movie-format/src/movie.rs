@@ -0,0 +1,105 @@
+//! Implementation of the generic [`Movie`] trait for FM2 files.
+use one_fpga::inputs::gamepad::ButtonSet;
+use one_fpga::inputs::Button;
+use one_fpga::movie::{Movie, MovieCommand, MovieFrame, MovieMetadata, RomChecksum};
+
+use crate::{FceFile, FceFrame, FceFrameCommand, FceInputButton, FceInputGamepad};
+
+impl From<FceInputButton> for Button {
+    fn from(value: FceInputButton) -> Self {
+        match value {
+            FceInputButton::A => Button::A,
+            FceInputButton::B => Button::B,
+            FceInputButton::Select => Button::Back,
+            FceInputButton::Start => Button::Start,
+            FceInputButton::Up => Button::DPadUp,
+            FceInputButton::Down => Button::DPadDown,
+            FceInputButton::Left => Button::DPadLeft,
+            FceInputButton::Right => Button::DPadRight,
+        }
+    }
+}
+
+impl From<FceInputGamepad> for ButtonSet {
+    fn from(value: FceInputGamepad) -> Self {
+        let mut set = ButtonSet::new();
+        for button in value.buttons() {
+            set.insert(button.into());
+        }
+        set
+    }
+}
+
+impl From<FceFrameCommand> for MovieCommand {
+    fn from(value: FceFrameCommand) -> Self {
+        match value {
+            FceFrameCommand::SoftReset => MovieCommand::SoftReset,
+            FceFrameCommand::HardReset => MovieCommand::PowerCycle,
+            FceFrameCommand::FdsDiskInsert => MovieCommand::FdsDiskInsert,
+            FceFrameCommand::FdsDiskEject => MovieCommand::FdsDiskSelect,
+            FceFrameCommand::VsInsertCoin => MovieCommand::VsInsertCoin,
+        }
+    }
+}
+
+impl FceFile {
+    fn movie_frame(&self, frame: &FceFrame) -> MovieFrame {
+        let commands = [
+            FceFrameCommand::SoftReset,
+            FceFrameCommand::HardReset,
+            FceFrameCommand::FdsDiskInsert,
+            FceFrameCommand::FdsDiskEject,
+            FceFrameCommand::VsInsertCoin,
+        ]
+        .into_iter()
+        .filter(|c| frame.commands.has(*c))
+        .map(MovieCommand::from)
+        .collect();
+
+        let ports = (0..self.ports())
+            .map(|p| {
+                frame
+                    .player(p)
+                    .and_then(|p| p.as_gamepad())
+                    .map(|g| ButtonSet::from(*g))
+            })
+            .collect();
+
+        MovieFrame { commands, ports }
+    }
+}
+
+impl Movie for FceFile {
+    fn metadata(&self) -> MovieMetadata {
+        let header = &self.header;
+        MovieMetadata {
+            system: Some(if header.fds { "FDS" } else { "NES" }.to_string()),
+            emulator: Some(format!("FCEUX {}", header.emu_version)),
+            author: header
+                .comments
+                .get("author")
+                .and_then(|a| a.first())
+                .cloned(),
+            rom_filename: Some(header.rom_filename.clone()).filter(|f| !f.is_empty()),
+            rom_checksum: Some(RomChecksum::Md5(header.rom_checksum)),
+            rerecord_count: header.rerecord_count.map(u64::from),
+            pal: header.pal,
+        }
+    }
+
+    fn ports(&self) -> usize {
+        if self.header.fourscore {
+            4
+        } else {
+            2
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.inputs.0.len()
+    }
+
+    fn frames(&self) -> Box<dyn Iterator<Item = MovieFrame> + '_> {
+        Box::new(self.inputs.iter().map(|f| self.movie_frame(f)))
+    }
+}
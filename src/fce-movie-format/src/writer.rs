@@ -0,0 +1,272 @@
+//! Serialization of FM2 files. Files are always written as version 3, which
+//! is the version FCEUX writes and reads.
+use std::io::Write;
+
+use base64::prelude::*;
+
+use crate::{
+    FceError, FceFile, FceFrame, FceHeader, FceInputGamepad, FceInputPort, FceInputPortType,
+    FceInputZapper,
+};
+
+fn port_type_value(ty: FceInputPortType) -> u8 {
+    match ty {
+        FceInputPortType::None => 0,
+        FceInputPortType::Gamepad => 1,
+        FceInputPortType::Zapper => 2,
+    }
+}
+
+fn format_guid(guid: &[u8; 16]) -> String {
+    let hex = hex::encode_upper(guid);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+pub(crate) fn write_header(header: &FceHeader, output: &mut impl Write) -> Result<(), FceError> {
+    writeln!(output, "version 3")?;
+    if !header.emu_version.is_empty() {
+        writeln!(output, "emuVersion {}", header.emu_version)?;
+    }
+    if let Some(rerecord_count) = header.rerecord_count {
+        writeln!(output, "rerecordCount {}", rerecord_count)?;
+    }
+    writeln!(output, "palFlag {}", header.pal as u8)?;
+    if !header.rom_filename.is_empty() {
+        writeln!(output, "romFilename {}", header.rom_filename)?;
+    }
+    writeln!(
+        output,
+        "romChecksum base64:{}",
+        BASE64_STANDARD.encode(header.rom_checksum)
+    )?;
+    writeln!(output, "guid {}", format_guid(&header.guid))?;
+    writeln!(output, "fourscore {}", header.fourscore as u8)?;
+    writeln!(output, "microphone {}", header.microphone as u8)?;
+    writeln!(output, "port0 {}", port_type_value(header.port0))?;
+    writeln!(output, "port1 {}", port_type_value(header.port1))?;
+    writeln!(output, "port2 {}", port_type_value(header.port2))?;
+    writeln!(output, "FDS {}", header.fds as u8)?;
+    writeln!(output, "NewPPU {}", header.new_ppu as u8)?;
+    if header.binary {
+        writeln!(output, "binary 1")?;
+    }
+    if let Some(length) = header.length {
+        writeln!(output, "length {}", length)?;
+    }
+
+    for (subject, comments) in &header.comments {
+        for comment in comments {
+            if subject.is_empty() {
+                // The subject is the first word of the comment, so leave it
+                // empty for the reader to find the text after it.
+                writeln!(output, "comment  {}", comment)?;
+            } else {
+                writeln!(output, "comment {} {}", subject, comment)?;
+            }
+        }
+    }
+    for (frame, subtitle) in &header.subtitles {
+        writeln!(output, "subtitle {} {}", frame, subtitle)?;
+    }
+    if let Some(savestate) = &header.savestate {
+        writeln!(output, "savestate 0x{}", hex::encode(savestate))?;
+    }
+
+    Ok(())
+}
+
+fn write_text_port(port: Option<&FceInputPort>, output: &mut impl Write) -> Result<(), FceError> {
+    match port {
+        None | Some(FceInputPort::None) => {}
+        Some(FceInputPort::Gamepad(gamepad)) => write!(output, "{}", gamepad)?,
+        Some(FceInputPort::Zapper(zapper)) => write!(output, "{}", zapper)?,
+    }
+    write!(output, "|")?;
+    Ok(())
+}
+
+pub(crate) fn write_text_frame(
+    header: &FceHeader,
+    frame: &FceFrame,
+    output: &mut impl Write,
+) -> Result<(), FceError> {
+    write!(output, "|{}|", frame.commands.0)?;
+    if header.fourscore {
+        for port in [&frame.port0, &frame.port1, &frame.port3, &frame.port4] {
+            let gamepad = port.unwrap_or(FceInputPort::Gamepad(FceInputGamepad::new()));
+            write_text_port(Some(&gamepad), output)?;
+        }
+    } else {
+        write_text_port(frame.port0.as_ref(), output)?;
+        write_text_port(frame.port1.as_ref(), output)?;
+    }
+    write_text_port(frame.port2.as_ref(), output)?;
+    writeln!(output)?;
+    Ok(())
+}
+
+fn write_binary_port(
+    ty: FceInputPortType,
+    port: Option<&FceInputPort>,
+    output: &mut impl Write,
+) -> Result<(), FceError> {
+    match ty {
+        FceInputPortType::None => {}
+        FceInputPortType::Gamepad => {
+            let gamepad = port
+                .and_then(|p| p.as_gamepad())
+                .copied()
+                .unwrap_or_default();
+            output.write_all(&[gamepad.0])?;
+        }
+        FceInputPortType::Zapper => {
+            let zapper = port
+                .and_then(|p| p.as_zapper())
+                .copied()
+                .unwrap_or_default();
+            output.write_all(&[
+                zapper.x as u8,
+                zapper.y as u8,
+                zapper.trigger as u8,
+                zapper.hit,
+            ])?;
+            output.write_all(&zapper.z.to_le_bytes())?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn write_binary_frame(
+    header: &FceHeader,
+    frame: &FceFrame,
+    output: &mut impl Write,
+) -> Result<(), FceError> {
+    output.write_all(&[frame.commands.0])?;
+    if header.fourscore {
+        for port in [&frame.port0, &frame.port1, &frame.port3, &frame.port4] {
+            write_binary_port(FceInputPortType::Gamepad, port.as_ref(), output)?;
+        }
+    } else {
+        write_binary_port(header.port0, frame.port0.as_ref(), output)?;
+        write_binary_port(header.port1, frame.port1.as_ref(), output)?;
+    }
+    Ok(())
+}
+
+impl FceFile {
+    /// Create a movie without any frames.
+    pub fn new(header: FceHeader) -> Self {
+        Self {
+            header,
+            inputs: Default::default(),
+        }
+    }
+
+    /// Add a frame at the end of the movie.
+    pub fn push(&mut self, frame: FceFrame) {
+        self.inputs.0.push(frame);
+    }
+
+    /// Write the movie, in binary or text depending on the header. The
+    /// `length` header is updated to the number of frames for binary files.
+    pub fn save_stream(&self, mut output: impl Write) -> Result<(), FceError> {
+        let mut header = self.header.clone();
+        if header.binary {
+            header.length = Some(self.inputs.0.len() as u32);
+        }
+
+        let mut recorder = FceRecorder::new(header, &mut output)?;
+        for frame in self.frames() {
+            recorder.record(frame)?;
+        }
+        recorder.finish()?;
+        Ok(())
+    }
+}
+
+/// Records a movie incrementally, writing every frame as it is recorded.
+/// Dropping the recorder without calling [`FceRecorder::finish`] leaves the
+/// output unflushed.
+pub struct FceRecorder<W: Write> {
+    header: FceHeader,
+    output: W,
+    frame_count: u32,
+}
+
+impl<W: Write> FceRecorder<W> {
+    /// Create a new recorder and write the header to the output. The input
+    /// log starts right after. The `length` header is optional and should
+    /// be left empty if the number of frames is not known in advance.
+    pub fn new(header: FceHeader, mut output: W) -> Result<Self, FceError> {
+        write_header(&header, &mut output)?;
+        if header.binary {
+            // The binary input log starts right after the `|` marker.
+            output.write_all(b"|")?;
+        }
+
+        Ok(Self {
+            header,
+            output,
+            frame_count: 0,
+        })
+    }
+
+    pub fn header(&self) -> &FceHeader {
+        &self.header
+    }
+
+    /// Number of frames recorded so far.
+    pub fn frame_count(&self) -> u32 {
+        self.frame_count
+    }
+
+    /// Create an empty frame matching the ports of this recording.
+    pub fn empty_frame(&self) -> FceFrame {
+        FceFrame::empty(&self.header)
+    }
+
+    /// Record a single frame.
+    pub fn record(&mut self, frame: &FceFrame) -> Result<(), FceError> {
+        if self.header.binary {
+            write_binary_frame(&self.header, frame, &mut self.output)?;
+        } else {
+            write_text_frame(&self.header, frame, &mut self.output)?;
+        }
+        self.frame_count += 1;
+        Ok(())
+    }
+
+    /// Flush the output and return it.
+    pub fn finish(mut self) -> Result<W, FceError> {
+        self.output.flush()?;
+        Ok(self.output)
+    }
+}
+
+impl std::fmt::Display for FceInputGamepad {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // RLDUTSBA, from the most significant bit.
+        for (i, c) in "RLDUTSBA".chars().enumerate() {
+            let pressed = self.0 & (0x80 >> i) != 0;
+            write!(f, "{}", if pressed { c } else { '.' })?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for FceInputZapper {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {}",
+            self.x, self.y, self.trigger as u8, self.hit, self.z
+        )
+    }
+}
@@ -0,0 +1,81 @@
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use fce_movie_format::{FceError, FceFile, FceFrameCommand, FceInputButton};
+
+fn load(name: &str) -> FceFile {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/assets")
+        .join(name);
+    let file = std::fs::File::open(path).unwrap();
+    FceFile::load_stream(BufReader::new(file)).unwrap()
+}
+
+#[test]
+fn binary_matches_text() {
+    let text = load("text.fm2");
+    let binary = load("binary.fm2");
+
+    assert!(!text.header.binary);
+    assert!(binary.header.binary);
+    assert_eq!(binary.header.length, Some(300));
+    assert_eq!(text.header.rom_checksum, binary.header.rom_checksum);
+    assert_eq!(text.header.guid, binary.header.guid);
+    assert_eq!(text.header.subtitles, binary.header.subtitles);
+    assert_eq!(text.header.comments, binary.header.comments);
+
+    assert_eq!(text.frames().count(), 300);
+    assert_eq!(
+        text.frames().collect::<Vec<_>>(),
+        binary.frames().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn binary_frames() {
+    let binary = load("binary.fm2");
+    let frames = binary.frames().collect::<Vec<_>>();
+
+    assert!(frames[0].commands.has(FceFrameCommand::HardReset));
+    assert!(frames[150].commands.has(FceFrameCommand::SoftReset));
+    assert!(!frames[151].commands.has(FceFrameCommand::SoftReset));
+
+    // Frame 1 is `..D.TSB.|........`.
+    let p0 = frames[1].port0.unwrap();
+    let p0 = p0.as_gamepad().unwrap();
+    assert_eq!(
+        p0.buttons(),
+        vec![
+            FceInputButton::B,
+            FceInputButton::Select,
+            FceInputButton::Start,
+            FceInputButton::Down
+        ]
+    );
+    assert!(frames[1]
+        .port1
+        .unwrap()
+        .as_gamepad()
+        .unwrap()
+        .buttons()
+        .is_empty());
+    assert!(frames[1].port2.is_none());
+}
+
+#[test]
+fn binary_truncated() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/assets/binary.fm2");
+    let mut data = std::fs::read(path).unwrap();
+    data.pop();
+
+    let result = FceFile::load_stream(data.as_slice());
+    assert!(matches!(result, Err(FceError::TruncatedBinaryRecord(299))));
+}
+
+#[test]
+fn binary_expansion_port() {
+    let movie = b"version 3\nemuVersion 22020\nport0 1\nport1 0\nport2 1\nbinary 1\n|\x00\x01";
+
+    let result = FceFile::load_stream(movie.as_slice());
+    assert!(matches!(result, Err(FceError::UnsupportedPort(2))));
+}
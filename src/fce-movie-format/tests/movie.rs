@@ -0,0 +1,37 @@
+#![cfg(feature = "movie")]
+
+use one_fpga::inputs::Button;
+use one_fpga::movie::{Movie, MovieCommand, RomChecksum};
+
+use fce_movie_format::FceFile;
+
+#[test]
+fn movie_frames() {
+    let movie = "version 3\nemuVersion 22020\nromFilename Test\n\
+        romChecksum base64:jjYwGG411HcjG/j9UOVM3Q==\nport0 1\nport1 1\nport2 0\n\
+        comment author golem\n\
+        |2|.......A|........||\n\
+        |0|R...T...|..D....A||\n";
+    let fm = FceFile::load_stream(movie.as_bytes()).unwrap();
+    let movie: &dyn Movie = &fm;
+
+    assert_eq!(movie.ports(), 2);
+    assert_eq!(movie.len(), 2);
+
+    let metadata = movie.metadata();
+    assert_eq!(metadata.author.as_deref(), Some("golem"));
+    assert_eq!(metadata.rom_filename.as_deref(), Some("Test"));
+    assert!(matches!(metadata.rom_checksum, Some(RomChecksum::Md5(_))));
+
+    let frames = movie.frames().collect::<Vec<_>>();
+    assert!(frames[0].has(MovieCommand::PowerCycle));
+    assert!(frames[0].ports[0].unwrap().contains(Button::A));
+
+    let p0 = frames[1].ports[0].unwrap();
+    assert!(p0.contains(Button::DPadRight));
+    assert!(p0.contains(Button::Start));
+    assert!(!p0.contains(Button::A));
+    let p1 = frames[1].ports[1].unwrap();
+    assert!(p1.contains(Button::DPadDown));
+    assert!(p1.contains(Button::A));
+}
@@ -0,0 +1,102 @@
+use fce_movie_format::{FceFile, FceInputButton, FceInputPortType, FceInputZapper};
+
+const HEADER: &str = "version 3\nemuVersion 22020\nromFilename Test\nport2 0\n";
+
+#[test]
+fn zapper_text() {
+    let movie = format!(
+        "{HEADER}port0 1\nport1 2\n\
+         |0|.......A|  12 200 1 3 45||\n\
+         |0|........|255 239 0 0 0||\n"
+    );
+    let fm = FceFile::load_stream(movie.as_bytes()).unwrap();
+    assert_eq!(fm.header.port1, FceInputPortType::Zapper);
+
+    let frames = fm.frames().collect::<Vec<_>>();
+    assert_eq!(
+        frames[0].port1.unwrap().as_zapper(),
+        Some(&FceInputZapper {
+            x: 12,
+            y: 200,
+            trigger: true,
+            hit: 3,
+            z: 45,
+        })
+    );
+    assert_eq!(
+        frames[1].port1.unwrap().as_zapper(),
+        Some(&FceInputZapper {
+            x: 255,
+            y: 239,
+            trigger: false,
+            hit: 0,
+            z: 0,
+        })
+    );
+}
+
+#[test]
+fn zapper_binary() {
+    let mut movie = format!("{HEADER}port0 2\nport1 0\nbinary 1\n|").into_bytes();
+    movie.extend_from_slice(&[0, 10, 20, 1, 2, 0x01, 0x02, 0, 0, 0, 0, 0, 0]);
+
+    let fm = FceFile::load_stream(movie.as_slice()).unwrap();
+    let frames = fm.frames().collect::<Vec<_>>();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(
+        frames[0].port0.unwrap().as_zapper(),
+        Some(&FceInputZapper {
+            x: 10,
+            y: 20,
+            trigger: true,
+            hit: 2,
+            z: 0x0201,
+        })
+    );
+    assert!(frames[0].port1.is_none());
+}
+
+#[test]
+fn fourscore_text() {
+    let movie = format!(
+        "{HEADER}port0 1\nport1 1\nfourscore 1\n\
+         |0|.......A|......B.|....T...|R.......||\n"
+    );
+    let fm = FceFile::load_stream(movie.as_bytes()).unwrap();
+    let frame = fm.frames().next().unwrap();
+
+    let buttons = (0..4)
+        .map(|p| frame.player(p).unwrap().as_gamepad().unwrap().buttons())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        buttons,
+        vec![
+            vec![FceInputButton::A],
+            vec![FceInputButton::B],
+            vec![FceInputButton::Start],
+            vec![FceInputButton::Right],
+        ]
+    );
+    assert!(frame.port2.is_none());
+}
+
+#[test]
+fn fourscore_binary() {
+    let mut movie = format!("{HEADER}port0 1\nport1 1\nfourscore 1\nbinary 1\n|").into_bytes();
+    movie.extend_from_slice(&[1, 0x01, 0x02, 0x08, 0x80]);
+
+    let fm = FceFile::load_stream(movie.as_slice()).unwrap();
+    let frame = fm.frames().next().unwrap();
+    assert!(frame
+        .port3
+        .unwrap()
+        .as_gamepad()
+        .unwrap()
+        .has(FceInputButton::Start));
+    assert!(frame
+        .port4
+        .unwrap()
+        .as_gamepad()
+        .unwrap()
+        .has(FceInputButton::Right));
+}
@@ -0,0 +1,139 @@
+use std::io::BufReader;
+use std::path::PathBuf;
+
+use fce_movie_format::{
+    FceFile, FceFrameCommand, FceHeader, FceInputButton, FceInputPort, FceInputPortType,
+    FceInputZapper, FceRecorder,
+};
+
+fn load(name: &str) -> FceFile {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/assets")
+        .join(name);
+    let file = std::fs::File::open(path).unwrap();
+    FceFile::load_stream(BufReader::new(file)).unwrap()
+}
+
+fn round_trip(fm: &FceFile) -> FceFile {
+    let mut output = Vec::new();
+    fm.save_stream(&mut output).unwrap();
+    FceFile::load_stream(output.as_slice()).unwrap()
+}
+
+fn assert_same(a: &FceFile, b: &FceFile) {
+    assert_eq!(a.header.rerecord_count, b.header.rerecord_count);
+    assert_eq!(a.header.rom_filename, b.header.rom_filename);
+    assert_eq!(a.header.rom_checksum, b.header.rom_checksum);
+    assert_eq!(a.header.guid, b.header.guid);
+    assert_eq!(a.header.comments, b.header.comments);
+    assert_eq!(a.header.subtitles, b.header.subtitles);
+    assert_eq!(a.header.savestate, b.header.savestate);
+    assert_eq!(a.header.binary, b.header.binary);
+    assert_eq!(
+        a.frames().collect::<Vec<_>>(),
+        b.frames().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn round_trip_text() {
+    let fm = load("text.fm2");
+    assert_same(&fm, &round_trip(&fm));
+}
+
+#[test]
+fn round_trip_text_is_identical() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/assets/text.fm2");
+    let original = std::fs::read_to_string(path).unwrap();
+
+    let mut output = Vec::new();
+    load("text.fm2").save_stream(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+
+    // Only the header key order differs, frames are written the same way.
+    let frames = |s: &str| {
+        s.lines()
+            .filter(|l| l.starts_with('|'))
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(frames(&original), frames(&output));
+}
+
+#[test]
+fn round_trip_binary() {
+    let fm = load("binary.fm2");
+    let copy = round_trip(&fm);
+    assert_same(&fm, &copy);
+    assert_eq!(copy.header.length, Some(300));
+}
+
+#[test]
+fn record() {
+    let mut header = FceHeader::new();
+    header.emu_version = "22020".to_string();
+    header.rom_filename = "Duck Hunt".to_string();
+    header.rom_checksum = [0xAB; 16];
+    header.guid = [0x12; 16];
+    header.port0 = FceInputPortType::Gamepad;
+    header.port1 = FceInputPortType::Zapper;
+    header.savestate = Some(vec![1, 2, 3]);
+    header
+        .comments
+        .entry("author".to_string())
+        .or_default()
+        .push("golem".to_string());
+
+    let mut recorder = FceRecorder::new(header, Vec::new()).unwrap();
+
+    let mut frame = recorder.empty_frame();
+    frame.commands.set(FceFrameCommand::HardReset);
+    recorder.record(&frame).unwrap();
+
+    let mut frame = recorder.empty_frame();
+    if let Some(FceInputPort::Gamepad(gamepad)) = &mut frame.port0 {
+        gamepad.set(FceInputButton::Start);
+    }
+    frame.port1 = Some(FceInputPort::Zapper(FceInputZapper {
+        x: 128,
+        y: 64,
+        trigger: true,
+        hit: 1,
+        z: 12345,
+    }));
+    recorder.record(&frame).unwrap();
+    assert_eq!(recorder.frame_count(), 2);
+
+    let output = recorder.finish().unwrap();
+    let text = String::from_utf8(output.clone()).unwrap();
+    assert!(text.starts_with("version 3\nemuVersion 22020\n"));
+    assert!(text.contains("guid 12121212-1212-1212-1212-121212121212\n"));
+    assert!(text.contains("comment author golem\n"));
+    assert!(text.ends_with("|2|........|0 0 0 0 0||\n|0|....T...|128 64 1 1 12345||\n"));
+
+    let fm = FceFile::load_stream(output.as_slice()).unwrap();
+    assert_eq!(fm.header.rom_filename, "Duck Hunt");
+    assert_eq!(fm.header.savestate, Some(vec![1, 2, 3]));
+    let frames = fm.frames().collect::<Vec<_>>();
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[1], &frame);
+}
+
+#[test]
+fn round_trip_comments() {
+    let mut header = FceHeader::new();
+    for (subject, comment) in [("author", "golem"), ("", "a comment without subject")] {
+        header
+            .comments
+            .entry(subject.to_string())
+            .or_default()
+            .push(comment.to_string());
+    }
+
+    let mut recorder = FceRecorder::new(header.clone(), Vec::new()).unwrap();
+    recorder.record(&recorder.empty_frame()).unwrap();
+
+    let output = recorder.finish().unwrap();
+    let fm = FceFile::load_stream(output.as_slice()).unwrap();
+    assert_eq!(fm.header.comments, header.comments);
+}
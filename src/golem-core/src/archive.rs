@@ -0,0 +1,218 @@
+//! Access to files inside zip and 7z archives. Archives are seen as
+//! directories, so a path like `games/NES/set.zip/Mario.nes` points to the
+//! file `Mario.nes` inside the `set.zip` archive.
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// The kind of archive, from its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    SevenZip,
+}
+
+impl ArchiveKind {
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?;
+        if ext.eq_ignore_ascii_case("zip") {
+            Some(Self::Zip)
+        } else if ext.eq_ignore_ascii_case("7z") {
+            Some(Self::SevenZip)
+        } else {
+            None
+        }
+    }
+}
+
+/// An entry directly inside a directory of an archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+}
+
+/// Whether the path is an archive file on the file system.
+pub fn is_archive(path: impl AsRef<Path>) -> bool {
+    let path = path.as_ref();
+    ArchiveKind::from_path(path).is_some() && path.is_file()
+}
+
+/// Split a path that goes through an archive into the path of the archive
+/// and the path inside it (empty for the archive itself). Returns `None` if
+/// the path does not go through an archive.
+pub fn split_path(path: impl AsRef<Path>) -> Option<(PathBuf, PathBuf)> {
+    let path = path.as_ref();
+    let archive = path.ancestors().find(|p| is_archive(p))?;
+    let inner = path.strip_prefix(archive).ok()?;
+    Some((archive.to_path_buf(), inner.to_path_buf()))
+}
+
+/// The name of an inner path, as stored in archives.
+fn inner_name(inner: &Path) -> String {
+    inner
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// List the entries directly under a directory of an archive. Directories
+/// that are only implied by the path of files are listed too.
+pub fn list(archive: impl AsRef<Path>, inner: impl AsRef<Path>) -> io::Result<Vec<ArchiveEntry>> {
+    let archive = archive.as_ref();
+    let kind = ArchiveKind::from_path(archive)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Not an archive"))?;
+
+    // All the (name, is_dir, size) in the archive.
+    let files: Vec<(String, bool, u64)> = match kind {
+        ArchiveKind::Zip => {
+            let mut zip = zip::ZipArchive::new(File::open(archive)?).map_err(io::Error::from)?;
+            let mut files = Vec::with_capacity(zip.len());
+            for i in 0..zip.len() {
+                let file = zip.by_index(i).map_err(io::Error::from)?;
+                files.push((file.name().to_string(), file.is_dir(), file.size()));
+            }
+            files
+        }
+        ArchiveKind::SevenZip => sevenz_rust::Archive::open(archive)
+            .map_err(|e| io::Error::other(e.to_string()))?
+            .files
+            .iter()
+            .map(|f| (f.name().to_string(), f.is_directory(), f.size()))
+            .collect(),
+    };
+
+    let prefix = inner_name(inner.as_ref());
+    let mut entries = BTreeMap::new();
+    for (name, is_dir, size) in files {
+        let name = name.trim_end_matches('/');
+        let rest = if prefix.is_empty() {
+            name
+        } else {
+            match name
+                .strip_prefix(prefix.as_str())
+                .and_then(|r| r.strip_prefix('/'))
+            {
+                Some(rest) => rest,
+                None => continue,
+            }
+        };
+
+        let entry = match rest.split_once('/') {
+            Some((dir, _)) => ArchiveEntry {
+                name: dir.to_string(),
+                is_dir: true,
+                size: 0,
+            },
+            None if !rest.is_empty() => ArchiveEntry {
+                name: rest.to_string(),
+                is_dir,
+                size,
+            },
+            None => continue,
+        };
+        entries.entry(entry.name.clone()).or_insert(entry);
+    }
+
+    Ok(entries.into_values().collect())
+}
+
+/// Read a file inside an archive.
+pub fn read(archive: impl AsRef<Path>, inner: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    let archive = archive.as_ref();
+    let name = inner_name(inner.as_ref());
+    let not_found = || io::Error::new(io::ErrorKind::NotFound, format!("{name} not in archive"));
+
+    match ArchiveKind::from_path(archive) {
+        Some(ArchiveKind::Zip) => {
+            let mut zip = zip::ZipArchive::new(File::open(archive)?).map_err(io::Error::from)?;
+            let mut file = zip.by_name(&name).map_err(|_| not_found())?;
+            let mut data = Vec::with_capacity(file.size() as usize);
+            file.read_to_end(&mut data)?;
+            Ok(data)
+        }
+        Some(ArchiveKind::SevenZip) => {
+            let mut reader =
+                sevenz_rust::SevenZReader::open(archive, sevenz_rust::Password::empty())
+                    .map_err(|e| io::Error::other(e.to_string()))?;
+
+            // Entries can be in solid blocks, so the ones before the file
+            // need to be decompressed too.
+            let mut data = None;
+            reader
+                .for_each_entries(|entry, reader| {
+                    if data.is_some() {
+                        return Ok(false);
+                    }
+                    if entry.name() == name {
+                        let mut buffer = Vec::with_capacity(entry.size() as usize);
+                        reader.read_to_end(&mut buffer)?;
+                        data = Some(buffer);
+                        return Ok(false);
+                    }
+                    io::copy(reader, &mut io::sink())?;
+                    Ok(true)
+                })
+                .map_err(|e| io::Error::other(e.to_string()))?;
+            data.ok_or_else(not_found)
+        }
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Not an archive",
+        )),
+    }
+}
+
+/// Read a file, which can be on the file system or inside an archive.
+pub fn read_path(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    match split_path(path.as_ref()) {
+        Some((archive, inner)) => read(archive, inner),
+        None => std::fs::read(path),
+    }
+}
+
+#[test]
+fn zip_as_directory() {
+    use std::io::Write;
+
+    let root = tempdir::TempDir::new("archive").unwrap();
+    let archive = root.path().join("set.ZIP");
+    let mut zip = zip::ZipWriter::new(File::create(&archive).unwrap());
+    let options = zip::write::FileOptions::default();
+    zip.start_file("Mario.nes", options).unwrap();
+    zip.write_all(b"mario").unwrap();
+    zip.start_file("hacks/Luigi.nes", options).unwrap();
+    zip.write_all(b"luigi!").unwrap();
+    zip.finish().unwrap();
+
+    let path = archive.join("hacks/Luigi.nes");
+    assert_eq!(
+        split_path(&path),
+        Some((archive.clone(), PathBuf::from("hacks/Luigi.nes")))
+    );
+    assert_eq!(split_path(root.path().join("Mario.nes")), None);
+
+    assert_eq!(
+        list(&archive, "").unwrap(),
+        vec![
+            ArchiveEntry {
+                name: "Mario.nes".to_string(),
+                is_dir: false,
+                size: 5,
+            },
+            ArchiveEntry {
+                name: "hacks".to_string(),
+                is_dir: true,
+                size: 0,
+            },
+        ]
+    );
+    assert_eq!(list(&archive, "hacks").unwrap().len(), 1);
+
+    assert_eq!(read_path(&path).unwrap(), b"luigi!");
+    assert!(read_path(archive.join("Zelda.nes")).is_err());
+}
@@ -6,11 +6,11 @@ use std::time::SystemTime;
 
 use image::DynamicImage;
 
-pub use bios::Bios;
+pub use bios::{Bios, BiosRequirement};
 pub use null::NullCore;
 pub use rom::Rom;
 
-use crate::inputs::{gamepad, keyboard};
+use crate::inputs::{gamepad, keyboard, mouse};
 
 pub mod bios;
 pub mod null;
@@ -21,6 +21,22 @@ pub mod rom;
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct ConfigMenuId(u32);
 
+impl ConfigMenuId {
+    pub fn new(id: u32) -> Self {
+        Self(id)
+    }
+
+    pub fn as_u32(&self) -> u32 {
+        self.0
+    }
+}
+
+impl From<u32> for ConfigMenuId {
+    fn from(value: u32) -> Self {
+        Self(value)
+    }
+}
+
 /// A menu item that can be displayed in the core's menu.
 /// This is used to configure the core's settings, in an abstract
 /// way.
@@ -183,6 +199,12 @@ pub trait Core {
     /// of [`&dyn Bios`].
     fn send_bios(&mut self, bios: Bios) -> Result<(), Error>;
 
+    /// The BIOS files this core needs, to be sent with [`Core::send_bios`]
+    /// before the ROM. By default, a core does not need any.
+    fn bios_requirements(&self) -> Vec<BiosRequirement> {
+        Vec::new()
+    }
+
     /// Send a key up event to the core.
     fn key_up(&mut self, key: keyboard::Scancode) -> Result<(), Error>;
 
@@ -211,6 +233,36 @@ pub trait Core {
     /// return `None`.
     fn gamepad_buttons(&self, index: usize) -> Result<Option<gamepad::ButtonSet>, Error>;
 
+    /// Send the position of an analog axis of a gamepad. The value is the full
+    /// range of an `i16`, with 0 being the center (or released, for triggers).
+    /// Cores that do not support analog inputs can ignore this.
+    fn gamepad_axis(&mut self, index: usize, axis: gamepad::Axis, value: i16) -> Result<(), Error> {
+        let _ = index;
+        let _ = axis;
+        let _ = value;
+        Ok(())
+    }
+
+    /// Move the mouse by a relative amount. A positive `dy` moves down, and a
+    /// positive `wheel` scrolls up. Cores that do not support a mouse can
+    /// ignore this.
+    fn mouse_move(&mut self, dx: i32, dy: i32, wheel: i32) -> Result<(), Error> {
+        let _ = (dx, dy, wheel);
+        Ok(())
+    }
+
+    /// Send a mouse button up event to the core.
+    fn mouse_button_up(&mut self, button: mouse::MouseButton) -> Result<(), Error> {
+        let _ = button;
+        Ok(())
+    }
+
+    /// Send a mouse button down event to the core.
+    fn mouse_button_down(&mut self, button: mouse::MouseButton) -> Result<(), Error> {
+        let _ = button;
+        Ok(())
+    }
+
     /// Returns the menu items that the core supports. This would correspond to the
     /// top level page of config items. If the core does not support a menu, this
     /// should return an empty vector.
@@ -302,6 +354,10 @@ impl Core for GolemCore {
         unsafe { &mut *self.inner.get() }.send_bios(bios)
     }
 
+    fn bios_requirements(&self) -> Vec<BiosRequirement> {
+        unsafe { &*self.inner.get() }.bios_requirements()
+    }
+
     fn key_up(&mut self, key: keyboard::Scancode) -> Result<(), Error> {
         unsafe { &mut *self.inner.get() }.key_up(key)
     }
@@ -338,6 +394,22 @@ impl Core for GolemCore {
         unsafe { &mut *self.inner.get() }.gamepad_buttons(index)
     }
 
+    fn gamepad_axis(&mut self, index: usize, axis: gamepad::Axis, value: i16) -> Result<(), Error> {
+        unsafe { &mut *self.inner.get() }.gamepad_axis(index, axis, value)
+    }
+
+    fn mouse_move(&mut self, dx: i32, dy: i32, wheel: i32) -> Result<(), Error> {
+        unsafe { &mut *self.inner.get() }.mouse_move(dx, dy, wheel)
+    }
+
+    fn mouse_button_up(&mut self, button: mouse::MouseButton) -> Result<(), Error> {
+        unsafe { &mut *self.inner.get() }.mouse_button_up(button)
+    }
+
+    fn mouse_button_down(&mut self, button: mouse::MouseButton) -> Result<(), Error> {
+        unsafe { &mut *self.inner.get() }.mouse_button_down(button)
+    }
+
     fn menu(&self) -> Result<Vec<CoreMenuItem>, Error> {
         unsafe { &mut *self.inner.get() }.menu()
     }
@@ -1,5 +1,5 @@
 use std::io::{Cursor, Read, Seek, SeekFrom};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 /// A BIOS, including any information the core needs to know about the BIOS.
@@ -29,3 +29,172 @@ impl Seek for Bios {
         }
     }
 }
+
+/// A BIOS (or boot ROM) that a core needs to run, declared by the core. It
+/// is found by its file name, or by its checksum if it has one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BiosRequirement {
+    /// The file name of the BIOS, e.g. `boot.rom`.
+    pub name: String,
+
+    /// The CRC32 of the BIOS, if the core needs a specific dump.
+    pub crc32: Option<u32>,
+
+    /// Whether the core can run without this BIOS.
+    pub optional: bool,
+}
+
+impl BiosRequirement {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            crc32: None,
+            optional: false,
+        }
+    }
+
+    pub fn with_crc32(self, crc32: u32) -> Self {
+        Self {
+            crc32: Some(crc32),
+            ..self
+        }
+    }
+
+    pub fn with_optional(self, optional: bool) -> Self {
+        Self { optional, ..self }
+    }
+
+    /// Whether the content matches the checksum, if there is one.
+    pub fn matches(&self, data: &[u8]) -> bool {
+        self.crc32.is_none_or(|crc| crc32fast::hash(data) == crc)
+    }
+
+    /// Find the BIOS in a directory. A file with the same name (ignoring
+    /// case) is used first, then any file with the same checksum.
+    pub fn find_in(&self, dir: impl AsRef<Path>) -> std::io::Result<Option<PathBuf>> {
+        let dir = dir.as_ref();
+        if !dir.is_dir() {
+            return Ok(None);
+        }
+
+        let mut files = std::fs::read_dir(dir)?
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.is_file())
+            .collect::<Vec<_>>();
+        files.sort();
+
+        let by_name = files.iter().find(|path| {
+            path.file_name()
+                .is_some_and(|name| name.to_string_lossy().eq_ignore_ascii_case(&self.name))
+        });
+        if let Some(path) = by_name {
+            if self.matches(&std::fs::read(path)?) {
+                return Ok(Some(path.clone()));
+            }
+        }
+
+        if self.crc32.is_some() {
+            for path in &files {
+                if self.matches(&std::fs::read(path)?) {
+                    return Ok(Some(path.clone()));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Parse a list of requirements, one per line, e.g. to declare the BIOS
+    /// of a core in a file shipped with it. Each line is a file name,
+    /// optionally followed by its CRC32 in hexadecimal and `optional`. Empty
+    /// lines and lines starting with `#` are ignored.
+    ///
+    /// ```text
+    /// # The FDS BIOS of the NES core.
+    /// disksys.rom 5E607DCF
+    /// ```
+    pub fn parse_list(list: &str) -> Result<Vec<Self>, String> {
+        list.lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(str::parse)
+            .collect()
+    }
+}
+
+impl std::str::FromStr for BiosRequirement {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let mut requirement = match words.next() {
+            Some(name) => Self::new(name),
+            None => return Err("Missing BIOS file name.".to_string()),
+        };
+
+        for word in words {
+            if word.eq_ignore_ascii_case("optional") {
+                requirement.optional = true;
+            } else if requirement.crc32.is_none() {
+                let crc = u32::from_str_radix(word, 16)
+                    .map_err(|_| format!("Invalid CRC32 {word:?} for {}.", requirement.name))?;
+                requirement.crc32 = Some(crc);
+            } else {
+                return Err(format!("Invalid BIOS requirement {s:?}."));
+            }
+        }
+        Ok(requirement)
+    }
+}
+
+impl std::fmt::Display for BiosRequirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.crc32 {
+            Some(crc) => write!(f, "{} (CRC32 {:08X})", self.name, crc),
+            None => f.write_str(&self.name),
+        }
+    }
+}
+
+#[test]
+fn find_bios() {
+    let dir = tempdir::TempDir::new("bios").unwrap();
+    std::fs::write(dir.path().join("BOOT.ROM"), b"boot").unwrap();
+    std::fs::write(dir.path().join("other.bin"), b"other").unwrap();
+
+    let boot = BiosRequirement::new("boot.rom");
+    assert_eq!(
+        boot.find_in(dir.path()).unwrap(),
+        Some(dir.path().join("BOOT.ROM"))
+    );
+
+    // A checksum that does not match the name falls back to other files.
+    let other = boot.clone().with_crc32(crc32fast::hash(b"other"));
+    assert_eq!(
+        other.find_in(dir.path()).unwrap(),
+        Some(dir.path().join("other.bin"))
+    );
+    assert_eq!(
+        other.to_string(),
+        format!("boot.rom (CRC32 {:08X})", crc32fast::hash(b"other"))
+    );
+
+    let missing = BiosRequirement::new("bios.bin").with_crc32(0);
+    assert_eq!(missing.find_in(dir.path()).unwrap(), None);
+    assert_eq!(boot.find_in(dir.path().join("none")).unwrap(), None);
+}
+
+#[test]
+fn parse_bios_requirements() {
+    let list = "# Comment\n\nboot.rom optional\n  disksys.rom 5E607DCF\n";
+    assert_eq!(
+        BiosRequirement::parse_list(list).unwrap(),
+        vec![
+            BiosRequirement::new("boot.rom").with_optional(true),
+            BiosRequirement::new("disksys.rom").with_crc32(0x5E60_7DCF),
+        ]
+    );
+
+    assert!(BiosRequirement::parse_list("bios.bin xyz").is_err());
+    assert!(BiosRequirement::parse_list("bios.bin 1234 5678").is_err());
+}
@@ -1,5 +1,7 @@
 use std::io::Cursor;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use crate::archive;
 
 /// A ROM, including any information the core needs to know about the ROM.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -10,3 +12,25 @@ pub enum Rom {
     /// A ROM that is stored in a file on the file system.
     File(PathBuf),
 }
+
+impl Rom {
+    /// Create a ROM from a path. Files inside archives are read in memory.
+    pub fn from_path(path: impl Into<PathBuf>) -> std::io::Result<Self> {
+        let path = path.into();
+        match archive::split_path(&path) {
+            Some((archive, inner)) => {
+                let data = archive::read(archive, inner)?;
+                Ok(Self::Memory(Some(path), Cursor::new(data)))
+            }
+            None => Ok(Self::File(path)),
+        }
+    }
+
+    /// The path the ROM was read from, if any.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Memory(path, _) => path.as_deref(),
+            Self::File(path) => Some(path),
+        }
+    }
+}
@@ -131,6 +131,11 @@ impl ButtonSet {
     pub fn remove(&mut self, button: Button) {
         self.0 &= !(1 << button.as_repr());
     }
+
+    /// Iterate over the pressed buttons.
+    pub fn iter(&self) -> impl Iterator<Item = Button> + '_ {
+        Button::iter().filter(|b| self.contains(*b))
+    }
 }
 
 impl std::fmt::Debug for ButtonSet {
@@ -152,6 +157,19 @@ impl std::fmt::Debug for ButtonSet {
 #[repr(transparent)]
 pub struct Axis(sdl3::gamepad::Axis);
 
+impl Axis {
+    pub const LEFT_X: Self = Self(sdl3::gamepad::Axis::LeftX);
+    pub const LEFT_Y: Self = Self(sdl3::gamepad::Axis::LeftY);
+    pub const RIGHT_X: Self = Self(sdl3::gamepad::Axis::RightX);
+    pub const RIGHT_Y: Self = Self(sdl3::gamepad::Axis::RightY);
+    pub const TRIGGER_LEFT: Self = Self(sdl3::gamepad::Axis::TriggerLeft);
+    pub const TRIGGER_RIGHT: Self = Self(sdl3::gamepad::Axis::TriggerRight);
+
+    pub fn as_sdl(&self) -> sdl3::gamepad::Axis {
+        self.0
+    }
+}
+
 impl From<sdl3::gamepad::Axis> for Axis {
     fn from(axis: sdl3::gamepad::Axis) -> Self {
         Self(axis)
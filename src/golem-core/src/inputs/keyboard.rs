@@ -69,4 +69,9 @@ impl ScancodeSet {
     pub fn remove(&mut self, scancode: Scancode) {
         self.set.remove(&scancode);
     }
+
+    /// Iterate over the pressed keys, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = Scancode> + '_ {
+        self.set.iter().copied()
+    }
 }
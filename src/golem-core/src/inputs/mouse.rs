@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumIter, EnumString, IntoEnumIterator};
+
+/// Mouse buttons. Cores only support the 3 main buttons.
+#[derive(
+    Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize, EnumIter, EnumString, Display,
+)]
+#[repr(u8)]
+pub enum MouseButton {
+    Left = 0,
+    Right = 1,
+    Middle = 2,
+}
+
+impl MouseButton {
+    pub fn as_repr(&self) -> u8 {
+        *self as u8
+    }
+}
+
+impl TryFrom<sdl3::mouse::MouseButton> for MouseButton {
+    type Error = ();
+
+    fn try_from(button: sdl3::mouse::MouseButton) -> Result<Self, Self::Error> {
+        match button {
+            sdl3::mouse::MouseButton::Left => Ok(MouseButton::Left),
+            sdl3::mouse::MouseButton::Right => Ok(MouseButton::Right),
+            sdl3::mouse::MouseButton::Middle => Ok(MouseButton::Middle),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A set of pressed mouse buttons.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct MouseButtonSet(u8);
+
+impl MouseButtonSet {
+    pub fn new() -> Self {
+        Self(0)
+    }
+
+    pub fn contains(&self, button: MouseButton) -> bool {
+        self.0 & (1 << button.as_repr()) != 0
+    }
+
+    pub fn insert(&mut self, button: MouseButton) {
+        self.0 |= 1 << button.as_repr();
+    }
+
+    pub fn remove(&mut self, button: MouseButton) {
+        self.0 &= !(1 << button.as_repr());
+    }
+
+    /// The buttons as bits, with left in bit 0, right in bit 1 and middle in bit 2.
+    pub fn as_u8(&self) -> u8 {
+        self.0
+    }
+
+    /// Iterate over the pressed buttons.
+    pub fn iter(&self) -> impl Iterator<Item = MouseButton> + '_ {
+        MouseButton::iter().filter(|b| self.contains(*b))
+    }
+}
+
+impl std::fmt::Debug for MouseButtonSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut f = f.debug_tuple("MouseButtonSet");
+
+        for b in self.iter() {
+            f.field(&b.to_string());
+        }
+        f.finish()
+    }
+}
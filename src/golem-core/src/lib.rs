@@ -1,8 +1,9 @@
 pub use core::Core;
 pub use core::GolemCore;
 
+pub mod archive;
 pub mod core;
 pub mod runner;
 
 pub mod inputs;
-
+pub mod movie;
@@ -0,0 +1,97 @@
+//! Abstraction over input movie formats (e.g. TAS files), so that a movie
+//! can be replayed on a core without knowing its file format.
+use std::fmt::Debug;
+
+use crate::inputs::gamepad::ButtonSet;
+
+/// A command that a movie can issue at the start of a frame, in addition to
+/// the controller inputs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MovieCommand {
+    /// Press the reset button of the console.
+    SoftReset,
+
+    /// Power cycle the console.
+    PowerCycle,
+
+    /// Insert the disk (Famicom Disk System).
+    FdsDiskInsert,
+
+    /// Select the next disk side (Famicom Disk System).
+    FdsDiskSelect,
+
+    /// Insert a coin (VS. System).
+    VsInsertCoin,
+}
+
+/// Checksum of the ROM a movie was recorded with.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RomChecksum {
+    Md5([u8; 16]),
+    Sha1([u8; 20]),
+}
+
+/// Information about a movie, independent of its format.
+#[derive(Debug, Clone, Default)]
+pub struct MovieMetadata {
+    /// The system the movie was recorded for (e.g. `NES`), if known.
+    pub system: Option<String>,
+
+    /// The emulator and its version, if known.
+    pub emulator: Option<String>,
+
+    pub author: Option<String>,
+
+    /// The ROM file name or game name the movie was recorded with.
+    pub rom_filename: Option<String>,
+    pub rom_checksum: Option<RomChecksum>,
+
+    pub rerecord_count: Option<u64>,
+    pub pal: bool,
+}
+
+/// The inputs of a single frame.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MovieFrame {
+    /// Commands to execute before sending the inputs of this frame.
+    pub commands: Vec<MovieCommand>,
+
+    /// Gamepad state of each port. A port that is not connected (or is not
+    /// a gamepad) is `None`.
+    pub ports: Vec<Option<ButtonSet>>,
+}
+
+impl MovieFrame {
+    pub fn has(&self, command: MovieCommand) -> bool {
+        self.commands.contains(&command)
+    }
+}
+
+/// A movie file, which is a list of frames of inputs.
+pub trait Movie {
+    /// Information about the movie.
+    fn metadata(&self) -> MovieMetadata;
+
+    /// The number of ports (players) used by the movie.
+    fn ports(&self) -> usize;
+
+    /// The number of frames in the movie.
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over all the frames of the movie.
+    fn frames(&self) -> Box<dyn Iterator<Item = MovieFrame> + '_>;
+}
+
+impl Debug for dyn Movie {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Movie")
+            .field("metadata", &self.metadata())
+            .field("ports", &self.ports())
+            .field("len", &self.len())
+            .finish()
+    }
+}
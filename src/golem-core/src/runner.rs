@@ -12,6 +12,10 @@ pub enum CoreType {
     /// Launch a core from an RBF file.
     RbfFile(PathBuf),
 
+    /// Launch an arcade game from an MRA file, which names the RBF core to
+    /// load and how to build its ROM.
+    MraFile(PathBuf),
+
     /// Launch the menu core.
     Menu,
 }
@@ -49,6 +53,10 @@ impl CoreLaunchInfo<()> {
         Self::new(CoreType::RbfFile(rbf_path))
     }
 
+    pub fn mra(mra_path: PathBuf) -> Self {
+        Self::new(CoreType::MraFile(mra_path))
+    }
+
     pub fn menu() -> Self {
         Self::new(CoreType::Menu)
     }
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+
+CREATE TABLE game_options
+(
+    id          INTEGER PRIMARY KEY           NOT NULL,
+    core_id     INTEGER REFERENCES cores (id) NOT NULL,
+    game_id     INTEGER REFERENCES games (id) NOT NULL,
+
+    status_bits BLOB                          NOT NULL,
+
+    updated_at  DATETIME                      NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX game_options_game_id_idx ON game_options (game_id);
@@ -10,5 +10,8 @@ pub use core_files::*;
 mod games;
 pub use games::*;
 
+mod game_options;
+pub use game_options::*;
+
 mod savestates;
 pub use savestates::*;
@@ -0,0 +1,69 @@
+use crate::schema;
+use diesel::prelude::*;
+
+/// The core options chosen for a specific game, overriding the options of
+/// the core.
+#[derive(Clone, Debug, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = crate::schema::game_options)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct GameOptions {
+    pub id: i32,
+
+    /// The core these options are for.
+    pub core_id: i32,
+
+    /// The game these options are for.
+    pub game_id: i32,
+
+    /// The raw status bits of the options.
+    pub status_bits: Vec<u8>,
+
+    /// The last time these options were saved.
+    pub updated_at: chrono::NaiveDateTime,
+}
+
+impl GameOptions {
+    pub fn get_for_game(
+        conn: &mut crate::Connection,
+        game_id: i32,
+    ) -> Result<Option<Self>, diesel::result::Error> {
+        use schema::game_options::dsl;
+        schema::game_options::table
+            .select(schema::game_options::all_columns)
+            .filter(dsl::game_id.eq(game_id))
+            .first(conn)
+            .optional()
+    }
+
+    /// Save the options of a game, replacing any previous ones.
+    pub fn set(
+        conn: &mut crate::Connection,
+        core_id: i32,
+        game_id: i32,
+        status_bits: Vec<u8>,
+    ) -> Result<Self, diesel::result::Error> {
+        use schema::game_options::dsl;
+        diesel::replace_into(schema::game_options::table)
+            .values((
+                dsl::core_id.eq(core_id),
+                dsl::game_id.eq(game_id),
+                dsl::status_bits.eq(status_bits),
+                dsl::updated_at.eq(chrono::Utc::now().naive_utc()),
+            ))
+            .execute(conn)?;
+        schema::game_options::table
+            .select(schema::game_options::all_columns)
+            .filter(dsl::game_id.eq(game_id))
+            .first(conn)
+    }
+
+    pub fn delete_for_game(
+        conn: &mut crate::Connection,
+        game_id: i32,
+    ) -> Result<(), diesel::result::Error> {
+        use schema::game_options::dsl;
+        diesel::delete(schema::game_options::table.filter(dsl::game_id.eq(game_id)))
+            .execute(conn)?;
+        Ok(())
+    }
+}
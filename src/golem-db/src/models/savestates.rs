@@ -51,6 +51,38 @@ impl SaveState {
             .load(conn)
     }
 
+    /// Change the name of this savestate. `None` removes the name.
+    pub fn rename(
+        &mut self,
+        conn: &mut crate::Connection,
+        name: Option<String>,
+    ) -> Result<(), diesel::result::Error> {
+        use schema::savestates::dsl;
+        diesel::update(schema::savestates::table.find(self.id))
+            .set(dsl::name.eq(&name))
+            .execute(conn)?;
+        self.name = name;
+        Ok(())
+    }
+
+    /// Record that this savestate was loaded.
+    pub fn play(&mut self, conn: &mut crate::Connection) -> Result<(), diesel::result::Error> {
+        use schema::savestates::dsl;
+        let now = chrono::Utc::now().naive_utc();
+        diesel::update(schema::savestates::table.find(self.id))
+            .set(dsl::last_played.eq(now))
+            .execute(conn)?;
+        self.last_played = Some(now);
+        Ok(())
+    }
+
+    /// Delete this savestate from the database. This does not delete its
+    /// files.
+    pub fn delete(self, conn: &mut crate::Connection) -> Result<(), diesel::result::Error> {
+        diesel::delete(schema::savestates::table.find(self.id)).execute(conn)?;
+        Ok(())
+    }
+
     pub fn create(
         conn: &mut crate::Connection,
         core_id: i32,
@@ -41,6 +41,16 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    game_options (id) {
+        id -> Integer,
+        core_id -> Integer,
+        game_id -> Integer,
+        status_bits -> Binary,
+        updated_at -> Timestamp,
+    }
+}
+
 diesel::table! {
     games (id) {
         id -> Integer,
@@ -79,10 +89,18 @@ diesel::table! {
 diesel::joinable!(core_files -> cores (core_id));
 diesel::joinable!(core_files -> games (game_id));
 diesel::joinable!(dat_files -> cores (core_id));
+diesel::joinable!(game_options -> cores (core_id));
+diesel::joinable!(game_options -> games (game_id));
 diesel::joinable!(games -> cores (core_id));
 diesel::joinable!(savestates -> cores (core_id));
 diesel::joinable!(savestates -> games (game_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
-    core_files, cores, dat_files, games, savestates, storage,
+    core_files,
+    cores,
+    dat_files,
+    game_options,
+    games,
+    savestates,
+    storage,
 );
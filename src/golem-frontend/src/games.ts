@@ -24,18 +24,47 @@ function start_game(game_id: number) {
     return;
   }
 
+  // Offer to start with the last savestate in the first slot, if there is
+  // one. It can then be restored from the core.
+  const last_savestate = db.queryOne(
+    "SELECT id FROM savestates WHERE game_id = ? ORDER BY created_at DESC LIMIT 1",
+    [g.id],
+  );
+  let savestate: number | undefined = undefined;
+  if (last_savestate) {
+    const choice = ui.textMenu({
+      title: "" + g.name,
+      back: () => "back",
+      items: [
+        { label: "Start", select: () => "start" },
+        { label: "Start with last state in slot 1", select: () => "continue" },
+      ],
+    });
+    if (choice === "back") {
+      return;
+    } else if (choice === "continue") {
+      savestate = last_savestate.id as number;
+    }
+  }
+
   db.execute("UPDATE games SET last_played = ? WHERE id = ?", [
     new Date().toISOString(),
     g.id,
   ]);
 
-  core.run({
-    core: { type: "path", path: "" + c.path },
-    game: { type: "rom-path", path: "" + g.path },
-    files: f.map((file) => "" + file.path),
-    autoloop: true,
-    showmenu: false,
-  });
+  try {
+    core.run({
+      core: { type: "path", path: "" + c.path },
+      game: { type: "rom-path", path: "" + g.path },
+      files: f.map((file) => "" + file.path),
+      savestate,
+      autoloop: true,
+      showmenu: false,
+    });
+  } catch (e: any) {
+    // e.g. a missing BIOS.
+    ui.alert("Could not start the game", e.message);
+  }
 }
 
 export function games_menu() {
@@ -12,10 +12,19 @@ declare module "@/golem/core" {
     path: string;
   }
 
+  /**
+   * A path to an arcade MRA file. The core it names is loaded and its ROMs
+   * are built from the zip files it lists.
+   */
+  export interface CoreMraPath {
+    type: "mra";
+    path: string;
+  }
+
   /**
    * The type of core to start.
    */
-  export type CoreType = CorePath;
+  export type CoreType = CorePath | CoreMraPath;
 
   /**
    * A path to a game ROM.
@@ -51,9 +60,11 @@ declare module "@/golem/core" {
     files?: (string | undefined)[];
 
     /**
-     * The savestate file to load. If savestates are not supported, this will be ignored.
+     * The savestate to load in the first slot, either the path to its file
+     * or the ID of a savestate in the database. If savestates are not
+     * supported, this will be ignored.
      */
-    savestate?: string;
+    savestate?: string | number;
 
     /**
      * Whether to show the core menu or not when launching. True by default.
@@ -82,7 +93,9 @@ declare module "@/golem/core" {
   }
 
   /**
-   * Starts a core with the given options.
+   * Starts a core with the given options. The BIOS files the core needs
+   * are loaded from the `bios/<core name>` directory, and this throws an
+   * error naming the ones that are missing.
    * @param options The options for the core.
    */
   export function run(options: RunOptions): GolemCore | void;
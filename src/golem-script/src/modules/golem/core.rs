@@ -1,13 +1,14 @@
 use std::path::PathBuf;
 
 use boa_engine::value::TryFromJs;
-use boa_engine::{js_string, Context, JsError, JsResult, JsString, JsValue, Module};
+use boa_engine::{js_string, Context, JsError, JsNativeError, JsResult, JsString, JsValue, Module};
 use boa_interop::{ContextData, IntoJsFunctionCopied, IntoJsModule};
 use boa_macros::{Finalize, JsData, Trace};
 use one_fpga::core::Rom;
-use one_fpga::runner::CoreLaunchInfo;
+use one_fpga::runner::{CoreLaunchInfo, Slot};
 
 use golem_ui::application::panels::core_loop::run_core_loop;
+use golem_ui::application::GoLEmApp;
 
 use crate::HostData;
 
@@ -15,6 +16,7 @@ use crate::HostData;
 #[derive(Debug, Trace, Finalize, JsData)]
 pub enum CoreType {
     Path { path: JsString },
+    Mra { path: JsString },
 }
 
 impl TryFromJs for CoreType {
@@ -33,6 +35,12 @@ impl TryFromJs for CoreType {
                             .to_string(context)?;
                         Ok(CoreType::Path { path })
                     }
+                    "mra" => {
+                        let path = object
+                            .get(js_string!("path"), context)?
+                            .to_string(context)?;
+                        Ok(CoreType::Mra { path })
+                    }
                     _ => Err(JsError::from_opaque(
                         js_string!("Invalid core type.").into(),
                     )),
@@ -79,26 +87,76 @@ impl TryFromJs for GameType {
     }
 }
 
+/// A savestate for JavaScript, either a path to its file or the ID of a
+/// savestate in the database.
+#[derive(Debug, Trace, Finalize, JsData)]
+pub enum SaveStateType {
+    Id(i32),
+    Path(JsString),
+}
+
+impl TryFromJs for SaveStateType {
+    fn try_from_js(value: &JsValue, _context: &mut Context) -> JsResult<Self> {
+        match value {
+            JsValue::Integer(id) => Ok(SaveStateType::Id(*id)),
+            JsValue::Rational(id)
+                if id.fract() == 0.0 && (i32::MIN as f64..=i32::MAX as f64).contains(id) =>
+            {
+                Ok(SaveStateType::Id(*id as i32))
+            }
+            JsValue::String(path) => Ok(SaveStateType::Path(path.clone())),
+            _ => Err(JsError::from_opaque(
+                js_string!("Invalid savestate.").into(),
+            )),
+        }
+    }
+}
+
+impl SaveStateType {
+    /// The path to the savestate file.
+    fn path(&self, app: &GoLEmApp) -> JsResult<PathBuf> {
+        match self {
+            SaveStateType::Path(path) => Ok(PathBuf::from(path.to_std_string_escaped())),
+            SaveStateType::Id(id) => {
+                let database = app.database();
+                let mut database = database.lock().unwrap();
+                let savestate = golem_db::models::SaveState::get(&mut database, *id)
+                    .map_err(|e| JsError::from(JsNativeError::error().with_message(e.to_string())))?
+                    .ok_or_else(|| {
+                        JsError::from(
+                            JsNativeError::error()
+                                .with_message(format!("Savestate {id} not found")),
+                        )
+                    })?;
+                Ok(PathBuf::from(savestate.path))
+            }
+        }
+    }
+}
+
 #[derive(Debug, Trace, Finalize, JsData, TryFromJs)]
 struct RunOptions {
     core: CoreType,
     game: Option<GameType>,
     files: Option<Vec<Option<String>>>,
-    savestate: Option<String>,
+    savestate: Option<SaveStateType>,
     showmenu: Option<bool>,
     autoloop: Option<bool>,
 }
 
-fn run_(options: RunOptions, ContextData(app): ContextData<HostData>) {
+fn run_(options: RunOptions, ContextData(app): ContextData<HostData>) -> JsResult<()> {
     let app = app.app_mut();
     let mut core_options = match &options.core {
         CoreType::Path { path } => CoreLaunchInfo::rbf(PathBuf::from(path.to_std_string_escaped())),
+        CoreType::Mra { path } => CoreLaunchInfo::mra(PathBuf::from(path.to_std_string_escaped())),
     };
 
     match &options.game {
         Some(GameType::RomPath { path }) => {
-            core_options =
-                core_options.with_rom(Rom::File(PathBuf::from(path.to_std_string_escaped())));
+            // Games inside archives are read in memory.
+            let rom = Rom::from_path(path.to_std_string_escaped())
+                .map_err(|e| JsError::from(JsNativeError::error().with_message(e.to_string())))?;
+            core_options = core_options.with_rom(rom);
         }
         None => {}
     };
@@ -111,20 +169,25 @@ fn run_(options: RunOptions, ContextData(app): ContextData<HostData>) {
         {
             core_options
                 .files
-                .insert(i, one_fpga::runner::Slot::File(PathBuf::from(file)));
+                .insert(i, Slot::File(PathBuf::from(file)));
         }
     }
 
+    if let Some(savestate) = &options.savestate {
+        core_options = core_options.with_save_state(Slot::File(savestate.path(app)?));
+    }
+
     eprintln!("Launching core: {:?}", core_options);
     let mut core = app
         .platform_mut()
         .core_manager_mut()
         .launch(core_options)
-        .unwrap();
+        .map_err(|e| JsError::from(JsNativeError::error().with_message(e)))?;
 
     if options.autoloop.unwrap_or(true) {
         run_core_loop(&mut *app, &mut core, options.showmenu.unwrap_or(true));
     }
+    Ok(())
 }
 
 pub fn create_module(context: &mut Context) -> JsResult<(JsString, Module)> {
@@ -11,6 +11,7 @@ use tracing::{info, warn};
 use golem_db::Connection;
 
 use crate::application::coordinator::Coordinator;
+use crate::application::recording::Recording;
 use crate::application::toolbar::Toolbar;
 use crate::data::paths;
 use crate::data::settings::Settings;
@@ -22,6 +23,7 @@ use crate::platform::WindowManager;
 pub mod menu;
 
 pub mod panels;
+pub mod recording;
 mod toolbar;
 mod widgets;
 
@@ -36,6 +38,9 @@ pub struct GoLEmApp {
 
     coordinator: Coordinator,
 
+    /// The video recording in progress, if any.
+    recording: Option<Recording>,
+
     render_toolbar: bool,
 
     joysticks: [Option<Joystick>; 32],
@@ -74,6 +79,7 @@ impl GoLEmApp {
         Self {
             toolbar: Toolbar::new(settings.clone(), database.clone()),
             coordinator: Coordinator::new(database.clone()),
+            recording: None,
             render_toolbar: true,
             joysticks,
             gamepads,
@@ -101,6 +107,10 @@ impl GoLEmApp {
         &mut self.osd_buffer
     }
 
+    pub fn recording_mut(&mut self) -> &mut Option<Recording> {
+        &mut self.recording
+    }
+
     pub fn database(&self) -> Arc<Mutex<Connection>> {
         self.database.clone()
     }
@@ -16,7 +16,8 @@ use one_fpga::runner::CoreLaunchInfo;
 use one_fpga::{Core, GolemCore};
 
 use crate::application::GoLEmApp;
-use crate::data::paths;
+use crate::core_manager::load_cheats;
+use crate::data::{core_options, paths};
 
 #[derive(Default, Debug, Clone, Copy)]
 pub struct GameStartInfo {
@@ -107,6 +108,13 @@ impl CoordinatorInner {
             }
         };
 
+        // A newly loaded core needs its BIOS before the game.
+        if info.data.core_id.is_some() {
+            app.platform_mut()
+                .core_manager_mut()
+                .send_bios(&mut golem_core)?;
+        }
+
         let c = golem_core
             .as_any_mut()
             .downcast_mut::<MisterFpgaCore>()
@@ -129,6 +137,12 @@ impl CoordinatorInner {
             let core_file = golem_db::models::CoreFile::latest_for_game(&mut database, game_id)
                 .map_err(|e| e.to_string())?;
 
+            // Options saved for this game override the ones of the core. Other
+            // games use the options of the core, not the ones of the last game.
+            if !core_options::restore_for_game(&mut database, c, game_id)? {
+                core_options::restore(c);
+            }
+
             let should_sav = c
                 .menu_options()
                 .iter()
@@ -136,6 +150,9 @@ impl CoordinatorInner {
                 .any(|i| i.save_support);
 
             c.load_file(Path::new(&game_path), None)?;
+
+            // Cheats of the game are all disabled when it starts.
+            load_cheats(c, Some(Path::new(&game_path)))?;
             if should_sav {
                 // Mount the SAV file.
                 let game_name = game.name.clone();
@@ -158,6 +175,8 @@ impl CoordinatorInner {
             let _ = game.play(&mut database);
             self.current_game = Some(game);
             should_show_menu = false;
+        } else {
+            load_cheats(c, None)?;
         }
 
         // Load all savestates for this game.
@@ -188,6 +207,41 @@ impl CoordinatorInner {
         Ok((should_show_menu, golem_core))
     }
 
+    /// Save the options of the core, for the current game if it has its
+    /// own options, or for the core otherwise.
+    pub fn save_core_options(&mut self, core: &MisterFpgaCore) -> Result<(), String> {
+        let mut database = self.database.lock().unwrap();
+        if let (Some(core_id), Some(game_id)) = (
+            self.current_core.as_ref().map(|c| c.id),
+            self.current_game.as_ref().map(|g| g.id),
+        ) {
+            let has_options = golem_db::models::GameOptions::get_for_game(&mut database, game_id)
+                .map_err(|e| e.to_string())?
+                .is_some();
+            if has_options {
+                return core_options::save_for_game(&mut database, core, core_id, game_id);
+            }
+        }
+
+        core_options::save(core)
+    }
+
+    /// Save the options of the core for the current game only.
+    pub fn save_game_options(&mut self, core: &MisterFpgaCore) -> Result<(), String> {
+        let core_id = self.current_core.as_ref().ok_or("No core loaded")?.id;
+        let game_id = self.current_game.as_ref().ok_or("No game loaded")?.id;
+        let mut database = self.database.lock().unwrap();
+        core_options::save_for_game(&mut database, core, core_id, game_id)
+    }
+
+    /// Reset the options of the core to their defaults, for the core and
+    /// the current game.
+    pub fn reset_core_options(&mut self, core: &mut MisterFpgaCore) -> Result<(), String> {
+        let game_id = self.current_game.as_ref().map(|g| g.id);
+        let mut database = self.database.lock().unwrap();
+        core_options::reset(&mut database, core, game_id)
+    }
+
     pub fn create_savestate(
         &mut self,
         slot: usize,
@@ -201,8 +255,12 @@ impl CoordinatorInner {
             std::fs::create_dir_all(&ss_root).map_err(|e| e.to_string())?;
         }
 
+        // Keep older savestates around so they can be loaded from the menu.
         let name = &game.name;
-        let savestate_path: PathBuf = ss_root.join(format!("{name}_{slot}")).with_extension("ss");
+        let time = chrono::Utc::now().format("%Y%m%d-%H%M%S");
+        let savestate_path: PathBuf = ss_root
+            .join(format!("{name}_{slot}_{time}"))
+            .with_extension("ss");
         let screenshot_path = if screenshot.is_some() {
             Some(savestate_path.with_extension("ss.png"))
         } else {
@@ -262,6 +320,18 @@ impl Coordinator {
         self.inner.lock().unwrap().current_sav.clone()
     }
 
+    pub fn save_core_options(&self, core: &MisterFpgaCore) -> Result<(), String> {
+        self.inner.lock().unwrap().save_core_options(core)
+    }
+
+    pub fn save_game_options(&self, core: &MisterFpgaCore) -> Result<(), String> {
+        self.inner.lock().unwrap().save_game_options(core)
+    }
+
+    pub fn reset_core_options(&self, core: &mut MisterFpgaCore) -> Result<(), String> {
+        self.inner.lock().unwrap().reset_core_options(core)
+    }
+
     pub fn create_savestate(
         &self,
         slot: usize,
@@ -3,6 +3,7 @@
 use std::path::{Path, PathBuf};
 
 use embedded_graphics::mono_font::ascii;
+use one_fpga::archive;
 use regex::Regex;
 
 use crate::application::menu::style::MenuReturn;
@@ -65,6 +66,46 @@ impl FilesystemMenuOptions {
     }
 }
 
+/// A file or directory shown in the menu. Archives are shown as
+/// directories, and their content can be browsed like the file system.
+struct Entry {
+    path: PathBuf,
+    name: String,
+    is_dir: bool,
+    size: u64,
+}
+
+/// Read the entries of a directory, which can be inside an archive.
+fn read_entries(path: &Path) -> Result<Vec<Entry>, std::io::Error> {
+    if let Some((archive_path, inner)) = archive::split_path(path) {
+        return Ok(archive::list(archive_path, inner)?
+            .into_iter()
+            .map(|entry| Entry {
+                path: path.join(&entry.name),
+                name: entry.name,
+                is_dir: entry.is_dir,
+                size: entry.size,
+            })
+            .collect());
+    }
+
+    Ok(std::fs::read_dir(path)?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let path = entry.path();
+            let name = path.file_name()?.to_string_lossy().to_string();
+            let metadata = std::fs::metadata(&path).ok()?;
+
+            Some(Entry {
+                is_dir: metadata.is_dir() || archive::is_archive(&path),
+                size: metadata.len(),
+                path,
+                name,
+            })
+        })
+        .collect())
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 enum MenuAction {
     Back,
@@ -138,63 +179,61 @@ pub fn select_file_path_menu(
     let mut sort = SortOption::NameAsc;
 
     loop {
-        let mut entries = std::fs::read_dir(&path)?
-            .filter_map(|entry| {
-                let entry = entry.ok()?;
-                let path = entry.path();
-                let name = path.file_name()?.to_string_lossy().to_string();
+        let mut entries = read_entries(&path)?
+            .into_iter()
+            .filter_map(|mut entry| {
+                let name = entry.name.as_str();
 
                 if !show_hidden && name.starts_with('.') {
                     return None;
                 }
 
                 if let Some(pattern) = pattern.as_ref() {
-                    if !pattern.is_match(&name) {
+                    if !pattern.is_match(name) {
                         return None;
                     }
                 }
 
                 if let Some(ext) = extensions.as_ref() {
-                    if path.is_file() {
-                        let extension = path.extension().unwrap_or_default().to_string_lossy();
-                        if !ext
-                            .iter()
-                            .any(|e| e.as_str().eq_ignore_ascii_case(&extension))
-                        {
-                            return None;
-                        }
+                    let extension = entry.path.extension().unwrap_or_default().to_string_lossy();
+                    let accepted = ext
+                        .iter()
+                        .any(|e| e.as_str().eq_ignore_ascii_case(&extension));
+
+                    // Archives the caller accepts (e.g. `zip` for arcade
+                    // cores) are selected like files, not browsed.
+                    if accepted && entry.is_dir && archive::is_archive(&entry.path) {
+                        entry.is_dir = false;
+                    }
+                    if !entry.is_dir && !accepted {
+                        return None;
                     }
                 }
 
-                if path.is_dir() {
-                    if let Some(pattern) = pattern.as_ref() {
-                        if !pattern.is_match(&name) {
-                            return None;
-                        }
-                    }
-                } else if directory {
+                // Archives cannot be selected as directories.
+                if directory && (!entry.is_dir || archive::is_archive(&entry.path)) {
                     return None;
                 }
 
-                let mut name = if show_extensions {
-                    name
-                } else {
-                    path.file_stem()
+                if !show_extensions && !entry.is_dir {
+                    entry.name = entry
+                        .path
+                        .file_stem()
                         .map(|p| p.to_string_lossy().to_string())
-                        .unwrap_or_default()
-                };
+                        .unwrap_or_default();
+                }
 
-                if path.is_dir() {
-                    name.push('/');
+                if entry.is_dir {
+                    entry.name.push('/');
                 }
 
-                Some((path, name))
+                Some(entry)
             })
             .collect::<Vec<_>>();
 
-        entries.sort_by(|(a, _), (b, _)| {
+        entries.sort_by(|a, b| {
             if dir_first {
-                match (a.is_dir(), b.is_dir()) {
+                match (a.is_dir, b.is_dir) {
                     (true, false) => return std::cmp::Ordering::Less,
                     (false, true) => return std::cmp::Ordering::Greater,
                     _ => {}
@@ -202,34 +241,23 @@ pub fn select_file_path_menu(
             }
 
             match sort {
-                SortOption::NameAsc => a.file_name().cmp(&b.file_name()),
-                SortOption::NameDesc => b.file_name().cmp(&a.file_name()),
-                SortOption::SizeAsc => a
-                    .metadata()
-                    .unwrap()
-                    .len()
-                    .cmp(&b.metadata().unwrap().len()),
-                SortOption::SizeDesc => b
-                    .metadata()
-                    .unwrap()
-                    .len()
-                    .cmp(&a.metadata().unwrap().len()),
+                SortOption::NameAsc => a.path.file_name().cmp(&b.path.file_name()),
+                SortOption::NameDesc => b.path.file_name().cmp(&a.path.file_name()),
+                SortOption::SizeAsc => a.size.cmp(&b.size),
+                SortOption::SizeDesc => b.size.cmp(&a.size),
             }
         });
 
         let items = entries
             .iter()
             .enumerate()
-            .map(|(idx, (path, name))| {
-                if path.is_dir() {
-                    (name, "DIR".to_string(), MenuAction::Select(idx))
+            .map(|(idx, entry)| {
+                if entry.is_dir {
+                    (&entry.name, "DIR".to_string(), MenuAction::Select(idx))
                 } else {
                     (
-                        name,
-                        humansize::format_size(
-                            std::fs::metadata(path).unwrap().len(),
-                            humansize::DECIMAL,
-                        ),
+                        &entry.name,
+                        humansize::format_size(entry.size, humansize::DECIMAL),
                         MenuAction::Select(idx),
                     )
                 }
@@ -278,11 +306,11 @@ pub fn select_file_path_menu(
 
         match selection {
             MenuAction::Select(idx) => {
-                let p = entries[idx].0.to_path_buf();
-                if p.is_dir() {
-                    path = p.to_path_buf();
+                let entry = &entries[idx];
+                if entry.is_dir {
+                    path = entry.path.clone();
                 } else {
-                    return Ok(Some(p));
+                    return Ok(Some(entry.path.clone()));
                 }
             }
             MenuAction::SelectCurrentDirectory => {
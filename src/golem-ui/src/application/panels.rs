@@ -4,3 +4,4 @@ pub mod input_tester;
 pub mod progress;
 pub mod qrcode;
 pub mod settings;
+pub mod text_input;
@@ -5,6 +5,8 @@ use tracing::{debug, error, info, trace};
 
 use one_fpga::{Core, GolemCore};
 
+use crate::application::panels::alert::alert;
+use crate::application::recording::Recording;
 use crate::application::GoLEmApp;
 use crate::input::commands::{CommandResult, ShortcutCommand};
 use crate::input::shortcut::Shortcut;
@@ -48,6 +50,26 @@ fn core_loop(app: &mut GoLEmApp, core: &mut GolemCore) {
                 debug!("Settings updated...");
             }
 
+            // A recording stops by itself when it reaches its maximum
+            // duration, in which case its errors are only known now.
+            if app
+                .recording_mut()
+                .as_ref()
+                .is_some_and(Recording::is_finished)
+            {
+                if let Some(Err(e)) = app.recording_mut().take().map(Recording::stop) {
+                    error!("Error recording video: {}", e);
+                    app.platform_mut().core_manager_mut().show_menu();
+                    let _ = alert(
+                        app,
+                        "Error",
+                        &format!("Error recording video: {e}"),
+                        &["Back"],
+                    );
+                    app.platform_mut().core_manager_mut().hide_menu();
+                }
+            }
+
             // Every 500 frames, show FPS.
             if trace_enabled && i % 500 == 0 {
                 trace!("Settings update took {:?}", now.elapsed());
@@ -60,6 +82,10 @@ fn core_loop(app: &mut GoLEmApp, core: &mut GolemCore) {
             prev = now;
         }
 
+        // Mouse movements are accumulated and sent once per loop, since SDL
+        // can send many motion events per frame.
+        let (mut mouse_dx, mut mouse_dy, mut mouse_wheel) = (0.0f32, 0.0f32, 0.0f32);
+
         for ev in state.events() {
             match ev {
                 Event::KeyDown {
@@ -91,13 +117,37 @@ fn core_loop(app: &mut GoLEmApp, core: &mut GolemCore) {
                     which, axis, value, ..
                 } => {
                     inputs.controller_axis_motion(which, axis, value);
-                    // TODO
-                    // let _ = core.sdl_axis_motion((which - 1) as u8, axis, value);
+                    let _ = core.gamepad_axis((which - 1) as usize, axis.into(), value);
+                }
+                Event::MouseMotion { xrel, yrel, .. } => {
+                    mouse_dx += xrel;
+                    mouse_dy += yrel;
+                }
+                Event::MouseWheel { y, .. } => {
+                    mouse_wheel += y;
+                }
+                Event::MouseButtonDown { mouse_btn, .. } => {
+                    if let Ok(button) = mouse_btn.try_into() {
+                        let _ = core.mouse_button_down(button);
+                    }
+                }
+                Event::MouseButtonUp { mouse_btn, .. } => {
+                    if let Ok(button) = mouse_btn.try_into() {
+                        let _ = core.mouse_button_up(button);
+                    }
                 }
                 _ => {}
             }
         }
 
+        if mouse_dx != 0.0 || mouse_dy != 0.0 || mouse_wheel != 0.0 {
+            let _ = core.mouse_move(
+                mouse_dx.round() as i32,
+                mouse_dy.round() as i32,
+                mouse_wheel.round() as i32,
+            );
+        }
+
         // Check if any action needs to be taken.
         let mut update_commands = false;
         for (command, mapping) in &commands {
@@ -186,6 +236,14 @@ pub fn run_core_loop(app: &mut GoLEmApp, core: &mut GolemCore, should_show_menu:
         core_loop(app, core);
     }
 
+    // Recordings are of the core's output, so they end with the core.
+    if let Some(recording) = app.recording_mut().take() {
+        match recording.stop() {
+            Ok(path) => debug!(?path, "Recording stopped"),
+            Err(e) => error!("Error recording video: {}", e),
+        }
+    }
+
     debug!("Core loop ended");
     info!("Loading Main Menu");
     app.platform_mut().core_manager_mut().load_menu().unwrap();
@@ -8,10 +8,13 @@ use mister_fpga::core::MisterFpgaCore;
 use one_fpga::{Core, GolemCore};
 use tracing::error;
 
+mod cheats;
 mod core_debug;
 mod core_settings;
+mod dip_switches;
 pub mod input_mapping;
 mod items;
+mod savestates;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 enum CoreMenuAction {
@@ -19,6 +22,11 @@ enum CoreMenuAction {
     CoreSettings,
     CoreMenuAction(core_settings::CoreMenuAction),
     InputMapping,
+    Cheats,
+    DipSwitches,
+    Savestates,
+    SaveGameSettings,
+    ResetSettings,
     DebugMenu,
     Back,
     Quit,
@@ -60,21 +68,64 @@ pub fn core_menu(app: &mut GoLEmApp, core: &mut GolemCore) -> bool {
     }
 
     let mut state = None;
+    let has_game = app.coordinator_mut().current_game().is_some();
+    // The options as they are saved, to only save them again when changed.
+    let mut saved_options = c.option_status_bits();
 
     let result = loop {
+        let menu_mask = c.read_menu_mask();
         let status = c.status_bits();
+        let savestate_slots = c
+            .save_states()
+            .filter(|_| has_game)
+            .map(|m| format!("{} slots", m.nb_slots()));
+        let cheats_label = c
+            .config()
+            .cheats_menu()
+            .and_then(|m| m.label())
+            .unwrap_or("Cheats");
+        let cheats_count = c
+            .cheats()
+            .filter(|cheats| !cheats.is_empty())
+            .map(|cheats| format!("{}/{}", cheats.nb_enabled(), cheats.len()));
+        let has_dip_switches =
+            c.config().has_dip_switches() && c.dip_switches().is_some_and(|d| !d.dips().is_empty());
         let mut additional_items = c
             .menu_options()
             .iter()
             .filter(|o| o.as_load_file().is_some())
-            .filter_map(|i| into_text_menu_item(i, &status))
+            .filter_map(|i| into_text_menu_item(i, &status, menu_mask))
             .map(|i| i.map_action(CoreMenuAction::CoreMenuAction))
             .chain([
                 ("-", "", CoreMenuAction::Unselectable).to_menu_item(),
                 ("Input Mapping", "", CoreMenuAction::InputMapping).to_menu_item(),
-                ("Debug", "", CoreMenuAction::DebugMenu).to_menu_item(),
             ])
             .collect::<Vec<_>>();
+        if has_dip_switches {
+            additional_items.push(("DIP Switches", "", CoreMenuAction::DipSwitches).to_menu_item());
+        }
+        if let Some(count) = &cheats_count {
+            additional_items
+                .push((cheats_label, count.as_str(), CoreMenuAction::Cheats).to_menu_item());
+        }
+        if let Some(slots) = &savestate_slots {
+            additional_items
+                .push(("Savestates", slots.as_str(), CoreMenuAction::Savestates).to_menu_item());
+        }
+        if has_game {
+            additional_items.push(
+                (
+                    "Save Settings for Game",
+                    "",
+                    CoreMenuAction::SaveGameSettings,
+                )
+                    .to_menu_item(),
+            );
+        }
+        additional_items.extend([
+            ("Reset Settings", "", CoreMenuAction::ResetSettings).to_menu_item(),
+            ("Debug", "", CoreMenuAction::DebugMenu).to_menu_item(),
+        ]);
 
         let version = c
             .config()
@@ -108,6 +159,25 @@ pub fn core_menu(app: &mut GoLEmApp, core: &mut GolemCore) -> bool {
                     break false;
                 }
             }
+            CoreMenuAction::Cheats => {
+                cheats::cheats_menu(app, c);
+            }
+            CoreMenuAction::DipSwitches => {
+                dip_switches::dip_switches_menu(app, c);
+            }
+            CoreMenuAction::Savestates => {
+                if savestates::savestates_menu(app, c) {
+                    break false;
+                }
+            }
+            CoreMenuAction::SaveGameSettings => match app.coordinator_mut().save_game_options(c) {
+                Ok(()) => saved_options = c.option_status_bits(),
+                Err(e) => error!(?e, "Could not save the game settings."),
+            },
+            CoreMenuAction::ResetSettings => match app.coordinator_mut().reset_core_options(c) {
+                Ok(()) => saved_options = c.option_status_bits(),
+                Err(e) => error!(?e, "Could not reset the core settings."),
+            },
             CoreMenuAction::DebugMenu => {
                 core_debug::debug_menu(app, c);
             }
@@ -130,6 +200,13 @@ pub fn core_menu(app: &mut GoLEmApp, core: &mut GolemCore) -> bool {
         }
     };
 
+    // Keep the options the user chose for the next time.
+    if c.option_status_bits() != saved_options {
+        if let Err(e) = app.coordinator_mut().save_core_options(c) {
+            error!(?e, "Could not save the core settings.");
+        }
+    }
+
     app.platform_mut().core_manager_mut().hide_menu();
     result
 }
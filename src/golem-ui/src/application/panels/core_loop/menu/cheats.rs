@@ -0,0 +1,75 @@
+use crate::application::menu::style::MenuReturn;
+use crate::application::menu::{text_menu, TextMenuOptions};
+use crate::application::panels::alert::alert;
+use crate::application::GoLEmApp;
+use mister_fpga::core::MisterFpgaCore;
+use tracing::error;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MenuAction {
+    Toggle(usize),
+    Back,
+}
+
+impl MenuReturn for MenuAction {
+    fn back() -> Option<Self> {
+        Some(MenuAction::Back)
+    }
+}
+
+/// Show the cheats of the current game and enable or disable them. Changes
+/// are sent to the core right away.
+pub fn cheats_menu(app: &mut GoLEmApp, core: &mut MisterFpgaCore) {
+    let title = core
+        .config()
+        .cheats_menu()
+        .and_then(|c| c.label())
+        .unwrap_or("Cheats")
+        .to_string();
+
+    let mut state = None;
+    loop {
+        // The core menu only shows this menu when the game has cheats.
+        let Some(cheats) = core.cheats() else {
+            return;
+        };
+
+        let items = cheats
+            .cheats()
+            .iter()
+            .enumerate()
+            .map(|(i, cheat)| {
+                (
+                    cheat.name.as_str(),
+                    if cheat.enabled { "On" } else { "Off" },
+                    MenuAction::Toggle(i),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let (result, new_state) = text_menu(
+            app,
+            &title,
+            &items,
+            TextMenuOptions::default().with_state(state),
+        );
+        state = Some(new_state);
+
+        match result {
+            MenuAction::Toggle(idx) => {
+                if let Some(cheats) = core.cheats_mut() {
+                    cheats.toggle(idx);
+                }
+                if let Err(e) = core.send_cheats() {
+                    error!(?e, "Could not send the cheats.");
+                    // Keep the cheats as the core has them.
+                    if let Some(cheats) = core.cheats_mut() {
+                        cheats.toggle(idx);
+                    }
+                    let _ = alert(app, "Error", &e, &["Back"]);
+                }
+            }
+            MenuAction::Back => return,
+        }
+    }
+}
@@ -2,7 +2,7 @@ use std::convert::TryFrom;
 
 use tracing::info;
 
-use mister_fpga::config_string::ConfigMenu;
+use mister_fpga::config_string::{menu_mask_get, ConfigMenu};
 use mister_fpga::core::MisterFpgaCore;
 use mister_fpga::types::StatusBitMap;
 use one_fpga::runner::CoreLaunchInfo;
@@ -32,6 +32,7 @@ impl MenuReturn for CoreMenuAction {
 pub fn into_text_menu_item<'a>(
     item: &'a ConfigMenu,
     status: &StatusBitMap,
+    menu_mask: u16,
 ) -> Option<TextMenuItem<'a, CoreMenuAction>> {
     match item {
         ConfigMenu::Empty(None) => Some(TextMenuItem::separator()),
@@ -61,37 +62,37 @@ pub fn into_text_menu_item<'a>(
             CoreMenuAction::Trigger(*index, *close_osd),
         )),
         ConfigMenu::HideIf(b, sub) => {
-            if status.get(*b as usize) {
+            if menu_mask_get(menu_mask, *b) {
                 None
             } else {
-                into_text_menu_item(sub, status)
+                into_text_menu_item(sub, status, menu_mask)
             }
         }
         ConfigMenu::HideUnless(b, sub) => {
-            if status.get(*b as usize) {
-                into_text_menu_item(sub, status)
+            if menu_mask_get(menu_mask, *b) {
+                into_text_menu_item(sub, status, menu_mask)
             } else {
                 None
             }
         }
         ConfigMenu::DisableIf(b, sub) => {
-            if status.get(*b as usize) {
-                into_text_menu_item(sub, status)
-            } else if let Some(item) = into_text_menu_item(sub, status) {
-                Some(item.disabled())
-            } else {
-                None
-            }
-        }
-        ConfigMenu::DisableUnless(b, sub) => {
-            if status.get(*b as usize) {
-                if let Some(item) = into_text_menu_item(sub, status) {
+            if menu_mask_get(menu_mask, *b) {
+                if let Some(item) = into_text_menu_item(sub, status, menu_mask) {
                     Some(item.disabled())
                 } else {
                     None
                 }
             } else {
-                into_text_menu_item(sub, status)
+                into_text_menu_item(sub, status, menu_mask)
+            }
+        }
+        ConfigMenu::DisableUnless(b, sub) => {
+            if menu_mask_get(menu_mask, *b) {
+                into_text_menu_item(sub, status, menu_mask)
+            } else if let Some(item) = into_text_menu_item(sub, status, menu_mask) {
+                Some(item.disabled())
+            } else {
+                None
             }
         }
         ConfigMenu::LoadFile(info) => {
@@ -112,7 +113,7 @@ pub fn into_text_menu_item<'a>(
         }
         ConfigMenu::PageItem(_index, sub) => {
             // TODO: add full page support.
-            into_text_menu_item(sub, status)
+            into_text_menu_item(sub, status, menu_mask)
         }
         _ => None,
     }
@@ -207,11 +208,12 @@ pub fn execute_core_settings(
 pub fn core_settings(app: &mut GoLEmApp, core: &mut MisterFpgaCore) -> bool {
     let mut state = None;
     loop {
+        let menu_mask = core.read_menu_mask();
         let status = *core.status_bits();
         let mut items = core
             .menu_options()
             .iter()
-            .filter_map(|i| into_text_menu_item(i, &status))
+            .filter_map(|i| into_text_menu_item(i, &status, menu_mask))
             .collect::<Vec<_>>();
 
         let (action, new_state) = text_menu(
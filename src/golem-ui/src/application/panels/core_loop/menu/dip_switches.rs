@@ -0,0 +1,74 @@
+use crate::application::menu::style::MenuReturn;
+use crate::application::menu::{text_menu, TextMenuOptions};
+use crate::application::panels::alert::alert;
+use crate::application::GoLEmApp;
+use crate::data::dip_switches;
+use mister_fpga::core::MisterFpgaCore;
+use tracing::error;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MenuAction {
+    Next(usize),
+    Reset,
+    Back,
+}
+
+impl MenuReturn for MenuAction {
+    fn back() -> Option<Self> {
+        Some(MenuAction::Back)
+    }
+}
+
+/// Show the DIP switches of the current arcade game and change them. Changes
+/// are sent to the core right away and saved for the next time the game is
+/// played.
+pub fn dip_switches_menu(app: &mut GoLEmApp, core: &mut MisterFpgaCore) {
+    let mut state = None;
+    loop {
+        let Some(dips) = core.dip_switches() else {
+            return;
+        };
+
+        let items = dips
+            .dips()
+            .iter()
+            .enumerate()
+            .map(|(i, dip)| {
+                let choice = dips
+                    .choice(i)
+                    .and_then(|c| dip.choices.get(c))
+                    .map_or("?", String::as_str);
+                (dip.name.as_str(), choice, MenuAction::Next(i))
+            })
+            .collect::<Vec<_>>();
+
+        let (result, new_state) = text_menu(
+            app,
+            "DIP Switches",
+            &items,
+            TextMenuOptions::default().with_state(state).with_suffix(&[(
+                "Reset to Defaults",
+                "",
+                MenuAction::Reset,
+            )]),
+        );
+        state = Some(new_state);
+
+        let Some(dips) = core.dip_switches_mut() else {
+            return;
+        };
+        match result {
+            MenuAction::Next(idx) => dips.next_choice(idx),
+            MenuAction::Reset => dips.reset(),
+            MenuAction::Back => return,
+        }
+
+        let result = core
+            .send_dip_switches()
+            .and_then(|_| core.dip_switches().map_or(Ok(()), dip_switches::save));
+        if let Err(e) = result {
+            error!(?e, "Could not update the DIP switches.");
+            let _ = alert(app, "Error", &e, &["Back"]);
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use crate::application::menu::style;
+use crate::application::menu::style::MenuReturn;
+use crate::application::menu::{text_menu, TextMenuOptions};
+use crate::application::panels::alert::alert;
+use crate::application::panels::text_input::text_input;
+use crate::application::widgets::menu::SizedMenu;
+use crate::application::widgets::thumbnail::Thumbnail;
+use crate::application::GoLEmApp;
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Dimensions, Point, Size};
+use embedded_graphics::mono_font::{ascii, MonoTextStyle};
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Line, PrimitiveStyle};
+use embedded_graphics::text::Text;
+use embedded_layout::layout::linear::{spacing, LinearLayout};
+use embedded_layout::object_chain::Chain;
+use embedded_layout::prelude::*;
+use embedded_menu::items::menu_item::SelectValue;
+use embedded_menu::items::MenuItem;
+use embedded_menu::Menu;
+use golem_db::models::SaveState as DbSaveState;
+use mister_fpga::core::MisterFpgaCore;
+use one_fpga::core::SaveState;
+use std::convert::identity;
+use tracing::error;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MenuAction {
+    Select(usize),
+    Back,
+}
+
+impl MenuReturn for MenuAction {
+    fn back() -> Option<Self> {
+        Some(MenuAction::Back)
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+enum SavestateAction {
+    CopyToSlot(usize),
+    Rename,
+    Delete,
+    #[default]
+    Back,
+}
+
+impl MenuReturn for SavestateAction {
+    fn back() -> Option<Self> {
+        Some(SavestateAction::Back)
+    }
+}
+
+impl SelectValue for SavestateAction {
+    fn marker(&self) -> &str {
+        ""
+    }
+}
+
+fn label(savestate: &DbSaveState) -> String {
+    savestate
+        .name
+        .clone()
+        .unwrap_or_else(|| savestate.created_at.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+/// Show the savestates of the current game, newest first. Returns `true` if a
+/// savestate was copied to a slot (in which case the OSD should be closed).
+pub fn savestates_menu(app: &mut GoLEmApp, core: &mut MisterFpgaCore) -> bool {
+    let Some(game) = app.coordinator_mut().current_game() else {
+        let _ = alert(app, "Savestates", "No game loaded.", &["Back"]);
+        return false;
+    };
+    let database = app.database();
+
+    let mut state = None;
+    loop {
+        let mut savestates =
+            match DbSaveState::list_for_game(&mut database.lock().unwrap(), game.id) {
+                Ok(savestates) => savestates,
+                Err(e) => {
+                    error!(?e, "Could not list the savestates.");
+                    Vec::new()
+                }
+            };
+        savestates.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+        let labels = savestates.iter().map(label).collect::<Vec<_>>();
+        let items = labels
+            .iter()
+            .zip(savestates.iter())
+            .enumerate()
+            .map(|(i, (label, ss))| {
+                (
+                    label.as_str(),
+                    if ss.favorite { "*" } else { "" },
+                    MenuAction::Select(i),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let (result, new_state) = text_menu(
+            app,
+            &format!("Savestates - {}", game.name),
+            &items,
+            TextMenuOptions::default().with_state(state),
+        );
+        state = Some(new_state);
+
+        match result {
+            MenuAction::Select(idx) => {
+                if savestate_details(app, core, savestates.swap_remove(idx)) {
+                    return true;
+                }
+            }
+            MenuAction::Back => return false,
+        }
+    }
+}
+
+/// Show a savestate with its screenshot, and let the user copy it to a slot,
+/// rename it or delete it. Returns `true` if it was copied.
+///
+/// Copying only writes the slot memory; cores restore a slot with their own
+/// "Load State" command, which cannot be triggered from here.
+fn savestate_details(app: &mut GoLEmApp, core: &mut MisterFpgaCore, mut ss: DbSaveState) -> bool {
+    let database = app.database();
+    let nb_slots = core.save_states().map_or(0, |s| s.nb_slots());
+
+    loop {
+        let action = savestate_alert(app, &ss, nb_slots);
+
+        match action {
+            SavestateAction::CopyToSlot(slot) => {
+                let result = std::fs::File::open(&ss.path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|mut f| {
+                        let state = core
+                            .save_states_mut()
+                            .and_then(|s| s.slots_mut().get_mut(slot))
+                            .ok_or("Invalid savestate slot")?;
+                        state.load(&mut f).map_err(|e| e.to_string())
+                    });
+
+                match result {
+                    Ok(()) => {
+                        if let Err(e) = ss.play(&mut database.lock().unwrap()) {
+                            error!(?e, "Could not update the savestate.");
+                        }
+                        let _ = alert(
+                            app,
+                            "Savestate Copied",
+                            &format!(
+                                "Copied to slot {}. Use the core's Load State command \
+                                 on that slot to restore it.",
+                                slot + 1
+                            ),
+                            &["Back"],
+                        );
+                        return true;
+                    }
+                    Err(e) => {
+                        error!(?e, "Could not copy the savestate.");
+                        let _ = alert(app, "Error", &e, &["Back"]);
+                    }
+                }
+            }
+            SavestateAction::Rename => {
+                let current = ss.name.clone().unwrap_or_default();
+                if let Some(name) = text_input(app, "Rename Savestate", &current) {
+                    let name = Some(name.trim().to_string()).filter(|n| !n.is_empty());
+                    if let Err(e) = ss.rename(&mut database.lock().unwrap(), name) {
+                        error!(?e, "Could not rename the savestate.");
+                    }
+                }
+            }
+            SavestateAction::Delete => {
+                let confirm = alert(
+                    app,
+                    "Delete Savestate",
+                    &format!("Delete \"{}\"? This cannot be undone.", label(&ss)),
+                    &["Delete", "Cancel"],
+                );
+                if confirm == Some(0) {
+                    if let Err(e) = std::fs::remove_file(&ss.path) {
+                        error!(?e, path = ?ss.path, "Could not delete the savestate file.");
+                    }
+                    if let Some(screenshot) = &ss.screenshot_path {
+                        if let Err(e) = std::fs::remove_file(screenshot) {
+                            error!(?e, path = ?screenshot, "Could not delete the screenshot.");
+                        }
+                    }
+                    if let Err(e) = ss.delete(&mut database.lock().unwrap()) {
+                        error!(?e, "Could not delete the savestate.");
+                    }
+                    return false;
+                }
+            }
+            SavestateAction::Back => return false,
+        }
+    }
+}
+
+fn savestate_alert(app: &mut GoLEmApp, ss: &DbSaveState, nb_slots: usize) -> SavestateAction {
+    let display_area = app.main_buffer().bounding_box();
+
+    let thumbnail = ss
+        .screenshot_path
+        .as_ref()
+        .and_then(|p| Thumbnail::from_path(p, Size::new(96, 72)).ok())
+        .unwrap_or_default();
+
+    let labels = (0..nb_slots)
+        .map(|i| format!("Copy to Slot {}", i + 1))
+        .collect::<Vec<_>>();
+    let mut items = labels
+        .iter()
+        .enumerate()
+        .map(|(i, l)| (l.as_str(), SavestateAction::CopyToSlot(i)))
+        .chain([
+            ("Rename", SavestateAction::Rename),
+            ("Delete", SavestateAction::Delete),
+            ("Back", SavestateAction::Back),
+        ])
+        .map(|(l, a)| MenuItem::new(l, a).with_value_converter(identity))
+        .collect::<Vec<_>>();
+
+    let menu = SizedMenu::new(
+        Size::new(128, 56),
+        Menu::with_style(" ", style::menu_style_simple())
+            .add_menu_items(&mut items)
+            .build(),
+    );
+
+    let title = label(ss);
+    let mut layout = LinearLayout::horizontal(
+        Chain::new(thumbnail).append(
+            LinearLayout::vertical(
+                Chain::new(Text::new(
+                    &title,
+                    Point::zero(),
+                    MonoTextStyle::new(&ascii::FONT_6X10, BinaryColor::On),
+                ))
+                .append(
+                    Line::new(
+                        Point::zero(),
+                        Point::new(display_area.bounding_box().size.width as i32 / 2, 0),
+                    )
+                    .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1)),
+                )
+                .append(menu),
+            )
+            .with_alignment(horizontal::Center)
+            .with_spacing(spacing::FixedMargin(2))
+            .arrange(),
+        ),
+    )
+    .with_alignment(vertical::Center)
+    .with_spacing(spacing::FixedMargin(4))
+    .arrange()
+    .align_to(&display_area, horizontal::Center, vertical::Center);
+
+    app.event_loop(move |app, state| {
+        let buffer = app.osd_buffer();
+        let _ = buffer.clear(BinaryColor::Off);
+        let _ = layout.draw(buffer);
+
+        let menu = &mut layout.inner_mut().object.inner_mut().object;
+        for ev in state.events() {
+            if let Some(action) = menu.interact(ev) {
+                return Some(action);
+            }
+        }
+        menu.update(buffer);
+
+        None
+    })
+}
@@ -9,6 +9,9 @@ enum MenuAction {
     ChangeTimestampFormat,
     ShowFps,
     InvertToolbar,
+    ScreenshotAspectRatio,
+    RecordingFormat,
+    RecordingMaxDuration,
     InputMapping,
     ResetAll,
     Back,
@@ -53,6 +56,25 @@ pub fn settings_panel(app: &mut GoLEmApp, core: &mut Option<&mut GolemCore>) {
                     },
                     MenuAction::InvertToolbar,
                 ),
+                (
+                    "Screenshot Aspect Ratio",
+                    if app.settings().screenshot_aspect_ratio() {
+                        "Core"
+                    } else {
+                        "Pixels"
+                    },
+                    MenuAction::ScreenshotAspectRatio,
+                ),
+                (
+                    "Recording Format",
+                    app.settings().recording_format().to_string().as_str(),
+                    MenuAction::RecordingFormat,
+                ),
+                (
+                    "Max Recording Duration",
+                    format!("{}s", app.settings().recording_max_duration().as_secs()).as_str(),
+                    MenuAction::RecordingMaxDuration,
+                ),
                 ("Input Mapping", "", MenuAction::InputMapping),
                 ("Reset all settings", "", MenuAction::ResetAll),
             ],
@@ -71,6 +93,15 @@ pub fn settings_panel(app: &mut GoLEmApp, core: &mut Option<&mut GolemCore>) {
             MenuAction::InvertToolbar => {
                 app.settings().toggle_invert_toolbar();
             }
+            MenuAction::ScreenshotAspectRatio => {
+                app.settings().toggle_screenshot_aspect_ratio();
+            }
+            MenuAction::RecordingFormat => {
+                app.settings().toggle_recording_format();
+            }
+            MenuAction::RecordingMaxDuration => {
+                app.settings().toggle_recording_max_duration();
+            }
             MenuAction::InputMapping => {
                 crate::application::panels::core_loop::menu::input_mapping::menu(app, core);
             }
@@ -0,0 +1,74 @@
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::mono_font::{ascii, MonoTextStyle};
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::primitives::{Line, PrimitiveStyle};
+use embedded_graphics::text::{Baseline, Text};
+use sdl3::event::Event;
+use sdl3::keyboard::Keycode;
+
+use crate::application::GoLEmApp;
+
+/// Ask the user to type a line of text with the keyboard, starting with
+/// `initial`. Returns `None` if the user cancelled.
+///
+/// There is no on-screen keyboard, so this needs a keyboard; gamepads
+/// cannot enter text here.
+pub fn text_input(app: &mut GoLEmApp, title: &str, initial: &str) -> Option<String> {
+    let display_area = app.main_buffer().bounding_box();
+    let width = display_area.size.width as i32;
+    let height = display_area.size.height as i32;
+
+    let title_style = MonoTextStyle::new(&ascii::FONT_8X13_BOLD, BinaryColor::On);
+    let text_style = MonoTextStyle::new(&ascii::FONT_6X10, BinaryColor::On);
+
+    let mut text = initial.to_string();
+
+    app.platform_mut().start_text_input();
+    let result = app.event_loop(move |app, state| {
+        let buffer = app.osd_buffer();
+        let _ = buffer.clear(BinaryColor::Off);
+        let _ =
+            Text::with_baseline(title, Point::new(1, 0), title_style, Baseline::Top).draw(buffer);
+        let _ = Line::new(Point::new(0, 14), Point::new(width, 14))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(buffer);
+        let _ = Text::with_baseline(
+            &format!("{text}_"),
+            Point::new(1, 18),
+            text_style,
+            Baseline::Top,
+        )
+        .draw(buffer);
+        let _ = Text::with_baseline(
+            "Keyboard only. Enter: OK  Esc: Cancel",
+            Point::new(1, height - 1),
+            text_style,
+            Baseline::Bottom,
+        )
+        .draw(buffer);
+
+        for ev in state.events() {
+            match ev {
+                Event::TextInput { text: typed, .. } => text.push_str(&typed),
+                Event::KeyDown {
+                    keycode: Some(keycode),
+                    ..
+                } => match keycode {
+                    Keycode::Return | Keycode::KpEnter => return Some(Some(text.clone())),
+                    Keycode::Escape => return Some(None),
+                    Keycode::Backspace => {
+                        text.pop();
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+        }
+
+        None
+    });
+    app.platform_mut().stop_text_input();
+
+    result
+}
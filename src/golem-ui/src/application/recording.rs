@@ -0,0 +1,179 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use cyclone_v::memory::DevMemMemoryMapper;
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, DynamicImage, Frame};
+use mister_fpga::framebuffer::FpgaFramebuffer;
+use tracing::{debug, info};
+
+use crate::data::paths;
+use crate::data::settings::RecordingFormat;
+
+/// How long to wait for a frame before checking whether the recording was
+/// stopped, e.g. when the core is paused.
+const FRAME_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Where the frames of a recording are written.
+enum Output {
+    Gif {
+        encoder: GifEncoder<BufWriter<File>>,
+        last: Option<(DynamicImage, Duration)>,
+    },
+    PngSequence {
+        dir: PathBuf,
+        count: usize,
+    },
+}
+
+impl Output {
+    fn create(path: &Path, format: RecordingFormat) -> Result<Self, String> {
+        match format {
+            RecordingFormat::Gif => {
+                let file = File::create(path).map_err(|e| e.to_string())?;
+                let mut encoder = GifEncoder::new(BufWriter::new(file));
+                encoder
+                    .set_repeat(Repeat::Infinite)
+                    .map_err(|e| e.to_string())?;
+                Ok(Self::Gif {
+                    encoder,
+                    last: None,
+                })
+            }
+            RecordingFormat::PngSequence => {
+                std::fs::create_dir_all(path).map_err(|e| e.to_string())?;
+                Ok(Self::PngSequence {
+                    dir: path.to_path_buf(),
+                    count: 0,
+                })
+            }
+        }
+    }
+
+    /// Add a frame, captured `at` since the start of the recording.
+    fn push(&mut self, image: DynamicImage, at: Duration) -> Result<(), String> {
+        match self {
+            Output::Gif { encoder, last } => {
+                // The delay of a GIF frame is only known once the next one
+                // is captured, so frames are written one behind.
+                if let Some((previous, previous_at)) = last.replace((image, at)) {
+                    Self::write_gif_frame(encoder, previous, at - previous_at)?;
+                }
+                Ok(())
+            }
+            Output::PngSequence { dir, count } => {
+                *count += 1;
+                image
+                    .save(dir.join(format!("{:06}.png", count)))
+                    .map_err(|e| e.to_string())
+            }
+        }
+    }
+
+    fn finish(self, end: Duration) -> Result<(), String> {
+        match self {
+            Output::Gif {
+                mut encoder,
+                last: Some((image, at)),
+            } => Self::write_gif_frame(&mut encoder, image, end.saturating_sub(at)),
+            _ => Ok(()),
+        }
+    }
+
+    fn write_gif_frame(
+        encoder: &mut GifEncoder<BufWriter<File>>,
+        image: DynamicImage,
+        delay: Duration,
+    ) -> Result<(), String> {
+        let frame = Frame::from_parts(
+            image.to_rgba8(),
+            0,
+            0,
+            Delay::from_saturating_duration(delay),
+        );
+        encoder.encode_frame(frame).map_err(|e| e.to_string())
+    }
+}
+
+/// A video recording of the core's output, from the scaler framebuffer.
+/// Frames are captured in a separate thread so the core keeps receiving
+/// inputs. Frames that come while the previous one is encoded are skipped.
+pub struct Recording {
+    path: PathBuf,
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<Result<usize, String>>,
+}
+
+impl Recording {
+    /// Start recording in the screenshots directory, for up to `max_duration`.
+    pub fn start(
+        core_name: &str,
+        format: RecordingFormat,
+        max_duration: Duration,
+    ) -> Result<Self, String> {
+        let timestamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S");
+        let name = format!("{}_{}", core_name.replace(' ', "_"), timestamp);
+        let path = match format {
+            RecordingFormat::Gif => paths::screenshots_root().join(format!("{name}.gif")),
+            RecordingFormat::PngSequence => paths::screenshots_root().join(name),
+        };
+        let mut output = Output::create(&path, format)?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = std::thread::spawn({
+            let stop = stop.clone();
+            move || {
+                let mut framebuffer = FpgaFramebuffer::<DevMemMemoryMapper>::create()?;
+                framebuffer.update_type_from_core();
+
+                let start = Instant::now();
+                let mut since = framebuffer.frame_counters();
+                let mut frames = 0;
+                while !stop.load(Ordering::Relaxed) && start.elapsed() < max_duration {
+                    let Some(wait) = framebuffer.wait_frame(since, Some(FRAME_TIMEOUT)) else {
+                        continue;
+                    };
+                    let index = wait.counters.newest().unwrap_or(0);
+                    since = wait.counters;
+
+                    let image = framebuffer.take_screenshot_of(index)?;
+                    output.push(image, start.elapsed())?;
+                    frames += 1;
+                    if wait.missed > 0 {
+                        debug!(missed = wait.missed, "Frames skipped while recording.");
+                    }
+                }
+
+                output.finish(start.elapsed())?;
+                Ok(frames)
+            }
+        });
+
+        info!(?path, "Recording started.");
+        Ok(Self { path, stop, thread })
+    }
+
+    /// Whether the recording stopped by itself (e.g. it reached its maximum
+    /// duration).
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    /// Stop the recording and wait for its file to be written. Returns the
+    /// path of the recording.
+    pub fn stop(self) -> Result<PathBuf, String> {
+        self.stop.store(true, Ordering::Relaxed);
+        let frames = self
+            .thread
+            .join()
+            .map_err(|_| "Recording thread panicked".to_string())??;
+
+        info!(path = ?self.path, frames, "Recording saved.");
+        Ok(self.path)
+    }
+}
@@ -13,6 +13,7 @@ pub mod menu;
 pub mod network;
 pub mod opt;
 pub mod text;
+pub mod thumbnail;
 
 #[derive(Debug, Copy, Clone)]
 pub struct EmptyView<C = BinaryColor>(Point, core::marker::PhantomData<C>);
@@ -0,0 +1,74 @@
+use std::path::Path;
+
+use embedded_graphics::draw_target::DrawTarget;
+use embedded_graphics::geometry::{Dimensions, Point, Size};
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::transform::Transform;
+use embedded_graphics::{Drawable, Pixel};
+use image::{DynamicImage, GrayImage};
+
+/// An image (e.g. a screenshot) scaled down and dithered to the 1-bit
+/// colors of the OSD.
+#[derive(Debug, Clone, Default)]
+pub struct Thumbnail {
+    top_left: Point,
+    image: GrayImage,
+}
+
+impl Thumbnail {
+    /// Create a thumbnail that fits in `max_size`, keeping the aspect ratio.
+    pub fn new(image: &DynamicImage, max_size: Size) -> Self {
+        let mut image = image.thumbnail(max_size.width, max_size.height).to_luma8();
+        image::imageops::dither(&mut image, &image::imageops::BiLevel);
+
+        Self {
+            top_left: Point::zero(),
+            image,
+        }
+    }
+
+    pub fn from_path(path: impl AsRef<Path>, max_size: Size) -> Result<Self, image::ImageError> {
+        Ok(Self::new(&image::open(path)?, max_size))
+    }
+}
+
+impl Dimensions for Thumbnail {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(
+            self.top_left,
+            Size::new(self.image.width(), self.image.height()),
+        )
+    }
+}
+
+impl Transform for Thumbnail {
+    fn translate(&self, by: Point) -> Self {
+        Self {
+            top_left: self.top_left + by,
+            image: self.image.clone(),
+        }
+    }
+
+    fn translate_mut(&mut self, by: Point) -> &mut Self {
+        self.top_left += by;
+        self
+    }
+}
+
+impl Drawable for Thumbnail {
+    type Color = BinaryColor;
+    type Output = ();
+
+    fn draw<D>(&self, target: &mut D) -> Result<Self::Output, D::Error>
+    where
+        D: DrawTarget<Color = Self::Color>,
+    {
+        target.draw_iter(self.image.enumerate_pixels().map(|(x, y, luma)| {
+            Pixel(
+                self.top_left + Point::new(x as i32, y as i32),
+                BinaryColor::from(luma.0[0] > 127),
+            )
+        }))
+    }
+}
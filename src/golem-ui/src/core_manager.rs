@@ -1,14 +1,23 @@
+use std::fs::File;
+use std::io::{Cursor, ErrorKind};
 use std::path::Path;
+use std::sync::Arc;
 use std::time::SystemTime;
 
 use byteorder::{LittleEndian, ReadBytesExt};
 
+use mister_fpga::core::buttons::ButtonMap;
+use mister_fpga::core::cheats::Cheats;
 use mister_fpga::core::file::SdCard;
-use mister_fpga::core::{MenuCore, MisterFpgaCore};
+use mister_fpga::core::{MenuCore, MisterFpgaCore, MisterFpgaSendFileInfo};
 use mister_fpga::fpga::MisterFpga;
-use one_fpga::core::SaveState;
+use mister_fpga::mra::{DipSwitches, Mra};
+use one_fpga::core::{Bios, BiosRequirement, SaveState};
 use one_fpga::runner::{CoreLaunchInfo, CoreType, Slot};
 use one_fpga::{Core, GolemCore};
+use tracing::warn;
+
+use crate::data::{core_options, dip_switches, paths};
 
 pub struct CoreManager {
     fpga: MisterFpga,
@@ -47,6 +56,10 @@ impl CoreManager {
         core.set_volume(0).map_err(|e| e.to_string())?;
         core.set_rtc(SystemTime::now()).map_err(|e| e.to_string())?;
 
+        if let Some(c) = core.as_any_mut().downcast_mut::<MisterFpgaCore>() {
+            core_options::restore(c);
+        }
+
         self.current_core = Some(core.clone());
         Ok(core)
     }
@@ -95,19 +108,155 @@ impl CoreManager {
         Ok(core)
     }
 
+    /// Load a core from its RBF file. The BIOS files the core needs can be
+    /// declared in a file next to it with the `.bios` extension (see
+    /// [`BiosRequirement::parse_list`]).
     pub fn load_core(&mut self, path: impl AsRef<Path>) -> Result<GolemCore, String> {
-        let bytes = std::fs::read(path.as_ref()).map_err(|e| e.to_string())?;
-        let core = self.load(&bytes, false)?;
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+        let mut core = self.load(&bytes, false)?;
+
+        let bios_path = path.with_extension("bios");
+        match std::fs::read_to_string(&bios_path) {
+            Ok(list) => {
+                if let Some(c) = core.as_any_mut().downcast_mut::<MisterFpgaCore>() {
+                    c.set_bios_requirements(BiosRequirement::parse_list(&list)?);
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("Could not read {}: {e}", bios_path.display())),
+        }
+        Ok(core)
+    }
+
+    /// Load an arcade game from its MRA file: load the core it names, then
+    /// build and send its ROMs. The DIP switches of the game are restored
+    /// from the last time it was played (see [`paths::dip_switches_path`]).
+    pub fn load_mra(&mut self, path: impl AsRef<Path>) -> Result<GolemCore, String> {
+        let path = path.as_ref();
+        let mra = Mra::from_path(path)?;
+        let mra_dir = path.parent().unwrap_or(Path::new("."));
+
+        let cores_dir = mra_dir.join("cores");
+        let core_root = paths::core_root_path();
+        let rbf = mra
+            .find_rbf(&[&cores_dir, mra_dir, &core_root])
+            .ok_or_else(|| format!("Could not find the core {} for {}.", mra.rbf, mra.name))?;
+        let mut core = self.load_core(rbf)?;
+
+        let c = core
+            .as_any_mut()
+            .downcast_mut::<MisterFpgaCore>()
+            .ok_or("Core is not a MiSTer core")?;
+
+        if let Some(buttons) = mra.buttons.as_ref().filter(|b| !b.default.is_empty()) {
+            let map = ButtonMap::map_from_snes_list(
+                &buttons
+                    .default
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>(),
+            );
+            for i in 0..6 {
+                if let Some(gamepad) = c.gamepad_mut(i) {
+                    *gamepad = map;
+                }
+            }
+        }
+
+        let roms_root = paths::arcade_roms_root_path();
+        for rom in &mra.roms {
+            let data = rom.build(&[mra_dir, &roms_root])?;
+            c.send_file(
+                MisterFpgaSendFileInfo::Buffered { index: rom.index },
+                "",
+                data.len() as u32,
+                Cursor::new(data),
+            )?;
+            c.end_send_file()?;
+        }
+
+        if let Some(switches) = &mra.switches {
+            let mut dips = DipSwitches::new(mra.key(), switches.clone());
+            dip_switches::restore(&mut dips);
+            c.set_dip_switches(Some(dips));
+            c.send_dip_switches()?;
+        }
+
         Ok(core)
     }
 
+    /// Find the BIOS files required by a core in its system BIOS directory
+    /// (see [`paths::bios_path`]). Returns an error naming the missing
+    /// files if a required BIOS cannot be found.
+    pub fn find_bios(&self, core: &GolemCore) -> Result<Vec<Bios>, String> {
+        let dir = paths::bios_path(core.name());
+        let mut bios = Vec::new();
+        let mut missing = Vec::new();
+
+        for requirement in core.bios_requirements() {
+            let found = requirement
+                .find_in(&dir)
+                .map_err(|e| format!("Could not read BIOS directory: {e}"))?;
+
+            match found {
+                Some(path) => {
+                    let file = File::open(&path).map_err(|e| e.to_string())?;
+                    bios.push(Bios::File(path, Arc::new(file)));
+                }
+                None if requirement.optional => {
+                    warn!(%requirement, ?dir, "Optional BIOS not found, continuing without it.");
+                }
+                None => missing.push(requirement.to_string()),
+            }
+        }
+
+        if !missing.is_empty() {
+            return Err(format!(
+                "Missing BIOS for {}: {}. Copy it to {}.",
+                core.name(),
+                missing.join(", "),
+                dir.display()
+            ));
+        }
+        Ok(bios)
+    }
+
+    /// Send the BIOS files a core needs, found with [`Self::find_bios`].
+    pub fn send_bios(&self, core: &mut GolemCore) -> Result<(), String> {
+        for bios in self.find_bios(core)? {
+            core.send_bios(bios).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
     pub fn launch(&mut self, info: CoreLaunchInfo<()>) -> Result<GolemCore, String> {
+        let is_running = matches!(info.core, CoreType::Current);
+        let is_mra = matches!(info.core, CoreType::MraFile(_));
         let mut golem_core = match info.core {
             CoreType::Current => self.get_current_core().ok_or("No core running")?,
             CoreType::Menu => self.load_menu()?,
             CoreType::RbfFile(path) => self.load_core(path)?,
+            CoreType::MraFile(path) => self.load_mra(path)?,
         };
 
+        // The BIOS needs to be sent before the ROM. If none were given, look
+        // for the ones the core needs (a running core already has them).
+        // Arcade games have theirs in the ROMs of their MRA, already sent.
+        if is_mra {
+            if !info.bios.is_empty() {
+                warn!("Ignoring the BIOS given for an MRA file.");
+            }
+        } else if info.bios.is_empty() {
+            if !is_running {
+                self.send_bios(&mut golem_core)?;
+            }
+        } else {
+            for bios in info.bios {
+                golem_core.send_bios(bios).map_err(|e| e.to_string())?;
+            }
+        }
+
         let mister_core = golem_core
             .as_any_mut()
             .downcast_mut::<MisterFpgaCore>()
@@ -117,6 +266,7 @@ impl CoreManager {
             mister_core
                 .send_rom(rom.clone())
                 .map_err(|e| e.to_string())?;
+            load_cheats(mister_core, rom.path())?;
         }
 
         if !info.files.is_empty() {
@@ -164,3 +314,22 @@ impl CoreManager {
         self.fpga_mut().osd_disable();
     }
 }
+
+/// Find the cheats of a game (see [`paths::cheats_path`]) and send them to
+/// the core, all disabled. Without a game, the cheats of the last one are
+/// removed. Does nothing if the core has no cheats menu.
+pub fn load_cheats(core: &mut MisterFpgaCore, rom: Option<&Path>) -> Result<(), String> {
+    if core.config().cheats_menu().is_none() {
+        return Ok(());
+    }
+
+    let cheats = match rom {
+        Some(rom) => Cheats::find(paths::cheats_path(core.name()), rom).unwrap_or_else(|e| {
+            warn!(?e, "Could not read the cheats of the game.");
+            None
+        }),
+        None => None,
+    };
+    core.set_cheats(cheats);
+    core.send_cheats()
+}
@@ -1,2 +1,4 @@
+pub mod core_options;
+pub mod dip_switches;
 pub mod paths;
 pub mod settings;
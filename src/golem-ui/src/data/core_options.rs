@@ -0,0 +1,80 @@
+//! Core options (the status bits chosen in the core settings) saved between
+//! sessions. They are saved per core in a file, like MiSTer `.CFG` files,
+//! and games can override them in the database.
+use std::io::ErrorKind;
+
+use tracing::warn;
+
+use golem_db::models::GameOptions;
+use golem_db::Connection;
+use mister_fpga::core::MisterFpgaCore;
+use mister_fpga::types::StatusBitMap;
+use one_fpga::Core;
+
+use crate::data::paths;
+
+/// Restore the options saved for the core, or their defaults if none were
+/// saved.
+pub fn restore(core: &mut MisterFpgaCore) {
+    let path = paths::core_options_path(core.name());
+    match std::fs::read(&path) {
+        Ok(bytes) => core.restore_option_status_bits(&StatusBitMap::from_bytes(&bytes)),
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            core.restore_option_status_bits(&StatusBitMap::new())
+        }
+        Err(e) => warn!(?e, ?path, "Could not read the core options"),
+    }
+}
+
+/// Save the current options of the core.
+pub fn save(core: &MisterFpgaCore) -> Result<(), String> {
+    let path = paths::core_options_path(core.name());
+    std::fs::write(path, core.option_status_bits().to_bytes()).map_err(|e| e.to_string())
+}
+
+/// Restore the options saved for a game. Returns whether the game has its
+/// own options.
+pub fn restore_for_game(
+    conn: &mut Connection,
+    core: &mut MisterFpgaCore,
+    game_id: i32,
+) -> Result<bool, String> {
+    let Some(options) = GameOptions::get_for_game(conn, game_id).map_err(|e| e.to_string())? else {
+        return Ok(false);
+    };
+
+    core.restore_option_status_bits(&StatusBitMap::from_bytes(&options.status_bits));
+    Ok(true)
+}
+
+/// Save the current options of the core for a game.
+pub fn save_for_game(
+    conn: &mut Connection,
+    core: &MisterFpgaCore,
+    core_id: i32,
+    game_id: i32,
+) -> Result<(), String> {
+    GameOptions::set(conn, core_id, game_id, core.option_status_bits().to_bytes())
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Reset the options of the core to their defaults, removing the saved
+/// options of the core and of the game.
+pub fn reset(
+    conn: &mut Connection,
+    core: &mut MisterFpgaCore,
+    game_id: Option<i32>,
+) -> Result<(), String> {
+    core.restore_option_status_bits(&StatusBitMap::new());
+
+    match std::fs::remove_file(paths::core_options_path(core.name())) {
+        Err(e) if e.kind() != ErrorKind::NotFound => return Err(e.to_string()),
+        _ => {}
+    }
+
+    if let Some(game_id) = game_id {
+        GameOptions::delete_for_game(conn, game_id).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,30 @@
+//! DIP switches of arcade games saved between sessions. They are saved per
+//! game in a file, like MiSTer `.dip` files.
+use std::io::ErrorKind;
+
+use tracing::warn;
+
+use mister_fpga::mra::DipSwitches;
+
+use crate::data::paths;
+
+/// Restore the DIP switches saved for the game, if any.
+pub fn restore(dip_switches: &mut DipSwitches) {
+    let path = paths::dip_switches_path(dip_switches.name());
+    match std::fs::read(&path) {
+        Ok(bytes) => {
+            let mut value = [0; 8];
+            let len = bytes.len().min(value.len());
+            value[..len].copy_from_slice(&bytes[..len]);
+            dip_switches.set_value(u64::from_le_bytes(value));
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => warn!(?e, ?path, "Could not read the DIP switches"),
+    }
+}
+
+/// Save the current DIP switches of the game.
+pub fn save(dip_switches: &DipSwitches) -> Result<(), String> {
+    let path = paths::dip_switches_path(dip_switches.name());
+    std::fs::write(path, dip_switches.value().to_le_bytes()).map_err(|e| e.to_string())
+}
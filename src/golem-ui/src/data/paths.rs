@@ -50,6 +50,63 @@ pub fn sav_root_path() -> PathBuf {
     p
 }
 
+pub fn core_options_root_path() -> PathBuf {
+    let p = config_root_path().join("configs");
+    if !p.exists() {
+        std::fs::create_dir_all(&p).unwrap();
+    }
+    p
+}
+
+/// The file where the options of a core are saved, e.g. `configs/NES.CFG`.
+pub fn core_options_path(core_name: &str) -> PathBuf {
+    core_options_root_path().join(format!("{core_name}.CFG"))
+}
+
+pub fn bios_root_path() -> PathBuf {
+    let p = config_root_path().join("bios");
+    if !p.exists() {
+        std::fs::create_dir_all(&p).unwrap();
+    }
+    p
+}
+
+/// The directory where the BIOS files of a system are, e.g. `bios/NES`.
+pub fn bios_path(core_name: &str) -> PathBuf {
+    bios_root_path().join(core_name)
+}
+
+pub fn cheats_root_path() -> PathBuf {
+    let p = config_root_path().join("cheats");
+    if !p.exists() {
+        std::fs::create_dir_all(&p).unwrap();
+    }
+    p
+}
+
+/// The directory where the cheat archives of a system are, e.g. `cheats/NES`.
+pub fn cheats_path(core_name: &str) -> PathBuf {
+    cheats_root_path().join(core_name)
+}
+
+pub fn arcade_roms_root_path() -> PathBuf {
+    let p = config_root_path().join("mame");
+    if !p.exists() {
+        std::fs::create_dir_all(&p).unwrap();
+    }
+    p
+}
+
+/// The file where the DIP switches of an arcade game are saved, e.g.
+/// `configs/dips/pacman.dip`.
+pub fn dip_switches_path(game_name: &str) -> PathBuf {
+    let p = core_options_root_path().join("dips");
+    if !p.exists() {
+        std::fs::create_dir_all(&p).unwrap();
+    }
+    p.join(format!("{game_name}.dip"))
+}
+
 pub fn savestates_path(core_name: &str) -> PathBuf {
     savestates_root_path().join(core_name)
 }
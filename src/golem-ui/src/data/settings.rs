@@ -90,6 +90,31 @@ impl DateTimeFormat {
     }
 }
 
+fn recording_max_duration_() -> u32 {
+    10
+}
+
+/// The file format of video recordings.
+#[derive(Default, Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize, Display)]
+pub enum RecordingFormat {
+    /// An animated GIF.
+    #[default]
+    Gif,
+
+    /// A directory with a numbered PNG file per frame, for encoding with
+    /// external tools.
+    PngSequence,
+}
+
+impl RecordingFormat {
+    pub fn next(&self) -> Self {
+        match self {
+            RecordingFormat::Gif => RecordingFormat::PngSequence,
+            RecordingFormat::PngSequence => RecordingFormat::Gif,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Hash, Serialize, Deserialize, Merge)]
 pub struct InnerSettings {
     #[serde(default = "show_fps_default_")]
@@ -104,6 +129,20 @@ pub struct InnerSettings {
     #[merge(strategy = merge::overwrite)]
     toolbar_datetime_format: DateTimeFormat,
 
+    /// Scale screenshots to the display aspect ratio of the core.
+    #[serde(default)]
+    #[merge(strategy = merge::overwrite)]
+    screenshot_aspect_ratio: bool,
+
+    #[serde(default)]
+    #[merge(strategy = merge::overwrite)]
+    recording_format: RecordingFormat,
+
+    /// The maximum duration of a video recording, in seconds.
+    #[serde(default = "recording_max_duration_")]
+    #[merge(strategy = merge::overwrite)]
+    recording_max_duration: u32,
+
     #[serde(default)]
     mappings: MappingSettings,
 
@@ -118,6 +157,9 @@ impl Default for InnerSettings {
             show_fps: false,
             invert_toolbar: true,
             toolbar_datetime_format: DateTimeFormat::default(),
+            screenshot_aspect_ratio: false,
+            recording_format: RecordingFormat::default(),
+            recording_max_duration: recording_max_duration_(),
             mappings: MappingSettings::default(),
             language: None,
         }
@@ -283,6 +325,21 @@ impl Settings {
         self.inner.read().unwrap().toolbar_datetime_format
     }
 
+    #[inline]
+    pub fn screenshot_aspect_ratio(&self) -> bool {
+        self.inner.read().unwrap().screenshot_aspect_ratio
+    }
+
+    #[inline]
+    pub fn recording_format(&self) -> RecordingFormat {
+        self.inner.read().unwrap().recording_format
+    }
+
+    #[inline]
+    pub fn recording_max_duration(&self) -> Duration {
+        Duration::from_secs(self.inner.read().unwrap().recording_max_duration as u64)
+    }
+
     #[inline]
     pub fn toggle_show_fps(&self) {
         let mut inner = self.inner.write().unwrap();
@@ -295,6 +352,31 @@ impl Settings {
         inner.invert_toolbar = !inner.invert_toolbar;
     }
 
+    #[inline]
+    pub fn toggle_screenshot_aspect_ratio(&self) {
+        let mut inner = self.inner.write().unwrap();
+        inner.screenshot_aspect_ratio = !inner.screenshot_aspect_ratio;
+    }
+
+    #[inline]
+    pub fn toggle_recording_format(&self) {
+        let mut inner = self.inner.write().unwrap();
+        inner.recording_format = inner.recording_format.next();
+    }
+
+    /// Cycle through the available maximum durations of recordings.
+    #[inline]
+    pub fn toggle_recording_max_duration(&self) {
+        let mut inner = self.inner.write().unwrap();
+        inner.recording_max_duration = match inner.recording_max_duration {
+            0..=4 => 5,
+            5..=9 => 10,
+            10..=29 => 30,
+            30..=59 => 60,
+            _ => 5,
+        };
+    }
+
     #[inline]
     pub fn toggle_toolbar_datetime_format(&self) {
         let mut inner = self.inner.write().unwrap();
@@ -354,6 +436,6 @@ fn serializes() {
     let serialized = json5::to_string(&settings).unwrap();
     assert_eq!(
         serialized,
-        r#"{"show_fps":false,"invert_toolbar":true,"toolbar_datetime_format":"Default","mappings":{"quit_core":"'F10'","reset_core":"'F11'","show_menu":["'A'","'F12'"],"take_screenshot":"'SysReq'"},"language":null}"#
+        r#"{"show_fps":false,"invert_toolbar":true,"toolbar_datetime_format":"Default","screenshot_aspect_ratio":false,"recording_format":"Gif","recording_max_duration":10,"mappings":{"quit_core":"'F10'","reset_core":"'F11'","show_menu":["'A'","'F12'"],"take_screenshot":"'SysReq'","toggle_recording":"'ScrollLock'"},"language":null}"#
     );
 }
@@ -218,7 +218,7 @@ fn serializes() {
     let serialized = json5::to_string(&settings).unwrap();
     assert_eq!(
         serialized,
-        r#"{"quit_core":"'F10'","reset_core":"'F11'","show_menu":"'F12'","take_screenshot":"'SysReq'"}"#
+        r#"{"quit_core":"'F10'","reset_core":"'F11'","show_menu":"'F12'","take_screenshot":"'SysReq'","toggle_recording":"'ScrollLock'"}"#
     );
 
     let new_settings = json5::from_str(&serialized).unwrap();
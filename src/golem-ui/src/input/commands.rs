@@ -10,6 +10,7 @@ use mister_fpga::core::MisterFpgaCore;
 use one_fpga::Core;
 
 use crate::application::panels::core_loop::menu::core_menu;
+use crate::application::recording::Recording;
 use crate::application::GoLEmApp;
 use crate::data::paths;
 use crate::input::shortcut::Shortcut;
@@ -29,6 +30,10 @@ pub enum ShortcutCommand {
     /// Take a screenshot and saves it to the screenshots folder.
     TakeScreenshot,
 
+    /// Start recording a video to the screenshots folder, or stop the
+    /// current recording.
+    ToggleRecording,
+
     /// This is a core-specific command, which is identified by a `u32` hash of its
     /// label. This allows cores to change the order of their bits, as long as
     /// the label stays the same. The hash is considered safe enough for this purpose
@@ -58,6 +63,7 @@ impl Display for ShortcutCommand {
             ShortcutCommand::ResetCore => write!(f, "Reset Core"),
             ShortcutCommand::QuitCore => write!(f, "Quit Core"),
             ShortcutCommand::TakeScreenshot => write!(f, "Take Screenshot"),
+            ShortcutCommand::ToggleRecording => write!(f, "Record Video"),
             ShortcutCommand::CoreSpecificCommand(id) => write!(f, "Core Specific Command {id}"),
             ShortcutCommand::JavaScriptCommand(id) => write!(f, "JavaScript Command {id}"),
         }
@@ -73,6 +79,7 @@ impl FromStr for ShortcutCommand {
             "reset_core" => Ok(ShortcutCommand::ResetCore),
             "quit_core" => Ok(ShortcutCommand::QuitCore),
             "take_screenshot" => Ok(ShortcutCommand::TakeScreenshot),
+            "toggle_recording" => Ok(ShortcutCommand::ToggleRecording),
             _ => Err("Invalid shortcut"),
         }
     }
@@ -85,6 +92,7 @@ impl ShortcutCommand {
             ShortcutCommand::ResetCore,
             ShortcutCommand::QuitCore,
             ShortcutCommand::TakeScreenshot,
+            ShortcutCommand::ToggleRecording,
         ]
     }
 
@@ -94,6 +102,7 @@ impl ShortcutCommand {
             ShortcutCommand::ResetCore => Some("reset_core"),
             ShortcutCommand::QuitCore => Some("quit_core"),
             ShortcutCommand::TakeScreenshot => Some("take_screenshot"),
+            ShortcutCommand::ToggleRecording => Some("toggle_recording"),
             ShortcutCommand::CoreSpecificCommand(_) => None,
             ShortcutCommand::JavaScriptCommand(_) => None,
         }
@@ -105,6 +114,9 @@ impl ShortcutCommand {
             ShortcutCommand::ResetCore => Some(Shortcut::default().with_key(Scancode::F11)),
             ShortcutCommand::QuitCore => Some(Shortcut::default().with_key(Scancode::F10)),
             ShortcutCommand::TakeScreenshot => Some(Shortcut::default().with_key(Scancode::SysReq)),
+            ShortcutCommand::ToggleRecording => {
+                Some(Shortcut::default().with_key(Scancode::ScrollLock))
+            }
             ShortcutCommand::CoreSpecificCommand(_) => None,
             ShortcutCommand::JavaScriptCommand(_) => None,
         }
@@ -134,10 +146,16 @@ impl ShortcutCommand {
             ShortcutCommand::TakeScreenshot => {
                 debug!("Taking screenshot");
                 let start = Instant::now();
-                let img = match core.screenshot() {
+                let img = match core.as_any_mut().downcast_mut::<MisterFpgaCore>() {
+                    Some(c) if app.settings().screenshot_aspect_ratio() => {
+                        c.take_screenshot_with_aspect_ratio()
+                    }
+                    _ => core.screenshot().map_err(|e| e.to_string()),
+                };
+                let img = match img {
                     Ok(img) => img,
                     Err(e) => {
-                        return CommandResult::Err(e.to_string());
+                        return CommandResult::Err(e);
                     }
                 };
 
@@ -163,6 +181,35 @@ impl ShortcutCommand {
 
                 CommandResult::Ok
             }
+            ShortcutCommand::ToggleRecording => {
+                // Stop the current recording, unless it already stopped by
+                // itself, in which case a new one is started.
+                if let Some(recording) = app.recording_mut().take() {
+                    let finished = recording.is_finished();
+                    match recording.stop() {
+                        Ok(path) if !finished => {
+                            debug!(?path, "Recording stopped");
+                            return CommandResult::Ok;
+                        }
+                        Err(e) if !finished => return CommandResult::Err(e),
+                        Err(e) => error!("Error recording video: {}", e),
+                        Ok(_) => {}
+                    }
+                }
+
+                let settings = app.settings();
+                match Recording::start(
+                    core.name(),
+                    settings.recording_format(),
+                    settings.recording_max_duration(),
+                ) {
+                    Ok(recording) => {
+                        *app.recording_mut() = Some(recording);
+                        CommandResult::Ok
+                    }
+                    Err(e) => CommandResult::Err(format!("Error starting recording: {}", e)),
+                }
+            }
             ShortcutCommand::CoreSpecificCommand(id) => {
                 let Some(core) = core.as_any_mut().downcast_mut::<MisterFpgaCore>() else {
                     error!("Core is not a MisterFPGA core");
@@ -34,6 +34,15 @@ impl<C: PixelColor> Window<C> {
     pub fn focus(&mut self) {
         self.inner.focus();
     }
+
+    /// Start receiving `TextInput` events for this window.
+    pub fn start_text_input(&self) {
+        self.inner.start_text_input();
+    }
+
+    pub fn stop_text_input(&self) {
+        self.inner.stop_text_input();
+    }
 }
 
 impl<C: PixelColor + From<Rgb888> + Into<Rgb888>> Window<C> {
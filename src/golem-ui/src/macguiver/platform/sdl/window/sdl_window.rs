@@ -85,6 +85,16 @@ impl SdlWindow {
     pub fn focus(&mut self) {
         self.canvas.window_mut().raise();
     }
+
+    pub fn start_text_input(&self) {
+        let window = self.canvas.window();
+        window.subsystem().text_input().start(window);
+    }
+
+    pub fn stop_text_input(&self) {
+        let window = self.canvas.window();
+        window.subsystem().text_input().stop(window);
+    }
 }
 
 #[ouroboros::self_referencing]
@@ -19,7 +19,7 @@ const SDL_VIDEO_DRIVER_DEFAULT: &str = "evdev";
 
 pub struct De10Platform {
     pub platform: SdlPlatform<BinaryColor>,
-    _window: Window<BinaryColor>,
+    window: Window<BinaryColor>,
     title_display: OsdDisplay,
     osd_display: OsdDisplay,
     core_manager: CoreManager,
@@ -50,7 +50,7 @@ impl Default for De10Platform {
 
         Self {
             platform,
-            _window: window,
+            window,
             title_display: OsdDisplay::title(),
             osd_display: OsdDisplay::main(),
             core_manager,
@@ -106,6 +106,14 @@ impl De10Platform {
         self.platform.events()
     }
 
+    pub fn start_text_input(&mut self) {
+        self.window.start_text_input();
+    }
+
+    pub fn stop_text_input(&mut self) {
+        self.window.stop_text_input();
+    }
+
     pub fn sdl(&mut self) -> &mut SdlPlatform<BinaryColor> {
         &mut self.platform
     }
@@ -898,6 +898,34 @@ impl MisterConfig {
     pub fn forced_scandoubler(&self) -> bool {
         self.forced_scandoubler.unwrap_or_default()
     }
+
+    /// The divider of the mouse speed, at least 1.
+    #[inline]
+    pub fn mouse_throttle(&self) -> u8 {
+        self.mouse_throttle.unwrap_or_default().max(1)
+    }
+
+    /// Whether all mice should also be used as spinners.
+    #[inline]
+    pub fn mouse_as_spinner(&self) -> bool {
+        self.spinner_vid == Some(0xFFFF) && self.spinner_pid == Some(0xFFFF)
+    }
+
+    /// The mouse axis used to emulate a spinner (0 for X, 1 for Y, 2 for the wheel).
+    #[inline]
+    pub fn spinner_axis(&self) -> u8 {
+        self.spinner_axis.unwrap_or_default()
+    }
+
+    /// The speed of the spinner, where 100 is the mouse speed, higher is slower
+    /// and negative values reverse the direction.
+    #[inline]
+    pub fn spinner_throttle(&self) -> i32 {
+        match self.spinner_throttle {
+            None | Some(0) => 100,
+            Some(throttle) => throttle,
+        }
+    }
 }
 
 #[cfg(test)]
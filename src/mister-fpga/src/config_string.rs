@@ -4,6 +4,7 @@
 //!
 //! This is located in utils to allow to run test. There is no FPGA or MiSTer specific
 //! code in this module, even though it isn't used outside of MiSTer itself.
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::fmt::{Debug, Formatter};
 use std::ops::{Deref, Range};
@@ -15,7 +16,8 @@ use once_cell::sync::Lazy;
 use regex::Regex;
 use tracing::debug;
 
-use one_fpga::core::CoreMenuItem;
+use cyclone_v::memory::MemoryMapper;
+use one_fpga::core::{ConfigMenuId, CoreMenuItem};
 pub use types::*;
 
 use crate::fpga::user_io;
@@ -99,6 +101,12 @@ pub struct LoadFileInfo {
     pub address: Option<FpgaRamMemoryAddress>,
 }
 
+/// Whether `bit` is set in the menu mask of a core, which the hide and
+/// disable conditions of the menu are checked against.
+pub fn menu_mask_get(menu_mask: u16, bit: u32) -> bool {
+    bit < u16::BITS && menu_mask & (1 << bit) != 0
+}
+
 /// A component of a Core config string.
 #[derive(Debug, Clone)]
 pub enum ConfigMenu {
@@ -256,8 +264,8 @@ impl ConfigMenu {
             | ConfigMenu::DisableUnless(_, sub)
             | ConfigMenu::HideIf(_, sub)
             | ConfigMenu::HideUnless(_, sub) => sub.label(),
+            ConfigMenu::Cheat(name) => name.as_deref(),
             // TODO: add those.
-            // ConfigMenu::Cheat(name) => name.as_ref().map(|x| x.as_str()),
             // ConfigMenu::LoadFileAndRemember(info) | ConfigMenu::LoadFile(info) => {
             //     info.label.as_ref().map(|l| l.as_str())
             // }
@@ -280,6 +288,24 @@ impl ConfigMenu {
         self.label().map(Self::id_from_str)
     }
 
+    /// The ID of this option or trigger in the generic core menu. Unlike
+    /// [`Self::id`], this also hashes the status bits of the item, so items
+    /// with the same label (e.g. on different pages) have different IDs.
+    pub fn menu_id(&self) -> Option<ConfigMenuId> {
+        let (label, bits) = match self.as_option().or_else(|| self.as_trigger())? {
+            ConfigMenu::Option { label, bits, .. } => (label, bits.clone()),
+            ConfigMenu::Trigger { label, index, .. } => (label, *index..*index + 1),
+            _ => return None,
+        };
+
+        let id = [bits.start, bits.end]
+            .into_iter()
+            .fold(Self::id_from_str(label), |id, b| {
+                id.wrapping_mul(223).wrapping_add(b as u32)
+            });
+        Some(ConfigMenuId::new(id))
+    }
+
     pub fn page(&self) -> Option<u8> {
         match self {
             ConfigMenu::DisableIf(_, inner) => inner.page(),
@@ -291,6 +317,86 @@ impl ConfigMenu {
             _ => None,
         }
     }
+
+    /// Convert this item to a generic [`CoreMenuItem`], using the current
+    /// `status` bits for option values and the core's `menu_mask` for
+    /// hide/disable conditions. Pages are
+    /// returned empty, and page items are returned without their page (see
+    /// [`Config::as_core_menu`]). Disabled items become unselectable labels.
+    /// Returns `None` if the item is hidden or has no generic equivalent.
+    pub fn as_core_menu_item(&self, status: &StatusBitMap, menu_mask: u16) -> Option<CoreMenuItem> {
+        fn disabled(item: CoreMenuItem) -> CoreMenuItem {
+            match item {
+                CoreMenuItem::Trigger { label, .. } | CoreMenuItem::IntOption { label, .. } => {
+                    CoreMenuItem::Label {
+                        selectable: false,
+                        label,
+                    }
+                }
+                other => other,
+            }
+        }
+
+        match self {
+            ConfigMenu::Empty(None) => Some(CoreMenuItem::Separator),
+            ConfigMenu::Empty(Some(label)) => Some(CoreMenuItem::Label {
+                selectable: false,
+                label: label.clone(),
+            }),
+            ConfigMenu::Option {
+                bits,
+                label,
+                choices,
+            } => Some(CoreMenuItem::IntOption {
+                id: self.menu_id()?,
+                label: label.clone(),
+                choices: choices.clone(),
+                value: status.get_range(bits.clone()),
+            }),
+            ConfigMenu::Trigger { label, .. } => Some(CoreMenuItem::Trigger {
+                id: self.menu_id()?,
+                label: label.clone(),
+            }),
+            ConfigMenu::Page { index, label } => Some(CoreMenuItem::Page {
+                sort: *index as i32,
+                label: label.clone(),
+                title: label.clone(),
+                items: Vec::new(),
+            }),
+            ConfigMenu::HideIf(b, sub) => {
+                if menu_mask_get(menu_mask, *b) {
+                    None
+                } else {
+                    sub.as_core_menu_item(status, menu_mask)
+                }
+            }
+            ConfigMenu::HideUnless(b, sub) => {
+                if menu_mask_get(menu_mask, *b) {
+                    sub.as_core_menu_item(status, menu_mask)
+                } else {
+                    None
+                }
+            }
+            ConfigMenu::DisableIf(b, sub) => {
+                let item = sub.as_core_menu_item(status, menu_mask)?;
+                Some(if menu_mask_get(menu_mask, *b) {
+                    disabled(item)
+                } else {
+                    item
+                })
+            }
+            ConfigMenu::DisableUnless(b, sub) => {
+                let item = sub.as_core_menu_item(status, menu_mask)?;
+                Some(if menu_mask_get(menu_mask, *b) {
+                    item
+                } else {
+                    disabled(item)
+                })
+            }
+            ConfigMenu::PageItem(_, sub) => sub.as_core_menu_item(status, menu_mask),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -308,7 +414,9 @@ pub struct Config {
 impl Config {
     /// Create a new config from the FPGA.
     /// This is disabled in Test as this module is still included in the test build.
-    pub fn from_fpga(fpga: &mut crate::fpga::MisterFpga) -> Result<Self, String> {
+    pub fn from_fpga(
+        fpga: &mut crate::fpga::MisterFpga<impl MemoryMapper>,
+    ) -> Result<Self, String> {
         let mut cfg_string = String::with_capacity(1024);
         fpga.spi_mut()
             .execute(user_io::UserIoGetString(&mut cfg_string))?;
@@ -339,6 +447,20 @@ impl Config {
         arr
     }
 
+    /// The mask of the status bits used by options (`O` entries), without
+    /// the reset bit and triggers. These are the bits kept between sessions.
+    pub fn option_bit_map_mask(&self) -> StatusBitMap {
+        let mut arr = StatusBitMap::new();
+        for item in self.menu.iter() {
+            if let Some(ConfigMenu::Option { ref bits, .. }) = item.as_option() {
+                for i in bits.clone() {
+                    arr.set(i as usize, true);
+                }
+            }
+        }
+        arr
+    }
+
     pub fn load_info(&self, path: impl AsRef<Path>) -> Result<Option<LoadFileInfo>, String> {
         let path_ext = match path.as_ref().extension() {
             Some(ext) => ext.to_string_lossy(),
@@ -359,6 +481,44 @@ impl Config {
         Ok(None)
     }
 
+    /// The load file entry used for the BIOS of the core, if any. This is
+    /// the first `F` entry on index 0 (`F0`) or that has `BIOS` in its label.
+    pub fn bios_load_info(&self) -> Option<LoadFileInfo> {
+        self.menu.iter().find_map(|item| {
+            let info = item.as_load_file()?.as_load_file_info()?;
+            let is_bios = info.index == 0
+                || info
+                    .label
+                    .as_ref()
+                    .is_some_and(|label| label.to_lowercase().contains("bios"));
+            is_bios.then(|| info.clone())
+        })
+    }
+
+    /// The `C` entry of the menu, if the core supports cheats. Its label is
+    /// the name of the cheats menu.
+    pub fn cheats_menu(&self) -> Option<&ConfigMenu> {
+        self.menu
+            .iter()
+            .find(|item| matches!(item, ConfigMenu::Cheat(_)))
+    }
+
+    /// Whether the menu has a `DIP` entry, i.e. the core reads the DIP
+    /// switches of arcade games.
+    pub fn has_dip_switches(&self) -> bool {
+        self.menu.iter().any(|item| matches!(item, ConfigMenu::Dip))
+    }
+
+    /// Find a menu item by its ID (see [`ConfigMenu::id`]).
+    pub fn find_menu(&self, id: u32) -> Option<&ConfigMenu> {
+        self.menu.iter().find(|item| item.id() == Some(id))
+    }
+
+    /// Find an option or trigger by its [`ConfigMenu::menu_id`].
+    pub fn find_menu_item(&self, id: ConfigMenuId) -> Option<&ConfigMenu> {
+        self.menu.iter().find(|item| item.menu_id() == Some(id))
+    }
+
     pub fn snes_default_button_list(&self) -> Option<&Vec<String>> {
         for item in self.menu.iter() {
             if let ConfigMenu::SnesButtonDefaultList { ref buttons } = item {
@@ -377,8 +537,53 @@ impl Config {
         None
     }
 
-    pub fn as_core_menu(&self) -> Vec<CoreMenuItem> {
-        todo!()
+    /// Find the trigger whose whole label is one of `labels` (case-insensitive),
+    /// and return its status bit index.
+    pub fn find_trigger_by_label(&self, labels: &[&str]) -> Option<u8> {
+        self.menu.iter().find_map(|item| match item.as_trigger() {
+            Some(ConfigMenu::Trigger { index, label, .. })
+                if labels.iter().any(|l| l.eq_ignore_ascii_case(label)) =>
+            {
+                Some(*index)
+            }
+            _ => None,
+        })
+    }
+
+    /// The menu of the core as generic [`CoreMenuItem`]s, with the current
+    /// values of options taken from `status` and the hide/disable conditions
+    /// checked against `menu_mask`. Items of a page are moved inside
+    /// it; items of hidden or undeclared pages are dropped.
+    ///
+    /// IDs are computed from labels and status bits (see
+    /// [`ConfigMenu::menu_id`]), so they stay the same if the config string
+    /// is reordered.
+    pub fn as_core_menu(&self, status: &StatusBitMap, menu_mask: u16) -> Vec<CoreMenuItem> {
+        let mut result = Vec::new();
+        let mut pages = HashMap::new();
+
+        for item in self.menu.iter() {
+            let Some(menu_item) = item.as_core_menu_item(status, menu_mask) else {
+                continue;
+            };
+
+            match (menu_item, item.page()) {
+                (page @ CoreMenuItem::Page { .. }, Some(index)) => {
+                    pages.insert(index, result.len());
+                    result.push(page);
+                }
+                (menu_item, Some(index)) => {
+                    if let Some(CoreMenuItem::Page { items, .. }) =
+                        pages.get(&index).and_then(|i| result.get_mut(*i))
+                    {
+                        items.push(menu_item);
+                    }
+                }
+                (menu_item, None) => result.push(menu_item),
+            }
+        }
+
+        result
     }
 }
 
@@ -483,6 +688,46 @@ fn config_string_nes() {
     );
 
     assert!(config.is_ok(), "{:?}", config);
+    let config = config.unwrap();
+    let bios = config.bios_load_info().unwrap();
+    assert_eq!(bios.index, 2);
+    assert_eq!(bios.label.as_deref(), Some("Load FDS BIOS"));
+
+    assert_eq!(config.cheats_menu().and_then(|c| c.label()), Some("Cheats"));
+    assert!(!config.has_dip_switches());
+
+    let reset = config.find_menu(ConfigMenu::id_from_str("Reset")).unwrap();
+    assert!(matches!(
+        reset.as_trigger(),
+        Some(ConfigMenu::Trigger { index: 0, .. })
+    ));
+
+    let settings = config.settings();
+    assert_eq!(settings.save_state.unwrap().1, 0x200000);
+    assert_eq!(settings.nb_save_state_slots(), 4);
+    assert_eq!(settings.uart_mode[0].speed, 31250);
+    assert_eq!(settings.midi_mode[0].speed, 31250);
+}
+
+#[test]
+fn config_string_settings() {
+    let settings = settings::Settings::from_str("SS3E000000:80000,2,UART115200").unwrap();
+    let (base, size) = settings.save_state.unwrap();
+    assert_eq!(base.as_u32(), 0x3E000000);
+    assert_eq!(size, 0x80000);
+    assert_eq!(settings.nb_save_state_slots(), 2);
+    assert_eq!(settings.uart_mode[0].speed, 115200);
+
+    let settings = settings::Settings::from_str("UART31250,MIDI").unwrap();
+    assert_eq!(settings.nb_save_state_slots(), 0);
+    assert_eq!(settings.midi_mode.len(), 1);
+
+    assert!(settings::Settings::from_str("SS3E000000:80000,0").is_err());
+
+    // 16 slots of 128MiB go past the end of the FPGA memory.
+    assert!(settings::Settings::from_str("SS3E000000:8000000,16").is_err());
+    assert!(settings::Settings::from_str("SS20000000:8000000,4").is_err());
+    assert!(settings::Settings::from_str("SS20000000:4000000,4").is_ok());
 }
 
 #[test]
@@ -640,6 +885,9 @@ fn input_tester() {
     assert!(config.is_ok(), "{:?}", config);
     let config = config.unwrap();
     assert!(config.settings.uart_mode.is_empty());
+    assert_eq!(config.find_trigger_by_label(&["open menu"]), Some(10));
+    assert_eq!(config.find_trigger_by_label(&["coin", "reset"]), Some(0));
+    assert_eq!(config.find_trigger_by_label(&["coin"]), None);
 }
 
 #[test]
@@ -699,3 +947,119 @@ fn config_string_gba() {
     );
     assert!(config.is_ok(), "{:?}", config);
 }
+
+#[test]
+fn config_string_as_core_menu() {
+    let config = Config::from_str(
+        [
+            "Test;;",
+            "O12,Region,NTSC,PAL,Dendy;",
+            "H3T4,Hidden Trigger;",
+            "h3O4,Shown Option,Off,On;",
+            "d6R0,Reset;",
+            "-;",
+            "DIP;",
+            "P1,Video;",
+            "P1O7,Scanlines,Off,On;",
+            "D5P1O8,Crop,Off,On;",
+            "H3P2,Hidden Page;",
+            "P2O9,Hidden Page Option,Off,On;",
+        ]
+        .join("")
+        .as_str(),
+    )
+    .unwrap();
+
+    let mut status = StatusBitMap::new();
+    status.set_range(1..3, 2);
+    status.set(7, true);
+
+    // Conditions use the menu mask, not the status bits.
+    let mut menu_mask = (1 << 3) | (1 << 5);
+
+    assert!(config.has_dip_switches());
+
+    let menu_id = |label| {
+        config
+            .find_menu(ConfigMenu::id_from_str(label))
+            .and_then(ConfigMenu::menu_id)
+    };
+    let menu = config.as_core_menu(&status, menu_mask);
+    assert_eq!(menu.len(), 5);
+    assert!(matches!(
+        &menu[0],
+        CoreMenuItem::IntOption { id, label, choices, value: 2 }
+            if Some(*id) == menu_id("Region")
+                && label == "Region"
+                && choices.len() == 3
+    ));
+    assert!(matches!(
+        &menu[1],
+        CoreMenuItem::IntOption { label, value: 0, .. } if label == "Shown Option"
+    ));
+    assert!(matches!(
+        &menu[2],
+        CoreMenuItem::Label { selectable: false, label } if label == "Reset"
+    ));
+    assert!(matches!(&menu[3], CoreMenuItem::Separator));
+
+    let CoreMenuItem::Page {
+        sort, title, items, ..
+    } = &menu[4]
+    else {
+        panic!("Expected a page");
+    };
+    assert_eq!(*sort, 1);
+    assert_eq!(title, "Video");
+    assert_eq!(items.len(), 2);
+    assert!(matches!(
+        &items[0],
+        CoreMenuItem::IntOption { label, value: 1, .. } if label == "Scanlines"
+    ));
+    assert!(matches!(
+        &items[1],
+        CoreMenuItem::Label { selectable: false, label } if label == "Crop"
+    ));
+
+    // The reset is enabled once its bit is set, and keeps its ID.
+    menu_mask |= 1 << 6;
+    let menu = config.as_core_menu(&status, menu_mask);
+    assert!(matches!(
+        &menu[2],
+        CoreMenuItem::Trigger { id, .. }
+            if Some(*id) == menu_id("Reset")
+    ));
+}
+
+#[test]
+fn config_string_find_trigger_by_label() {
+    let config = Config::from_str("NES;;T1,Insert Disk;T2,Eject Disk;R0,Reset;V,v1").unwrap();
+
+    assert_eq!(config.find_trigger_by_label(&["insert disk"]), Some(1));
+    assert_eq!(config.find_trigger_by_label(&["Disk"]), None);
+    assert_eq!(config.find_trigger_by_label(&["eject"]), None);
+    assert_eq!(config.find_trigger_by_label(&["Eject Disk"]), Some(2));
+}
+
+#[test]
+fn config_string_menu_id_duplicate_labels() {
+    let config = Config::from_str(
+        "Test;;P1,Player 1;P2,Player 2;P1O12,Controller,Pad,Mouse;P2O34,Controller,Pad,Mouse;\
+         P1T5,Swap;P2T6,Swap;V,v1",
+    )
+    .unwrap();
+
+    let ids = config
+        .menu
+        .iter()
+        .filter_map(ConfigMenu::menu_id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids.len(), 4);
+    assert_ne!(ids[0], ids[1]);
+    assert_ne!(ids[2], ids[3]);
+
+    let item = config.find_menu_item(ids[1]).and_then(|m| m.as_option());
+    assert!(matches!(item, Some(ConfigMenu::Option { bits, .. }) if *bits == (3..5)));
+    let item = config.find_menu_item(ids[3]).and_then(|m| m.as_trigger());
+    assert!(matches!(item, Some(ConfigMenu::Trigger { index: 6, .. })));
+}
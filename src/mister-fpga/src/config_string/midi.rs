@@ -15,7 +15,7 @@ impl FromStr for MidiSpeed {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let captures = LABELED_SPEED_RE.captures(s).ok_or("Invalid MIDI mode")?;
 
-        let speed = match captures.get(1) {
+        let speed = match captures.get(1).filter(|s| !s.as_str().is_empty()) {
             None => DEFAULT_MIDI_SPEED,
             Some(s) => s.as_str().parse::<u32>().map_err(|_| "Invalid MIDI mode")?,
         };
@@ -95,7 +95,7 @@ fn disable_unless(line: u8) -> impl FnMut(Input) -> Result<ConfigMenu> {
     move |input| {
         map(
             tuple((char('d'), integer, config_menu_line(line))),
-            |(_, s, c)| ConfigMenu::DisableIf(s, Box::new(c)),
+            |(_, s, c)| ConfigMenu::DisableUnless(s, Box::new(c)),
         )(input)
     }
 }
@@ -115,7 +115,7 @@ fn hide_unless(line: u8) -> impl FnMut(Input) -> Result<ConfigMenu> {
     move |input| {
         map(
             tuple((char('h'), integer, config_menu_line(line))),
-            |(_, s, c)| ConfigMenu::HideIf(s, Box::new(c)),
+            |(_, s, c)| ConfigMenu::HideUnless(s, Box::new(c)),
         )(input)
     }
 }
@@ -280,9 +280,10 @@ fn trigger(input: Input) -> Result<ConfigMenu> {
                 preceded(char('T'), status_bit_index),
                 preceded(char('t'), map(status_bit_index, |i| i + 32)),
             )),
+            char(','),
             recognize(many0(satisfy(|c| c != ';'))),
         )),
-        |(index, label)| ConfigMenu::Trigger {
+        |(index, _, label)| ConfigMenu::Trigger {
             close_osd: false,
             index,
             label: label.to_string(),
@@ -5,6 +5,12 @@ use std::convert::TryFrom;
 use std::fmt::Debug;
 use std::str::FromStr;
 
+/// The number of savestate slots of a core that does not specify it.
+pub const DEFAULT_SAVE_STATE_SLOTS: usize = 4;
+
+/// The maximum number of savestate slots a core can ask for.
+const MAX_SAVE_STATE_SLOTS: usize = 16;
+
 #[derive(Default, Clone)]
 pub struct Settings {
     /// UART mode
@@ -15,6 +21,9 @@ pub struct Settings {
 
     /// The save state memory range of the core.
     pub save_state: Option<(FpgaRamMemoryAddress, usize)>,
+
+    /// The number of save state slots, if the core specifies it.
+    pub save_state_slots: Option<usize>,
 }
 
 impl Debug for Settings {
@@ -30,15 +39,23 @@ impl Debug for Settings {
             .field("uart_mode", &self.uart_mode)
             .field("midi_mode", &self.midi_mode)
             .field("save_state", &save_state)
+            .field("save_state_slots", &self.save_state_slots)
             .finish()
     }
 }
 
 impl Settings {
+    /// The number of save state slots of the core, or 0 if it does not
+    /// support save states.
+    pub fn nb_save_state_slots(&self) -> usize {
+        match self.save_state {
+            Some(_) => self.save_state_slots.unwrap_or(DEFAULT_SAVE_STATE_SLOTS),
+            None => 0,
+        }
+    }
+
     fn parse_save_state(s: &str) -> Result<(FpgaRamMemoryAddress, usize), &'static str> {
         if let Some((base, size)) = s.split_once(':') {
-            // Strip anything after a comma of size.
-            let size = size.split(',').next().unwrap_or(size);
             let base = usize::from_str_radix(base, 16).map_err(|_| "Invalid base")?;
             let size = usize::from_str_radix(size, 16).map_err(|_| "Invalid size")?;
             // Verify overflow.
@@ -57,6 +74,14 @@ impl Settings {
             Err("Could not parse save state range")
         }
     }
+
+    fn parse_save_state_slots(s: &str) -> Result<usize, &'static str> {
+        let slots = s.parse::<usize>().map_err(|_| "Invalid save state slots")?;
+        if slots == 0 || slots > MAX_SAVE_STATE_SLOTS {
+            return Err("Invalid number of save state slots");
+        }
+        Ok(slots)
+    }
 }
 
 impl FromStr for Settings {
@@ -64,23 +89,31 @@ impl FromStr for Settings {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut save_state = None;
+        let mut save_state_slots = None;
         let mut uart_mode = Vec::new();
         let mut midi_mode = Vec::new();
 
-        for setting in s.split(',') {
-            let setting = setting.trim();
+        let mut settings = s.split(',').map(str::trim).peekable();
+        while let Some(setting) = settings.next() {
             if setting.is_empty() {
                 continue;
             }
 
-            if let Some(s) = s.strip_prefix("SS") {
+            if let Some(s) = setting.strip_prefix("SS") {
+                // The format is `SS{base}:{size}[,{slots}]`, so the number of
+                // slots looks like its own setting.
                 save_state = Some(Self::parse_save_state(s)?);
-            } else if let Some(s) = s.strip_prefix("UART") {
+                if let Some(slots) =
+                    settings.next_if(|s| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()))
+                {
+                    save_state_slots = Some(Self::parse_save_state_slots(slots)?);
+                }
+            } else if let Some(s) = setting.strip_prefix("UART") {
                 // Parse strings of format "12345(label):56789(label 2)".
                 for speed in s.split(':') {
                     uart_mode.push(speed.parse::<uart::UartSpeed>()?);
                 }
-            } else if let Some(s) = s.strip_prefix("MIDI") {
+            } else if let Some(s) = setting.strip_prefix("MIDI") {
                 // Parse strings of format "12345(label):56789(label 2)".
                 for speed in s.split(':') {
                     midi_mode.push(speed.parse::<midi::MidiSpeed>()?);
@@ -90,10 +123,25 @@ impl FromStr for Settings {
             }
         }
 
-        Ok(Self {
+        let settings = Self {
             save_state,
+            save_state_slots,
             uart_mode,
             midi_mode,
-        })
+        };
+
+        // All the slots are mapped at once, so they must all fit in the
+        // memory shared with the FPGA.
+        if let Some((base, size)) = settings.save_state {
+            let end = size
+                .checked_mul(settings.nb_save_state_slots())
+                .and_then(|total| base.as_usize().checked_add(total))
+                .ok_or("Save state range overflow")?;
+            if end > cyclone_v::ranges::HOST_MEMORY.end {
+                return Err("Save state slots do not fit in FPGA memory");
+            }
+        }
+
+        Ok(settings)
     }
 }
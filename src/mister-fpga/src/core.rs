@@ -1,4 +1,5 @@
 pub mod buttons;
+pub mod cheats;
 pub mod file;
 pub mod volume;
 
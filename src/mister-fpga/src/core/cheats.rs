@@ -0,0 +1,235 @@
+//! Cheats for cores that have a `C` entry in their config string. Cheats of a
+//! game are in a zip archive that contains one `.gg` or `.cht` file per cheat.
+//! Each file is a list of codes of [`CHEAT_CODE_SIZE`] bytes, which are sent
+//! as is to the core.
+use std::io;
+use std::path::{Path, PathBuf};
+
+use one_fpga::archive;
+use one_fpga::archive::ArchiveKind;
+
+/// The size of a single cheat code, in bytes.
+pub const CHEAT_CODE_SIZE: usize = 16;
+
+/// The extensions of cheat files inside an archive.
+const CHEAT_EXTENSIONS: [&str; 2] = ["gg", "cht"];
+
+/// The size of the iNES header, which is not part of the CRC32 in the name
+/// of NES cheat archives.
+const INES_HEADER_SIZE: usize = 16;
+
+/// Split the name of a cheat archive (without its extension) into the name
+/// of the game and the CRC32 of the ROM, e.g. `Game (USA) [0123ABCD]`.
+fn split_crc(stem: &str) -> (&str, Option<u32>) {
+    stem.strip_suffix(']')
+        .and_then(|rest| rest.rsplit_once('['))
+        .and_then(|(name, crc)| {
+            let crc = u32::from_str_radix(crc, 16).ok()?;
+            Some((name.trim_end(), Some(crc)))
+        })
+        .unwrap_or((stem, None))
+}
+
+/// The CRC32 a ROM can be known by in cheat archive names.
+fn rom_crcs(rom: &Path) -> Vec<u32> {
+    let Ok(data) = archive::read_path(rom) else {
+        return Vec::new();
+    };
+
+    let mut crcs = vec![crc32fast::hash(&data)];
+    if data.starts_with(b"NES\x1a") && data.len() > INES_HEADER_SIZE {
+        crcs.push(crc32fast::hash(&data[INES_HEADER_SIZE..]));
+    }
+    crcs
+}
+
+/// Find the cheat archive of a ROM in a directory. The archive must be named
+/// after the ROM (ignoring case), or contain the CRC32 of the ROM between
+/// brackets.
+pub fn find_archive(dir: impl AsRef<Path>, rom: impl AsRef<Path>) -> Option<PathBuf> {
+    let rom = rom.as_ref();
+    let rom_stem = rom.file_stem()?.to_string_lossy().to_lowercase();
+
+    let archives = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| ArchiveKind::from_path(path) == Some(ArchiveKind::Zip))
+        .filter_map(|path| {
+            let stem = path.file_stem()?.to_string_lossy().to_lowercase();
+            Some((path, stem))
+        })
+        .collect::<Vec<_>>();
+
+    if let Some((path, _)) = archives
+        .iter()
+        .find(|(_, stem)| *stem == rom_stem || split_crc(stem).0 == rom_stem)
+    {
+        return Some(path.clone());
+    }
+
+    let crcs = rom_crcs(rom);
+    archives
+        .into_iter()
+        .find(|(_, stem)| split_crc(stem).1.is_some_and(|crc| crcs.contains(&crc)))
+        .map(|(path, _)| path)
+}
+
+/// A cheat of a game, which can be enabled or not.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cheat {
+    /// The name of the cheat, from its file name.
+    pub name: String,
+
+    pub enabled: bool,
+
+    /// The file of the cheat inside the archive.
+    file: String,
+}
+
+/// The cheats of a game, from its cheat archive. None are enabled at first.
+#[derive(Debug, Clone)]
+pub struct Cheats {
+    archive: PathBuf,
+    cheats: Vec<Cheat>,
+}
+
+impl Cheats {
+    /// List the cheats in a cheat archive.
+    pub fn from_archive(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let cheats = archive::list(path, "")?
+            .into_iter()
+            .filter(|entry| !entry.is_dir)
+            .filter_map(|entry| {
+                let (name, ext) = entry.name.rsplit_once('.')?;
+                CHEAT_EXTENSIONS
+                    .iter()
+                    .any(|e| ext.eq_ignore_ascii_case(e))
+                    .then(|| Cheat {
+                        name: name.to_string(),
+                        enabled: false,
+                        file: entry.name.clone(),
+                    })
+            })
+            .collect();
+
+        Ok(Self {
+            archive: path.to_path_buf(),
+            cheats,
+        })
+    }
+
+    /// Find the cheats of a ROM in a directory (see [`find_archive`]).
+    pub fn find(dir: impl AsRef<Path>, rom: impl AsRef<Path>) -> io::Result<Option<Self>> {
+        find_archive(dir, rom).map(Self::from_archive).transpose()
+    }
+
+    pub fn archive(&self) -> &Path {
+        &self.archive
+    }
+
+    pub fn cheats(&self) -> &[Cheat] {
+        &self.cheats
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cheats.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cheats.len()
+    }
+
+    pub fn nb_enabled(&self) -> usize {
+        self.cheats.iter().filter(|c| c.enabled).count()
+    }
+
+    /// Enable or disable a cheat. Returns its new state.
+    pub fn toggle(&mut self, index: usize) -> Option<bool> {
+        let cheat = self.cheats.get_mut(index)?;
+        cheat.enabled = !cheat.enabled;
+        Some(cheat.enabled)
+    }
+
+    /// The codes of all the enabled cheats, in order.
+    pub fn codes(&self) -> io::Result<Vec<u8>> {
+        let mut codes = Vec::new();
+        for cheat in self.cheats.iter().filter(|c| c.enabled) {
+            let data = archive::read(&self.archive, &cheat.file)?;
+            if data.is_empty() || data.len() % CHEAT_CODE_SIZE != 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid cheat file {}", cheat.file),
+                ));
+            }
+            codes.extend(data);
+        }
+        Ok(codes)
+    }
+}
+
+#[test]
+fn cheats_from_archive() {
+    use std::fs::File;
+    use std::io::Write;
+
+    let root = tempdir::TempDir::new("cheats").unwrap();
+    let rom = root.path().join("Game (USA).nes");
+    std::fs::write(&rom, b"NES\x1a").unwrap();
+
+    let path = root.path().join("Game (USA) [0123ABCD].zip");
+    let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+    let options = zip::write::FileOptions::default();
+    zip.start_file("Infinite Lives.gg", options).unwrap();
+    zip.write_all(&[1; CHEAT_CODE_SIZE]).unwrap();
+    zip.start_file("Moon Jump.CHT", options).unwrap();
+    zip.write_all(&[2; CHEAT_CODE_SIZE * 2]).unwrap();
+    zip.start_file("Broken.gg", options).unwrap();
+    zip.write_all(&[3; 3]).unwrap();
+    zip.start_file("readme.txt", options).unwrap();
+    zip.write_all(b"Not a cheat").unwrap();
+    zip.finish().unwrap();
+
+    assert_eq!(find_archive(root.path(), &rom), Some(path.clone()));
+    assert_eq!(
+        find_archive(root.path(), root.path().join("Other.nes")),
+        None
+    );
+
+    let mut cheats = Cheats::find(root.path(), &rom).unwrap().unwrap();
+    let names = cheats
+        .cheats()
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["Broken", "Infinite Lives", "Moon Jump"]);
+    assert!(cheats.codes().unwrap().is_empty());
+
+    assert_eq!(cheats.toggle(1), Some(true));
+    assert_eq!(cheats.toggle(2), Some(true));
+    assert_eq!(cheats.nb_enabled(), 2);
+    let mut expected = vec![1; CHEAT_CODE_SIZE];
+    expected.extend([2; CHEAT_CODE_SIZE * 2]);
+    assert_eq!(cheats.codes().unwrap(), expected);
+
+    cheats.toggle(0);
+    assert!(cheats.codes().is_err());
+}
+
+#[test]
+fn find_archive_by_crc() {
+    let root = tempdir::TempDir::new("cheats").unwrap();
+    let rom = root.path().join("game.nes");
+    let mut data = b"NES\x1a".to_vec();
+    data.resize(INES_HEADER_SIZE, 0);
+    data.extend(b"program");
+    std::fs::write(&rom, &data).unwrap();
+
+    let crc = crc32fast::hash(b"program");
+    let path = root.path().join(format!("Game (USA) [{crc:08X}].zip"));
+    std::fs::write(&path, b"").unwrap();
+    std::fs::write(root.path().join("Other [00000000].zip"), b"").unwrap();
+
+    assert_eq!(find_archive(root.path(), &rom), Some(path));
+}
@@ -105,7 +105,9 @@ impl Core for MenuCore {
     }
 
     fn send_bios(&mut self, _bios: Bios) -> Result<(), Error> {
-        unreachable!("Menu core does not support BIOS")
+        Err(Error::Message(
+            "Menu core does not support BIOS".to_string(),
+        ))
     }
 
     fn key_up(&mut self, _key: Scancode) -> Result<(), Error> {
@@ -2,7 +2,7 @@ use std::any::Any;
 use std::ffi::OsStr;
 use std::fmt::Debug;
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom, Write};
+use std::io::{Cursor, ErrorKind, Read, Seek, SeekFrom, Write};
 use std::path::Path;
 use std::time::SystemTime;
 
@@ -10,16 +10,21 @@ use image::DynamicImage;
 use tracing::{debug, info, trace};
 
 use cyclone_v::memory::{DevMemMemoryMapper, MemoryMapper};
-use one_fpga::core::{Bios, ConfigMenuId, CoreMenuItem, Error, MountedFile, Rom, SaveState};
-use one_fpga::inputs::gamepad::ButtonSet;
+use one_fpga::archive;
+use one_fpga::core::{
+    Bios, BiosRequirement, ConfigMenuId, CoreMenuItem, Error, MountedFile, Rom, SaveState,
+};
+use one_fpga::inputs::gamepad::{Axis, ButtonSet};
 use one_fpga::inputs::keyboard::ScancodeSet;
-use one_fpga::inputs::{Button, Scancode};
+use one_fpga::inputs::mouse::MouseButtonSet;
+use one_fpga::inputs::{Button, MouseButton, Scancode};
 use one_fpga::Core;
 
 use crate::config::{Config, HdmiLimitedConfig, VgaMode};
 use crate::config_string;
-use crate::config_string::{ConfigMenu, FpgaRamMemoryAddress, LoadFileInfo};
+use crate::config_string::{menu_mask_get, ConfigMenu, FpgaRamMemoryAddress, LoadFileInfo};
 use crate::core::buttons::ButtonMap;
+use crate::core::cheats::Cheats;
 use crate::core::file::SdCard;
 use crate::core::video;
 use crate::core::video::VideoInfo;
@@ -28,15 +33,27 @@ use crate::fpga::file_io::{
     FileExtension, FileIndex, FileTxData16Bits, FileTxData8Bits, FileTxDisabled, FileTxEnabled,
 };
 use crate::fpga::user_io::{
-    ButtonSwitches, GetSdStat, GetStatusBits, SdRead, SdStatOutput, SdWrite, SetSdConf, SetSdInfo,
-    SetSdStat, SetStatusBits, UserIoButtonSwitch, UserIoJoystick, UserIoKeyboardKeyDown,
-    UserIoKeyboardKeyUp, UserIoRtc,
+    ButtonSwitches, GetMenuMask, GetSdStat, GetStatusBits, SdRead, SdStatOutput, SdWrite,
+    SetSdConf, SetSdInfo, SetSdStat, SetStatusBits, UserIoAnalogJoystick, UserIoButtonSwitch,
+    UserIoJoystick, UserIoKeyboardKeyDown, UserIoKeyboardKeyUp, UserIoMouse, UserIoRtc,
 };
 use crate::fpga::{user_io, CoreInterfaceType, CoreType, MisterFpga};
 use crate::keyboard::Ps2Scancode;
+use crate::mra::DipSwitches;
 use crate::savestate::SaveStateManager;
 use crate::types::StatusBitMap;
 
+#[cfg(test)]
+use crate::fpga::simulator::fake_core::FakeCore;
+#[cfg(test)]
+use cyclone_v::memory::SimulatedMemoryMapper;
+
+/// The file index the codes of the enabled cheats are sent on.
+const CHEATS_FILE_INDEX: u8 = 255;
+
+/// The file index the DIP switches of arcade games are sent on.
+const DIP_SWITCHES_FILE_INDEX: u8 = 254;
+
 pub enum MisterFpgaSendFileInfo {
     Memory {
         index: u8,
@@ -56,7 +73,10 @@ impl MisterFpgaSendFileInfo {
         }
     }
 
-    pub fn from_path(path: impl AsRef<Path>, core: &MisterFpgaCore) -> Result<Self, String> {
+    pub fn from_path<M: MemoryMapper>(
+        path: impl AsRef<Path>,
+        core: &MisterFpgaCore<M>,
+    ) -> Result<Self, String> {
         let info = core
             .config
             .load_info(path)?
@@ -72,8 +92,8 @@ impl MisterFpgaSendFileInfo {
     }
 }
 
-pub struct MisterFpgaCore {
-    fpga: MisterFpga,
+pub struct MisterFpgaCore<M: MemoryMapper = DevMemMemoryMapper> {
+    fpga: MisterFpga<M>,
     pub is_menu: bool,
     pub core_type: CoreType,
     pub spi_type: CoreInterfaceType,
@@ -83,20 +103,54 @@ pub struct MisterFpgaCore {
     // All the images that are mounted. Can only have 16 images at once.
     cards: Box<[Option<SdCard>; 16]>,
 
-    save_states: Option<SaveStateManager<DevMemMemoryMapper>>,
+    save_states: Option<SaveStateManager<M>>,
+
+    // The cheats of the current game, if the core supports cheats.
+    cheats: Option<Cheats>,
+
+    // The DIP switches of the current arcade game, if any.
+    dip_switches: Option<DipSwitches>,
+
+    // The BIOS files declared for this core, besides `boot.rom`.
+    bios_requirements: Vec<BiosRequirement>,
+
     gamepads: [ButtonMap; 6],
 
+    // The keys and buttons currently pressed, as sent through the `Core` trait.
+    keys: ScancodeSet,
+    buttons: [ButtonSet; 6],
+
+    // The position of the analog sticks (left, right) of each gamepad.
+    sticks: [[(i8, i8); 2]; 6],
+
+    // Spinner emulation from a mouse. The remainder is the movement that
+    // was not sent yet because of the throttle.
+    spinner_axis: u8,
+    spinner_throttle: i32,
+    spinner_remainder: i32,
+
+    // The mouse buttons pressed, and the movement that was not sent yet
+    // because of the throttle.
+    mouse_buttons: MouseButtonSet,
+    mouse_throttle: i32,
+    mouse_remainder: (i32, i32),
+    mouse_as_spinner: bool,
+
     status: StatusBitMap,
     status_counter: u8,
 
-    framebuffer: crate::framebuffer::FpgaFramebuffer<DevMemMemoryMapper>,
+    // The mask the hide and disable conditions of the menu are checked
+    // against. Cores usually compute it from their status bits.
+    menu_mask: u16,
+
+    framebuffer: crate::framebuffer::FpgaFramebuffer<M>,
 
     // A cache for the video_info.
     video_info: Option<VideoInfo>,
 }
 
-impl MisterFpgaCore {
-    pub fn new(mut fpga: MisterFpga) -> Result<Self, String> {
+impl<M: MemoryMapper> MisterFpgaCore<M> {
+    pub fn new(mut fpga: MisterFpga<M>) -> Result<Self, String> {
         fpga.wait_for_ready();
 
         let config = config_string::Config::from_fpga(&mut fpga)?;
@@ -126,6 +180,8 @@ impl MisterFpgaCore {
         let save_states = SaveStateManager::from_config_string(&config);
         const NONE: Option<SdCard> = None;
 
+        let framebuffer = crate::framebuffer::FpgaFramebuffer::create()?;
+
         Ok(MisterFpgaCore {
             is_menu: false,
             fpga,
@@ -135,15 +191,29 @@ impl MisterFpgaCore {
             config,
             cards: Box::new([NONE; 16]),
             save_states,
+            cheats: None,
+            dip_switches: None,
+            bios_requirements: Vec::new(),
             gamepads: [map; 6],
+            keys: ScancodeSet::new(),
+            buttons: [ButtonSet::new(); 6],
+            sticks: [[(0, 0); 2]; 6],
+            spinner_axis: 0,
+            spinner_throttle: 100,
+            spinner_remainder: 0,
+            mouse_buttons: MouseButtonSet::new(),
+            mouse_throttle: 1,
+            mouse_remainder: (0, 0),
+            mouse_as_spinner: false,
             status: Default::default(),
             status_counter: 0,
-            framebuffer: crate::framebuffer::FpgaFramebuffer::default(),
+            menu_mask: 0,
+            framebuffer,
             video_info: None,
         })
     }
 
-    pub fn spi_mut(&mut self) -> &mut crate::fpga::Spi<DevMemMemoryMapper> {
+    pub fn spi_mut(&mut self) -> &mut crate::fpga::Spi<M> {
         self.fpga.spi_mut()
     }
 
@@ -192,21 +262,40 @@ impl MisterFpgaCore {
             .unwrap_or("")
             .to_uppercase();
 
-        let now = std::time::Instant::now();
         debug!("Sending file {:?} to core", path);
 
+        // Files inside archives are extracted in memory.
+        if let Some((archive, inner)) = archive::split_path(path) {
+            let data = archive::read(archive, inner).map_err(|e| e.to_string())?;
+            let size = data.len() as u32;
+            return self.send_file(info, &ext, size, Cursor::new(data));
+        }
+
         let file = File::open(path).map_err(|e| e.to_string())?;
         let size = file.metadata().map_err(|e| e.to_string())?.len() as u32;
 
-        self.start_send_file(info.index(), &ext, size)?;
+        self.send_file(info, &ext, size, file)
+    }
+
+    /// Send the content of a reader (ROM or BIOS) to the core.
+    pub fn send_file(
+        &mut self,
+        info: MisterFpgaSendFileInfo,
+        ext: &str,
+        size: u32,
+        reader: impl Read,
+    ) -> Result<(), String> {
+        let now = std::time::Instant::now();
+
+        self.start_send_file(info.index(), ext, size)?;
         match info {
             MisterFpgaSendFileInfo::Memory { index, address } => {
                 trace!(?index, ?address, ?ext, ?size, "File info (memory)");
-                self.send_file_to_sdram_(size, address, file)?;
+                self.send_file_to_sdram_(size, address, reader)?;
             }
             MisterFpgaSendFileInfo::Buffered { index } => {
                 trace!(?index, ?ext, ?size, "File info (buffered)");
-                self.send_file_to_buffer_(size, file)?;
+                self.send_file_to_buffer_(size, reader)?;
             }
         }
         self.read_status_bits();
@@ -231,6 +320,83 @@ impl MisterFpgaCore {
         self.fpga.spi_mut().execute(FileTxDisabled)
     }
 
+    /// The cheats of the current game, if any were set.
+    pub fn cheats(&self) -> Option<&Cheats> {
+        self.cheats.as_ref()
+    }
+
+    pub fn cheats_mut(&mut self) -> Option<&mut Cheats> {
+        self.cheats.as_mut()
+    }
+
+    /// Declare the BIOS files this core needs, e.g. from a file shipped with
+    /// the core. Config strings don't say which BIOS a core needs, so only
+    /// an optional `boot.rom` is looked for without them. Declared files
+    /// other than `boot.rom` are loaded with the BIOS entry of the menu.
+    pub fn set_bios_requirements(&mut self, requirements: Vec<BiosRequirement>) {
+        self.bios_requirements = requirements;
+    }
+
+    /// Set the cheats of the current game. This does not send them to the
+    /// core, see [`Self::send_cheats`].
+    pub fn set_cheats(&mut self, cheats: Option<Cheats>) {
+        self.cheats = cheats;
+    }
+
+    /// Send the codes of the enabled cheats to the core. Cheats are sent
+    /// like a file on a reserved index, and an empty list disables them.
+    pub fn send_cheats(&mut self) -> Result<(), String> {
+        let mut codes = match &self.cheats {
+            Some(cheats) => cheats.codes().map_err(|e| e.to_string())?,
+            None => Vec::new(),
+        };
+        if codes.is_empty() {
+            // Cores expect at least one word.
+            codes.resize(2, 0);
+        }
+        debug!(size = codes.len(), "Sending cheats to core");
+
+        let size = codes.len() as u32;
+        self.fpga
+            .spi_mut()
+            .execute(FileIndex::from(CHEATS_FILE_INDEX))?;
+        self.fpga.spi_mut().execute(FileTxEnabled(None))?;
+        self.send_file_to_buffer_(size, Cursor::new(codes))?;
+        self.end_send_file()
+    }
+
+    /// The DIP switches of the current arcade game, if any were set.
+    pub fn dip_switches(&self) -> Option<&DipSwitches> {
+        self.dip_switches.as_ref()
+    }
+
+    pub fn dip_switches_mut(&mut self) -> Option<&mut DipSwitches> {
+        self.dip_switches.as_mut()
+    }
+
+    /// Set the DIP switches of the current arcade game. This does not send
+    /// them to the core, see [`Self::send_dip_switches`].
+    pub fn set_dip_switches(&mut self, dip_switches: Option<DipSwitches>) {
+        self.dip_switches = dip_switches;
+    }
+
+    /// Send the DIP switches to the core, as 8 bytes (lowest first) on a
+    /// reserved file index. Does nothing if there are no DIP switches.
+    pub fn send_dip_switches(&mut self) -> Result<(), String> {
+        let Some(dip_switches) = &self.dip_switches else {
+            return Ok(());
+        };
+        let bytes = dip_switches.value().to_le_bytes();
+        debug!(value = dip_switches.value(), "Sending DIP switches to core");
+
+        self.fpga
+            .spi_mut()
+            .execute(FileIndex::from(DIP_SWITCHES_FILE_INDEX))?;
+        self.fpga.spi_mut().execute(FileTxEnabled(None))?;
+        self.send_file_to_buffer_(bytes.len() as u32, Cursor::new(bytes))?;
+        self.end_send_file()
+    }
+
     /// Return the core parsed config structure.
     pub fn config(&self) -> &config_string::Config {
         &self.config
@@ -267,15 +433,33 @@ impl MisterFpgaCore {
         &self.status
     }
 
-    /// Update the internal cache and return it.
+    /// Update the internal cache and return it. The menu mask is read
+    /// again too, as it depends on the status bits.
     pub fn read_status_bits(&mut self) -> &StatusBitMap {
         self.fpga
             .spi_mut()
             .execute(GetStatusBits(&mut self.status, &mut self.status_counter))
             .unwrap();
+        self.read_menu_mask();
         &self.status
     }
 
+    /// Return the mask of the hide and disable conditions of the menu. This
+    /// is an internal cache. Use [`Self::read_menu_mask`] to read the mask
+    /// from the core.
+    pub fn menu_mask(&self) -> u16 {
+        self.menu_mask
+    }
+
+    /// Update the internal cache of the menu mask and return it.
+    pub fn read_menu_mask(&mut self) -> u16 {
+        self.fpga
+            .spi_mut()
+            .execute(GetMenuMask(&mut self.menu_mask))
+            .unwrap();
+        self.menu_mask
+    }
+
     /// Send status bits to the core.
     pub fn send_status_bits(&mut self, bits: StatusBitMap) {
         debug!(?bits, "Setting status bits");
@@ -283,6 +467,23 @@ impl MisterFpgaCore {
         self.status = bits;
     }
 
+    /// The status bits of the core options, without the reset bit and
+    /// triggers. This is what should be saved between sessions.
+    pub fn option_status_bits(&self) -> StatusBitMap {
+        let mut bits = StatusBitMap::new();
+        bits.copy_masked(&self.status, &self.config.option_bit_map_mask());
+        bits
+    }
+
+    /// Restore the core options from status bits saved with
+    /// [`Self::option_status_bits`]. Bits that are not options of this core
+    /// are ignored.
+    pub fn restore_option_status_bits(&mut self, saved: &StatusBitMap) {
+        let mut bits = self.status;
+        bits.copy_masked(saved, &self.config.option_bit_map_mask());
+        self.send_status_bits(bits);
+    }
+
     pub fn menu_options(&self) -> &[ConfigMenu] {
         self.config().menu.as_slice()
     }
@@ -353,13 +554,96 @@ impl MisterFpgaCore {
             .unwrap();
     }
 
+    /// Send the position of an analog stick of a gamepad.
+    pub fn send_analog_stick(&mut self, joystick_idx: u8, right: bool, x: i8, y: i8) {
+        self.fpga
+            .spi_mut()
+            .execute(
+                UserIoAnalogJoystick::stick(joystick_idx, right, x, y)
+                    .with_io_version(self.io_version),
+            )
+            .unwrap();
+        self.sticks[joystick_idx as usize][right as usize] = (x, y);
+    }
+
+    /// Send the absolute position of a paddle.
+    pub fn send_paddle(&mut self, joystick_idx: u8, value: u8) {
+        self.fpga
+            .spi_mut()
+            .execute(
+                UserIoAnalogJoystick::paddle(joystick_idx, value).with_io_version(self.io_version),
+            )
+            .unwrap();
+    }
+
+    /// Send a relative movement of a spinner.
+    pub fn send_spinner(&mut self, joystick_idx: u8, delta: i8) {
+        self.fpga
+            .spi_mut()
+            .execute(
+                UserIoAnalogJoystick::spinner(joystick_idx, delta).with_io_version(self.io_version),
+            )
+            .unwrap();
+    }
+
+    /// Emulate a spinner from a mouse movement, using the `spinner_axis` and
+    /// `spinner_throttle` options of the MiSTer config.
+    pub fn mouse_spinner(&mut self, joystick_idx: u8, dx: i32, dy: i32, wheel: i32) {
+        let movement = match self.spinner_axis {
+            1 => dy,
+            2 => wheel,
+            _ => dx,
+        };
+        if movement == 0 {
+            return;
+        }
+
+        let total = self.spinner_remainder + movement * 100;
+        let delta = (total / self.spinner_throttle).clamp(i8::MIN as i32, i8::MAX as i32);
+        self.spinner_remainder = total - delta * self.spinner_throttle;
+
+        if delta != 0 {
+            self.send_spinner(joystick_idx, delta as i8);
+        }
+    }
+
+    /// Send a mouse movement to the core, divided by the `mouse_throttle` option
+    /// of the MiSTer config. A positive `dy` moves down, and a positive `wheel`
+    /// scrolls up.
+    pub fn send_mouse(&mut self, dx: i32, dy: i32, wheel: i32) {
+        let throttle = self.mouse_throttle;
+        let (x, y) = (self.mouse_remainder.0 + dx, self.mouse_remainder.1 + dy);
+        let (dx, dy) = (x / throttle, y / throttle);
+        self.mouse_remainder = (x - dx * throttle, y - dy * throttle);
+
+        if dx == 0 && dy == 0 && wheel == 0 {
+            return;
+        }
+        self.send_mouse_packet(dx, dy, wheel);
+    }
+
+    fn send_mouse_packet(&mut self, dx: i32, dy: i32, wheel: i32) {
+        let clamp = |v: i32| v.clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+
+        // PS/2 mice move up with a positive Y.
+        self.fpga
+            .spi_mut()
+            .execute(UserIoMouse::new(
+                self.mouse_buttons.as_u8(),
+                clamp(dx),
+                clamp(-dy),
+                wheel.clamp(i8::MIN as i32, i8::MAX as i32) as i8,
+            ))
+            .unwrap();
+    }
+
     /// Access the internal save state manager, in readonly.
-    pub fn save_states(&self) -> Option<&SaveStateManager<DevMemMemoryMapper>> {
+    pub fn save_states(&self) -> Option<&SaveStateManager<M>> {
         self.save_states.as_ref()
     }
 
     /// Access the internal save state manager.
-    pub fn save_states_mut(&mut self) -> Option<&mut SaveStateManager<DevMemMemoryMapper>> {
+    pub fn save_states_mut(&mut self) -> Option<&mut SaveStateManager<M>> {
         self.save_states.as_mut()
     }
 
@@ -368,7 +652,18 @@ impl MisterFpgaCore {
         self.framebuffer.take_screenshot()
     }
 
-    pub fn framebuffer(&self) -> &crate::framebuffer::FpgaFramebuffer<DevMemMemoryMapper> {
+    /// Take a screenshot, scaled to the display aspect ratio of the core
+    /// (e.g. 4:3 for most consoles) instead of its resolution.
+    pub fn take_screenshot_with_aspect_ratio(&mut self) -> Result<DynamicImage, String> {
+        let image = self.take_screenshot()?;
+        let aspect_ratio = self.video_info()?.display_aspect_ratio();
+        Ok(crate::framebuffer::scale_to_aspect_ratio(
+            image,
+            aspect_ratio,
+        ))
+    }
+
+    pub fn framebuffer(&self) -> &crate::framebuffer::FpgaFramebuffer<M> {
         &self.framebuffer
     }
 
@@ -456,7 +751,7 @@ impl MisterFpgaCore {
             return Err("File too large.".to_string());
         }
         let mut crc = crc32fast::Hasher::new();
-        let mut mem = DevMemMemoryMapper::create(address.as_usize(), size as usize)?;
+        let mut mem = M::create(address.as_usize(), size as usize)?;
 
         let mut bytes2send = size;
         while bytes2send > 0 {
@@ -483,36 +778,48 @@ impl MisterFpgaCore {
         let mut crc = crc32fast::Hasher::new();
         let now = std::time::Instant::now();
 
-        let mut buffer = [0u8; 4096];
+        // Use a buffer of words so it's aligned for the 16 bits bus.
+        let mut buffer16 = [0u16; 2048];
         loop {
-            match reader.read(&mut buffer).map_err(|e| e.to_string()) {
-                Ok(0) => break,
-                Ok(size) => {
-                    crc.update(&buffer[..size]);
-
-                    match self.spi_type {
-                        CoreInterfaceType::SpiBus8Bit => {
-                            self.fpga
-                                .spi_mut()
-                                .execute(FileTxData8Bits(&buffer[..size]))?;
-                        }
-                        CoreInterfaceType::SpiBus16Bit => {
-                            // This is safe since size cannot be larger than 4096.
-                            let buf16 = unsafe {
-                                std::slice::from_raw_parts(
-                                    buffer.as_ptr() as *const u16,
-                                    size + size % 2,
-                                )
-                            };
-                            self.fpga
-                                .spi_mut()
-                                .execute(FileTxData16Bits(&buf16[..size / 2]))?;
-                        }
-                    }
+            // This is safe since the byte slice covers exactly the words.
+            let buffer =
+                unsafe { std::slice::from_raw_parts_mut(buffer16.as_mut_ptr() as *mut u8, 4096) };
+
+            // Fill the whole buffer, as a reader can return short reads of an
+            // odd size. Only the last chunk can then end on half a word.
+            let mut len = 0;
+            while len < buffer.len() {
+                match reader.read(&mut buffer[len..]) {
+                    Ok(0) => break,
+                    Ok(size) => len += size,
+                    Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e.to_string()),
                 }
-                Err(e) => {
-                    return Err(e);
+            }
+            if len == 0 {
+                break;
+            }
+            crc.update(&buffer[..len]);
+
+            match self.spi_type {
+                CoreInterfaceType::SpiBus8Bit => {
+                    self.fpga
+                        .spi_mut()
+                        .execute(FileTxData8Bits(&buffer[..len]))?;
                 }
+                CoreInterfaceType::SpiBus16Bit => {
+                    // Pad an odd trailing byte, so it is not dropped.
+                    if len % 2 == 1 {
+                        buffer[len] = 0;
+                    }
+                    self.fpga
+                        .spi_mut()
+                        .execute(FileTxData16Bits(&buffer16[..len.div_ceil(2)]))?;
+                }
+            }
+
+            if len < buffer.len() {
+                break;
             }
         }
 
@@ -524,22 +831,33 @@ impl MisterFpgaCore {
         Ok(())
     }
 
-    pub fn trigger_menu(&mut self, menu: &ConfigMenu) -> Result<bool, String> {
+    /// Unwrap the hide, disable and page conditions of a menu item, checking
+    /// them against the menu mask. Returns an error if the item is hidden or
+    /// disabled.
+    fn enabled_menu<'a>(&self, menu: &'a ConfigMenu) -> Result<&'a ConfigMenu, String> {
         match menu {
             ConfigMenu::HideIf(cond, sub) | ConfigMenu::DisableIf(cond, sub) => {
-                if self.config().status_bit_map_mask().get(*cond as usize) {
-                    self.trigger_menu(sub)
+                if !menu_mask_get(self.menu_mask, *cond) {
+                    self.enabled_menu(sub)
                 } else {
                     Err("Cannot trigger menu".to_string())
                 }
             }
             ConfigMenu::HideUnless(cond, sub) | ConfigMenu::DisableUnless(cond, sub) => {
-                if !self.config().status_bit_map_mask().get(*cond as usize) {
-                    self.trigger_menu(sub)
+                if menu_mask_get(self.menu_mask, *cond) {
+                    self.enabled_menu(sub)
                 } else {
                     Err("Cannot trigger menu".to_string())
                 }
             }
+            ConfigMenu::PageItem(_, sub) => self.enabled_menu(sub),
+            _ => Ok(menu),
+        }
+    }
+
+    pub fn trigger_menu(&mut self, menu: &ConfigMenu) -> Result<bool, String> {
+        self.read_menu_mask();
+        match self.enabled_menu(menu)? {
             ConfigMenu::Option { bits, choices, .. } => {
                 let (from, to) = (bits.start, bits.end);
                 let mut bits = *self.status_bits();
@@ -553,7 +871,6 @@ impl MisterFpgaCore {
                 self.status_pulse(*index as usize);
                 Ok(true)
             }
-            ConfigMenu::PageItem(_, sub) => self.trigger_menu(sub),
 
             // TODO: see if we can implement more (like Load File).
             _ => Ok(false),
@@ -561,12 +878,12 @@ impl MisterFpgaCore {
     }
 }
 
-impl Core for MisterFpgaCore {
+impl<M: MemoryMapper + 'static> Core for MisterFpgaCore<M> {
     fn init(&mut self) -> Result<(), Error> {
         self.soft_reset();
         self.fpga
             .spi_mut()
-            .execute(user_io::SetMemorySize::from_fpga().unwrap())
+            .execute(user_io::SetMemorySize::from_fpga::<M>().unwrap())
             .map_err(Error::Message)?;
 
         // Initialize the framebuffer.
@@ -612,6 +929,11 @@ impl Core for MisterFpgaCore {
 
         self.spi_mut().execute(switches).unwrap();
 
+        self.spinner_axis = options.spinner_axis();
+        self.spinner_throttle = options.spinner_throttle();
+        self.mouse_throttle = options.mouse_throttle() as i32;
+        self.mouse_as_spinner = options.mouse_as_spinner();
+
         video::init(&options);
         video::init_mode(&options, &mut self.fpga, self.is_menu);
         self.framebuffer.update_type_from_core();
@@ -683,63 +1005,263 @@ impl Core for MisterFpgaCore {
 
     fn send_rom(&mut self, rom: Rom) -> Result<(), Error> {
         match rom {
-            Rom::Memory(_, _) => Err(Error::Message(
-                "Memory ROMs are not supported yet.".to_string(),
-            )),
+            Rom::Memory(path, data) => {
+                // Use the entry that matches the extension, falling back to
+                // the first file entry of the core.
+                let info = path
+                    .as_ref()
+                    .and_then(|p| self.config.load_info(p).ok().flatten())
+                    .or_else(|| {
+                        self.config
+                            .menu
+                            .iter()
+                            .find_map(|item| item.as_load_file()?.as_load_file_info().cloned())
+                    })
+                    .ok_or_else(|| Error::Message("Core does not load files.".to_string()))?;
+
+                let ext = path
+                    .as_ref()
+                    .and_then(|p| p.extension())
+                    .and_then(|e| e.to_str())
+                    .map(|e| e.to_uppercase())
+                    .or_else(|| info.extensions.first().map(|e| e.to_string()))
+                    .unwrap_or_default();
+                let size = data.get_ref().len() as u32;
+
+                debug!(?path, index = info.index, size, "Sending ROM to core");
+                let info = MisterFpgaSendFileInfo::from_file_info(info).map_err(Error::Message)?;
+                self.send_file(info, &ext, size, data)
+                    .map_err(Error::Message)
+            }
             Rom::File(path) => self.load_file(&path, None).map_err(Error::Message),
         }
     }
 
-    fn send_bios(&mut self, _bios: Bios) -> Result<(), Error> {
-        todo!()
+    fn send_bios(&mut self, mut bios: Bios) -> Result<(), Error> {
+        let path = match &bios {
+            Bios::Memory(path, _) => path.clone(),
+            Bios::File(path, _) => Some(path.clone()),
+        };
+        let mut data = Vec::new();
+        bios.rewind()?;
+        bios.read_to_end(&mut data)?;
+
+        // `boot.rom` is always loaded on index 0. Only the files declared
+        // for the core (found by name or checksum) use the BIOS entry of the
+        // config string.
+        let name = path
+            .as_ref()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let is_declared = !name.eq_ignore_ascii_case("boot.rom")
+            && self.bios_requirements.iter().any(|r| {
+                !r.name.eq_ignore_ascii_case("boot.rom")
+                    && (r.name.eq_ignore_ascii_case(&name) || r.crc32.is_some() && r.matches(&data))
+            });
+
+        let (info, default_ext) = match self.config.bios_load_info().filter(|_| is_declared) {
+            Some(info) => {
+                let ext = info.extensions.first().map(|e| e.to_string());
+                let info = MisterFpgaSendFileInfo::from_file_info(info).map_err(Error::Message)?;
+                (info, ext)
+            }
+            None => (MisterFpgaSendFileInfo::Buffered { index: 0 }, None),
+        };
+
+        let ext = path
+            .as_ref()
+            .and_then(|p| p.extension())
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_uppercase())
+            .or(default_ext)
+            .unwrap_or_else(|| "ROM".to_string());
+        let size = data.len() as u32;
+
+        debug!(?path, index = info.index(), size, "Sending BIOS to core");
+        self.send_file(info, &ext, size, Cursor::new(data))
+            .map_err(Error::Message)?;
+        self.end_send_file().map_err(Error::Message)
+    }
+
+    fn bios_requirements(&self) -> Vec<BiosRequirement> {
+        // Cores load `boot.rom` on index 0 if it exists. It is only required
+        // if it was declared (see [`Self::set_bios_requirements`]).
+        let mut requirements = self.bios_requirements.clone();
+        if !requirements
+            .iter()
+            .any(|r| r.name.eq_ignore_ascii_case("boot.rom"))
+        {
+            requirements.insert(0, BiosRequirement::new("boot.rom").with_optional(true));
+        }
+        requirements
     }
 
     fn key_up(&mut self, key: Scancode) -> Result<(), Error> {
         self.key_up(key);
+        self.keys.remove(key);
         Ok(())
     }
 
     fn key_down(&mut self, key: Scancode) -> Result<(), Error> {
         self.key_down(key);
+        self.keys.insert(key);
         Ok(())
     }
 
-    fn keys_set(&mut self, _keys: ScancodeSet) -> Result<(), Error> {
-        todo!()
+    fn keys_set(&mut self, keys: ScancodeSet) -> Result<(), Error> {
+        let released = self
+            .keys
+            .iter()
+            .filter(|k| !keys.contains(*k))
+            .collect::<Vec<_>>();
+        for key in released {
+            self.key_up(key);
+        }
+
+        let pressed = keys
+            .iter()
+            .filter(|k| !self.keys.contains(*k))
+            .collect::<Vec<_>>();
+        for key in pressed {
+            self.key_down(key);
+        }
+
+        self.keys = keys;
+        Ok(())
     }
 
     fn keys(&self) -> Result<ScancodeSet, Error> {
-        todo!()
+        Ok(self.keys.clone())
     }
 
     fn gamepad_button_up(&mut self, index: usize, button: Button) -> Result<(), Error> {
+        if index >= self.gamepads.len() {
+            return Err(Error::Message(format!("Invalid gamepad index {index}.")));
+        }
         self.gamepad_button_up(index as u8, button as u8);
+        self.buttons[index].remove(button);
         Ok(())
     }
 
     fn gamepad_button_down(&mut self, index: usize, button: Button) -> Result<(), Error> {
+        if index >= self.gamepads.len() {
+            return Err(Error::Message(format!("Invalid gamepad index {index}.")));
+        }
         self.gamepad_button_down(index as u8, button as u8);
+        self.buttons[index].insert(button);
+        Ok(())
+    }
+
+    fn gamepad_buttons_set(&mut self, index: usize, buttons: ButtonSet) -> Result<(), Error> {
+        let Some(map) = self.gamepads.get(index) else {
+            return Err(Error::Message(format!("Invalid gamepad index {index}.")));
+        };
+
+        let mut map = *map;
+        map.clear();
+        for button in buttons.iter() {
+            map.down(button.as_repr());
+        }
+        self.send_gamepad(index as u8, map);
+        self.buttons[index] = buttons;
+        Ok(())
+    }
+
+    fn gamepad_buttons(&self, index: usize) -> Result<Option<ButtonSet>, Error> {
+        Ok(self.buttons.get(index).copied())
+    }
+
+    fn gamepad_axis(&mut self, index: usize, axis: Axis, value: i16) -> Result<(), Error> {
+        if index >= self.gamepads.len() {
+            return Err(Error::Message(format!("Invalid gamepad index {index}.")));
+        }
+
+        let (right, is_x) = match axis {
+            a if a == Axis::LEFT_X => (false, true),
+            a if a == Axis::LEFT_Y => (false, false),
+            a if a == Axis::RIGHT_X => (true, true),
+            a if a == Axis::RIGHT_Y => (true, false),
+            // MiSTer cores have no analog triggers.
+            _ => return Ok(()),
+        };
+
+        let value = (value >> 8) as i8;
+        let (mut x, mut y) = self.sticks[index][right as usize];
+        if is_x {
+            x = value;
+        } else {
+            y = value;
+        }
+        self.send_analog_stick(index as u8, right, x, y);
+
+        // The left stick also drives the paddle, centered at 128.
+        if !right && is_x {
+            self.send_paddle(index as u8, (x as u8) ^ 0x80);
+        }
         Ok(())
     }
 
-    fn gamepad_buttons_set(&mut self, _index: usize, _buttons: ButtonSet) -> Result<(), Error> {
-        todo!()
+    fn mouse_move(&mut self, dx: i32, dy: i32, wheel: i32) -> Result<(), Error> {
+        if self.mouse_as_spinner {
+            self.mouse_spinner(0, dx, dy, wheel);
+        }
+        self.send_mouse(dx, dy, wheel);
+        Ok(())
     }
 
-    fn gamepad_buttons(&self, _index: usize) -> Result<Option<ButtonSet>, Error> {
-        todo!()
+    fn mouse_button_up(&mut self, button: MouseButton) -> Result<(), Error> {
+        self.mouse_buttons.remove(button);
+        self.send_mouse_packet(0, 0, 0);
+        Ok(())
+    }
+
+    fn mouse_button_down(&mut self, button: MouseButton) -> Result<(), Error> {
+        self.mouse_buttons.insert(button);
+        self.send_mouse_packet(0, 0, 0);
+        Ok(())
     }
 
     fn menu(&self) -> Result<Vec<CoreMenuItem>, Error> {
-        Ok(self.config.as_core_menu())
+        Ok(self.config.as_core_menu(self.status_bits(), self.menu_mask))
     }
 
-    fn trigger(&mut self, _id: ConfigMenuId) -> Result<(), Error> {
-        todo!()
+    fn trigger(&mut self, id: ConfigMenuId) -> Result<(), Error> {
+        // Keep the conditions of the item, so hidden or disabled triggers
+        // cannot be fired.
+        let menu = self
+            .config
+            .find_menu_item(id)
+            .filter(|item| item.as_trigger().is_some())
+            .cloned()
+            .ok_or_else(|| Error::Message(format!("Unknown trigger {id:?}.")))?;
+
+        self.trigger_menu(&menu).map_err(Error::Message)?;
+        Ok(())
     }
 
-    fn int_option(&mut self, _id: ConfigMenuId, _value: u32) -> Result<(), Error> {
-        todo!()
+    fn int_option(&mut self, id: ConfigMenuId, value: u32) -> Result<(), Error> {
+        let mut status = *self.read_status_bits();
+        let item = self
+            .config
+            .find_menu_item(id)
+            .filter(|item| item.as_option().is_some())
+            .ok_or_else(|| Error::Message(format!("Unknown option {id:?}.")))?;
+        let Ok(ConfigMenu::Option { bits, choices, .. }) = self.enabled_menu(item) else {
+            return Err(Error::Message(format!(
+                "Option {id:?} is hidden or disabled."
+            )));
+        };
+
+        if value as usize >= choices.len() {
+            return Err(Error::Message(format!(
+                "Invalid value {value} for option {id:?}."
+            )));
+        }
+
+        status.set_range(bits.clone(), value);
+        self.send_status_bits(status);
+        Ok(())
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -750,3 +1272,522 @@ impl Core for MisterFpgaCore {
         self
     }
 }
+
+#[cfg(test)]
+const TEST_CONFIG_STRING: &str = "Test;;F1,BIN,Load ROM;O1,Option,Off,On;R0,Reset;V,v221106";
+
+#[cfg(test)]
+fn fake_core(
+    interface_type: CoreInterfaceType,
+) -> (FakeCore, MisterFpgaCore<SimulatedMemoryMapper>) {
+    let fake = FakeCore::new(TEST_CONFIG_STRING).with_interface_type(interface_type);
+    let core = MisterFpgaCore::new(fake.fpga()).unwrap();
+    (fake, core)
+}
+
+#[test]
+fn new_with_simulated_core() {
+    let (_, core) = fake_core(CoreInterfaceType::SpiBus16Bit);
+
+    assert_eq!(core.name(), "Test");
+    assert_eq!(core.core_type, CoreType::CoreTypeGeneric);
+    assert!(core.spi_type.is_wide());
+    assert_eq!(core.io_version, 1);
+    assert_eq!(core.menu_options().len(), 4);
+}
+
+#[test]
+fn bios_is_optional_without_bios_files() {
+    // A BIOS entry in the menu, but no BIOS file.
+    let fake = FakeCore::new("NES;;FS,NESFDSNSF;H1F2,BIN,Load FDS BIOS;R0,Reset;V,v123456");
+    let core = MisterFpgaCore::new(fake.fpga()).unwrap();
+    assert!(core.config.bios_load_info().is_some());
+
+    let root_dir = tempdir::TempDir::new("bios").unwrap();
+    let requirements = core.bios_requirements();
+    assert!(!requirements.is_empty());
+    for requirement in requirements {
+        assert!(requirement.optional);
+        assert_eq!(requirement.find_in(root_dir.path()).unwrap(), None);
+    }
+}
+
+#[cfg(test)]
+#[rstest::rstest]
+#[case::bus_8bit(CoreInterfaceType::SpiBus8Bit)]
+#[case::bus_16bit(CoreInterfaceType::SpiBus16Bit)]
+fn load_file_sends_data(#[case] interface_type: CoreInterfaceType) {
+    let wide = interface_type.is_wide();
+    let (fake, mut core) = fake_core(interface_type);
+
+    // An odd size, to make sure the last byte is sent on a 16 bits bus.
+    let data = (0..5001u32).map(|i| (i * 7) as u8).collect::<Vec<_>>();
+    let root_dir = tempdir::TempDir::new("mister").unwrap();
+    let path = root_dir.path().join("game.bin");
+    std::fs::write(&path, &data).unwrap();
+
+    core.load_file(&path, None).unwrap();
+    core.end_send_file().unwrap();
+
+    let files = fake.files();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].index, 1);
+    assert_eq!(files[0].extension, "BIN");
+    assert_eq!(files[0].size, Some(data.len() as u32));
+    assert!(files[0].done);
+
+    // A 16 bits bus pads the last word.
+    let mut expected = data.clone();
+    if wide {
+        expected.push(0);
+    }
+    assert_eq!(files[0].data, expected);
+}
+
+/// A reader returning at most 3 bytes at a time, like a slow file.
+#[cfg(test)]
+struct OddChunks<R>(R);
+
+#[cfg(test)]
+impl<R: Read> Read for OddChunks<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = buf.len().min(3);
+        self.0.read(&mut buf[..len])
+    }
+}
+
+#[test]
+fn send_file_with_odd_reads() {
+    let (fake, mut core) = fake_core(CoreInterfaceType::SpiBus16Bit);
+
+    let data = (0..5001u32).map(|i| (i * 7) as u8).collect::<Vec<_>>();
+    core.send_file(
+        MisterFpgaSendFileInfo::Buffered { index: 1 },
+        "BIN",
+        data.len() as u32,
+        OddChunks(data.as_slice()),
+    )
+    .unwrap();
+    core.end_send_file().unwrap();
+
+    // Only the last word is padded.
+    let mut expected = data.clone();
+    expected.push(0);
+    assert_eq!(fake.files()[0].data, expected);
+}
+
+#[test]
+fn send_cheats_to_core() {
+    use crate::core::cheats::CHEAT_CODE_SIZE;
+
+    let (fake, mut core) = fake_core(CoreInterfaceType::SpiBus16Bit);
+
+    // Without cheats, an empty list is sent to disable them.
+    core.send_cheats().unwrap();
+
+    let root_dir = tempdir::TempDir::new("mister").unwrap();
+    let path = root_dir.path().join("game.zip");
+    let mut zip = zip::ZipWriter::new(File::create(&path).unwrap());
+    zip.start_file("Lives.gg", zip::write::FileOptions::default())
+        .unwrap();
+    zip.write_all(&[1; CHEAT_CODE_SIZE]).unwrap();
+    zip.finish().unwrap();
+
+    let mut cheats = Cheats::from_archive(&path).unwrap();
+    cheats.toggle(0);
+    core.set_cheats(Some(cheats));
+    core.send_cheats().unwrap();
+
+    let files = fake.files();
+    assert_eq!(files.len(), 2);
+    assert!(files
+        .iter()
+        .all(|f| f.index == CHEATS_FILE_INDEX && f.size.is_none() && f.done));
+    assert_eq!(files[0].data, [0, 0]);
+    assert_eq!(files[1].data, [1; CHEAT_CODE_SIZE]);
+}
+
+#[test]
+fn send_dip_switches_to_core() {
+    use crate::mra::{Dip, Switches};
+
+    let (fake, mut core) = fake_core(CoreInterfaceType::SpiBus8Bit);
+
+    // Nothing is sent without DIP switches.
+    core.send_dip_switches().unwrap();
+    assert!(fake.files().is_empty());
+
+    let switches = Switches {
+        dips: vec![Dip {
+            name: "Lives".to_string(),
+            bits: 8..10,
+            choices: vec!["3".to_string(), "5".to_string()],
+            values: vec![0, 1],
+        }],
+        default: 0x02,
+    };
+    let mut dip_switches = DipSwitches::new("game", switches);
+    dip_switches.next_choice(0);
+    core.set_dip_switches(Some(dip_switches));
+    core.send_dip_switches().unwrap();
+
+    let files = fake.files();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].index, DIP_SWITCHES_FILE_INDEX);
+    assert!(files[0].done);
+    assert_eq!(files[0].data, [0x02, 0x01, 0, 0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn restore_option_status_bits() {
+    let (fake, mut core) = fake_core(CoreInterfaceType::SpiBus16Bit);
+
+    // Only the option bit (O1) is restored, not the reset or unknown bits.
+    let mut saved = StatusBitMap::new();
+    saved.set(0, true);
+    saved.set(1, true);
+    saved.set(40, true);
+    core.restore_option_status_bits(&saved);
+
+    let mut expected = StatusBitMap::new();
+    expected.set(1, true);
+    assert_eq!(fake.status_bits(), expected);
+    assert_eq!(core.option_status_bits(), expected);
+}
+
+#[test]
+fn send_bios_on_boot_index() {
+    let (fake, mut core) = fake_core(CoreInterfaceType::SpiBus8Bit);
+
+    // Without a BIOS entry in the menu, `boot.rom` is optional and sent on
+    // index 0.
+    assert_eq!(
+        Core::bios_requirements(&core),
+        vec![BiosRequirement::new("boot.rom").with_optional(true)]
+    );
+
+    let data = vec![5, 6, 7];
+    let bios = Bios::Memory(
+        Some(std::path::PathBuf::from("boot.rom")),
+        std::io::Cursor::new(data.clone()),
+    );
+    Core::send_bios(&mut core, bios).unwrap();
+
+    let files = fake.files();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].index, 0);
+    assert_eq!(files[0].extension, "ROM");
+    assert_eq!(files[0].data, data);
+    assert!(files[0].done);
+}
+
+#[test]
+fn send_declared_bios_on_menu_entry() {
+    let fake = FakeCore::new("Test;;F1,NES,Load Game;F2,BIN,Load FDS BIOS;V,v1");
+    let mut core = MisterFpgaCore::new(fake.fpga()).unwrap();
+    let bios = |name: &str, data: &[u8]| {
+        Bios::Memory(
+            Some(std::path::PathBuf::from(name)),
+            std::io::Cursor::new(data.to_vec()),
+        )
+    };
+
+    // Without declarations, everything goes to index 0.
+    Core::send_bios(&mut core, bios("disksys.rom", &[1])).unwrap();
+
+    core.set_bios_requirements(vec![
+        BiosRequirement::new("boot.rom"),
+        BiosRequirement::new("disksys.rom"),
+        BiosRequirement::new("fds.bin").with_crc32(crc32fast::hash(&[3])),
+    ]);
+    assert_eq!(
+        Core::bios_requirements(&core),
+        vec![
+            BiosRequirement::new("boot.rom"),
+            BiosRequirement::new("disksys.rom"),
+            BiosRequirement::new("fds.bin").with_crc32(crc32fast::hash(&[3])),
+        ]
+    );
+
+    // `boot.rom` stays on index 0, declared files (by name or checksum) use
+    // the BIOS entry.
+    Core::send_bios(&mut core, bios("boot.rom", &[2])).unwrap();
+    Core::send_bios(&mut core, bios("DISKSYS.ROM", &[1])).unwrap();
+    Core::send_bios(&mut core, bios("renamed.bin", &[3])).unwrap();
+
+    let files = fake.files();
+    assert_eq!(
+        files.iter().map(|f| f.index).collect::<Vec<_>>(),
+        vec![0, 0, 2, 2]
+    );
+}
+
+#[test]
+fn send_rom_from_memory() {
+    let (fake, mut core) = fake_core(CoreInterfaceType::SpiBus8Bit);
+
+    // Without a path, the first file entry and its extension are used.
+    let data = vec![1, 2, 3, 4];
+    Core::send_rom(
+        &mut core,
+        Rom::Memory(None, std::io::Cursor::new(data.clone())),
+    )
+    .unwrap();
+    core.end_send_file().unwrap();
+
+    let files = fake.files();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0].index, 1);
+    assert_eq!(files[0].extension, "BIN");
+    assert_eq!(files[0].size, Some(4));
+    assert_eq!(files[0].data, data);
+}
+
+#[cfg(test)]
+#[rstest::rstest]
+#[case::bus_8bit(CoreInterfaceType::SpiBus8Bit)]
+#[case::bus_16bit(CoreInterfaceType::SpiBus16Bit)]
+fn poll_mounts_reads_and_writes(#[case] interface_type: CoreInterfaceType) {
+    use crate::fpga::simulator::fake_core::{SdRequest, SD_BLOCK_SIZE};
+
+    let (fake, mut core) = fake_core(interface_type);
+    let card = (0..SD_BLOCK_SIZE * 4)
+        .map(|i| (i / SD_BLOCK_SIZE) as u8 + 1)
+        .collect::<Vec<_>>();
+    core.mount(SdCard::from_memory(card.clone()), 0).unwrap();
+
+    // Nothing requested.
+    assert!(!core.poll_mounts().unwrap());
+
+    fake.push_sd_request(SdRequest::Read {
+        disk: 0,
+        lba: 2,
+        blocks: 1,
+    });
+    fake.push_sd_request(SdRequest::Write {
+        disk: 0,
+        lba: 1,
+        data: vec![0xAB; SD_BLOCK_SIZE],
+    });
+
+    assert!(core.poll_mounts().unwrap());
+    let reads = fake.sd_reads();
+    assert_eq!(reads.len(), 1);
+    assert_eq!(reads[0].lba, 2);
+    assert_eq!(reads[0].data, &card[SD_BLOCK_SIZE * 2..SD_BLOCK_SIZE * 3]);
+
+    assert!(core.poll_mounts().unwrap());
+    assert_eq!(fake.pending_sd_requests(), 0);
+    assert!(!core.poll_mounts().unwrap());
+
+    let mut written = Vec::new();
+    let io = core.cards[0].as_mut().unwrap().as_io();
+    io.seek(SeekFrom::Start(0)).unwrap();
+    io.read_to_end(&mut written).unwrap();
+    assert_eq!(&written[..SD_BLOCK_SIZE], &card[..SD_BLOCK_SIZE]);
+    assert_eq!(
+        &written[SD_BLOCK_SIZE..SD_BLOCK_SIZE * 2],
+        &[0xAB; SD_BLOCK_SIZE][..]
+    );
+    assert_eq!(&written[SD_BLOCK_SIZE * 2..], &card[SD_BLOCK_SIZE * 2..]);
+}
+
+#[test]
+fn trigger_menu_sends_status_bits() {
+    let (fake, mut core) = fake_core(CoreInterfaceType::SpiBus16Bit);
+    let option = core.menu_options()[1].clone();
+    let reset = core.menu_options()[2].clone();
+
+    // Options cycle through their values.
+    assert!(core.trigger_menu(&option).unwrap());
+    assert!(fake.status_bits().get(1));
+    assert!(core.trigger_menu(&option).unwrap());
+    assert!(!fake.status_bits().get(1));
+
+    // Triggers pulse their bit.
+    assert!(core.trigger_menu(&reset).unwrap());
+    let history = fake.status_history();
+    assert_eq!(history.len(), 4);
+    assert!(history[2].get(0));
+    assert!(!history[3].get(0));
+}
+
+#[test]
+fn hidden_or_disabled_menu_cannot_be_used() {
+    let fake =
+        FakeCore::new("Test;;O1,Lock,Off,On;H1R2,Hidden Reset;D1O3,Locked Option,Off,On;V,v1");
+    let mut core = MisterFpgaCore::new(fake.fpga()).unwrap();
+    let id = |label: &str| {
+        let item = core.config.menu.iter().find(|m| m.label() == Some(label));
+        item.and_then(|m| m.menu_id()).unwrap()
+    };
+    let (lock, reset, option) = (id("Lock"), id("Hidden Reset"), id("Locked Option"));
+
+    core.trigger(reset).unwrap();
+    core.int_option(option, 1).unwrap();
+    assert!(fake.status_bits().get(3));
+
+    // Conditions are checked against the menu mask, not the status bits.
+    core.int_option(lock, 1).unwrap();
+    core.trigger(reset).unwrap();
+
+    fake.set_menu_mask(1 << 1);
+    assert!(core.trigger(reset).is_err());
+    assert!(core.int_option(option, 0).is_err());
+    assert!(fake.status_bits().get(3));
+}
+
+#[test]
+fn nes_menu_conditions_use_menu_mask() {
+    const SAVE_STATE: &str = "Save state(Alt+F1-F4)";
+
+    let fake = FakeCore::new(include_str!("../../tests/assets/config_string/nes/config"));
+    let mut core = MisterFpgaCore::new(fake.fpga()).unwrap();
+    let save_state_enabled = |core: &MisterFpgaCore<SimulatedMemoryMapper>| {
+        core.menu()
+            .unwrap()
+            .into_iter()
+            .find_map(|item| match item {
+                CoreMenuItem::Trigger { label, .. } if label == SAVE_STATE => Some(true),
+                CoreMenuItem::Label { label, .. } if label == SAVE_STATE => Some(false),
+                _ => None,
+            })
+            .unwrap()
+    };
+    let save_state = core
+        .config
+        .menu
+        .iter()
+        .find(|m| m.label() == Some(SAVE_STATE))
+        .and_then(|m| m.menu_id())
+        .unwrap();
+
+    // Status bit 7 is the "Save Backup RAM" trigger, not the savestate
+    // condition (`d7`), which is bit 7 of the menu mask.
+    let mut bits = StatusBitMap::new();
+    bits.set(7, true);
+    fake.set_status_bits(bits);
+    core.read_status_bits();
+    assert!(!save_state_enabled(&core));
+    assert!(core.trigger(save_state).is_err());
+
+    fake.set_menu_mask(1 << 7);
+    core.read_status_bits();
+    assert!(save_state_enabled(&core));
+    core.trigger(save_state).unwrap();
+}
+
+#[test]
+fn read_status_bits_from_core() {
+    let (fake, mut core) = fake_core(CoreInterfaceType::SpiBus16Bit);
+
+    let mut bits = StatusBitMap::new();
+    bits.set(1, true);
+    bits.set(0, true);
+    fake.set_status_bits(bits);
+
+    let read = *core.read_status_bits();
+    assert!(read.get(1));
+
+    // The core acknowledges the change with bit 0 cleared.
+    assert!(!read.get(0));
+    assert_eq!(fake.status_history(), vec![read]);
+
+    // No change, nothing is sent back.
+    core.read_status_bits();
+    assert_eq!(fake.status_history().len(), 1);
+}
+
+#[cfg(test)]
+fn analog_commands(fake: &FakeCore) -> Vec<Vec<u16>> {
+    fake.other_commands()
+        .into_iter()
+        .filter(|(features, words)| features.io() && matches!(words[0] & 0xFF, 0x1A | 0x3D))
+        .map(|(_, words)| words)
+        .collect()
+}
+
+#[test]
+fn gamepad_axis_sends_analog() {
+    let (fake, mut core) = fake_core(CoreInterfaceType::SpiBus16Bit);
+
+    core.gamepad_axis(1, Axis::LEFT_X, i16::MAX).unwrap();
+    core.gamepad_axis(1, Axis::RIGHT_Y, i16::MIN).unwrap();
+    core.gamepad_axis(1, Axis::TRIGGER_LEFT, 1000).unwrap();
+    assert!(core.gamepad_axis(6, Axis::LEFT_X, 0).is_err());
+
+    assert_eq!(
+        analog_commands(&fake),
+        vec![
+            // Left stick, then the paddle.
+            vec![0x1A, 0x01, 0x007F],
+            vec![0x1A, 0x11, 0x00FF],
+            // Right stick.
+            vec![0x3D, 0x01, 0x8000],
+        ]
+    );
+}
+
+#[test]
+fn mouse_spinner_throttle() {
+    let (fake, mut core) = fake_core(CoreInterfaceType::SpiBus16Bit);
+
+    core.mouse_spinner(0, 5, 3, 0);
+
+    // Half the speed, keeping the remainder for the next movement.
+    core.spinner_throttle = 200;
+    core.mouse_spinner(0, 3, 0, 0);
+    core.mouse_spinner(0, 3, 0, 0);
+
+    // Reversed, on the Y axis.
+    core.spinner_throttle = -100;
+    core.spinner_axis = 1;
+    core.spinner_remainder = 0;
+    core.mouse_spinner(0, 5, 3, 0);
+
+    assert_eq!(
+        analog_commands(&fake),
+        vec![
+            vec![0x1A, 0x20, 5],
+            vec![0x1A, 0x20, 1],
+            vec![0x1A, 0x20, 2],
+            vec![0x1A, 0x20, 0xFD],
+        ]
+    );
+}
+
+#[test]
+fn mouse_packets() {
+    let (fake, mut core) = fake_core(CoreInterfaceType::SpiBus16Bit);
+
+    core.mouse_button_down(MouseButton::Left).unwrap();
+    core.mouse_move(3, -2, 1).unwrap();
+    core.mouse_button_up(MouseButton::Left).unwrap();
+
+    // Half the speed, keeping the remainder for the next movement.
+    core.mouse_throttle = 2;
+    core.mouse_move(-3, 600, 0).unwrap();
+    // Nothing sent for a movement that is too small.
+    core.mouse_move(1, 1, 0).unwrap();
+    core.mouse_move(3, 1, 0).unwrap();
+
+    let packets = fake
+        .other_commands()
+        .into_iter()
+        .filter(|(features, words)| features.io() && words[0] == 0x04)
+        .map(|(_, words)| words[1..].to_vec())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        packets,
+        vec![
+            vec![0x0009, 0, 0],
+            // Up is positive in PS/2.
+            vec![0x0109, 3, 2],
+            vec![0x0008, 0, 0],
+            // Clamped to 9 bits, with the sign in the status.
+            vec![0x0038, 0xFF, 0x01],
+            // With the remainders of the previous movements.
+            vec![0x0028, 1, 0xFF],
+        ]
+    );
+}
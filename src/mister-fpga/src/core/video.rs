@@ -39,7 +39,7 @@ pub fn select_mode(
 
 pub fn init_mode(
     options: &config::MisterConfig,
-    fpga: &mut crate::fpga::MisterFpga,
+    fpga: &mut crate::fpga::MisterFpga<impl MemoryMapper>,
     is_menu: bool,
 ) {
     if is_menu {
@@ -159,6 +159,16 @@ impl VideoInfo {
         self.aspect_ratio
     }
 
+    /// The aspect ratio the core should be displayed at. This is the one
+    /// the core asks for if any, or the aspect ratio of its resolution.
+    pub fn display_aspect_ratio(&self) -> AspectRatio {
+        if self.arx != 0 && self.ary != 0 {
+            AspectRatio::new(self.arx, self.ary)
+        } else {
+            self.aspect_ratio
+        }
+    }
+
     pub fn vtime(&self) -> Duration {
         Duration::from_nanos(self.vtime_ms as u64 * 10)
     }
@@ -564,14 +564,14 @@ fn hdmi_config_set_hdr(device: &mut LinuxI2CDevice, options: &MisterConfig) -> R
 
 pub fn init_mode_menu(
     options: &config::MisterConfig,
-    fpga: &mut crate::fpga::MisterFpga,
+    fpga: &mut crate::fpga::MisterFpga<impl MemoryMapper>,
 ) -> Result<(), String> {
     video_mode::init_mode(options, fpga.spi_mut(), true)
 }
 
 pub fn init_mode_core(
     options: &config::MisterConfig,
-    fpga: &mut crate::fpga::MisterFpga,
+    fpga: &mut crate::fpga::MisterFpga<impl MemoryMapper>,
 ) -> Result<(), String> {
     video_mode::init_mode(options, fpga.spi_mut(), false)
 }
@@ -1,7 +1,7 @@
 use std::cell::UnsafeCell;
 use std::io::Read;
-use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use strum::{Display, EnumIter, FromRepr};
@@ -9,13 +9,14 @@ use tracing::{debug, error, info, trace};
 
 use cyclone_v::fpgamgrregs::ctrl::{FpgaCtrlCfgWidth, FpgaCtrlEn, FpgaCtrlNce};
 use cyclone_v::fpgamgrregs::stat::StatusRegisterMode;
-use cyclone_v::memory::DevMemMemoryMapper;
+use cyclone_v::memory::{DevMemMemoryMapper, MemoryMapper};
 pub use program::Program;
 pub use spi::*;
 
 use crate::fpga::osd_io::{OsdDisable, OsdEnable};
 
 mod program;
+pub mod simulator;
 mod spi;
 
 #[derive(Debug, Copy, Clone)]
@@ -101,19 +102,28 @@ impl CoreInterfaceType {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct MisterFpga {
-    soc: Arc<UnsafeCell<cyclone_v::SocFpga<DevMemMemoryMapper>>>,
-    spi: Spi<DevMemMemoryMapper>,
+#[derive(Debug)]
+pub struct MisterFpga<M: MemoryMapper = DevMemMemoryMapper> {
+    soc: Arc<UnsafeCell<cyclone_v::SocFpga<M>>>,
+    spi: Spi<M>,
+}
+
+impl<M: MemoryMapper> Clone for MisterFpga<M> {
+    fn clone(&self) -> Self {
+        Self {
+            soc: self.soc.clone(),
+            spi: self.spi.clone(),
+        }
+    }
 }
 
 // SAFETY:
 // Since the FPGA is using memory-mapped I/O, it is not safe to send it to another thread.
-unsafe impl Send for MisterFpga {}
-unsafe impl Sync for MisterFpga {}
+unsafe impl<M: MemoryMapper> Send for MisterFpga<M> {}
+unsafe impl<M: MemoryMapper> Sync for MisterFpga<M> {}
 
 // OSD specific functions.
-impl MisterFpga {
+impl<M: MemoryMapper> MisterFpga<M> {
     pub fn osd_enable(&mut self) {
         let _ = self.spi_mut().execute(OsdEnable);
     }
@@ -123,16 +133,45 @@ impl MisterFpga {
 }
 
 impl MisterFpga {
-    fn new(soc: Arc<UnsafeCell<cyclone_v::SocFpga<DevMemMemoryMapper>>>) -> Self {
+    pub fn init() -> Result<Self, &'static str> {
+        unsafe {
+            if INITIALIZED.load(Ordering::Relaxed) {
+                const MSG: &str = "FPGA already initialized. This is an error.";
+                error!("{}", MSG);
+                return Err(MSG);
+            }
+
+            info!("Initializing FPGA");
+
+            let fpga = Self::from_soc(cyclone_v::SocFpga::default());
+
+            FPGA_SINGLETON = Some(fpga.clone());
+
+            INITIALIZED.store(true, Ordering::Relaxed);
+            Ok(fpga)
+        }
+    }
+}
+
+impl<M: MemoryMapper> MisterFpga<M> {
+    fn new(soc: Arc<UnsafeCell<cyclone_v::SocFpga<M>>>) -> Self {
         Self {
             soc: soc.clone(),
             spi: Spi::new(soc),
         }
     }
 
+    /// Create an FPGA from any SoC. This does not register the FPGA as the
+    /// global instance, see [`MisterFpga::init`] for the DE10-Nano.
+    pub fn from_soc(soc: cyclone_v::SocFpga<M>) -> Self {
+        let mut fpga = Self::new(Arc::new(UnsafeCell::new(soc)));
+        fpga.regs_mut().set_gpo(0);
+        fpga
+    }
+
     #[inline]
     #[allow(clippy::mut_from_ref)]
-    fn soc_mut(&self) -> &mut cyclone_v::SocFpga<DevMemMemoryMapper> {
+    fn soc_mut(&self) -> &mut cyclone_v::SocFpga<M> {
         unsafe { &mut (*self.soc.get()) }
     }
 
@@ -144,44 +183,23 @@ impl MisterFpga {
         self.soc_mut().regs_mut()
     }
 
-    pub fn init() -> Result<Self, &'static str> {
-        unsafe {
-            if INITIALIZED.load(Ordering::Relaxed) {
-                const MSG: &str = "FPGA already initialized. This is an error.";
-                error!("{}", MSG);
-                return Err(MSG);
-            }
-
-            info!("Initializing FPGA");
-
-            let soc = cyclone_v::SocFpga::default();
-            let soc = Arc::new(UnsafeCell::new(soc));
-            let mut fpga = Self::new(soc.clone());
-
-            fpga.regs_mut().set_gpo(0);
-
-            FPGA_SINGLETON = Some(fpga.clone());
-
-            INITIALIZED.store(true, Ordering::Relaxed);
-            Ok(fpga)
-        }
-    }
-
-    pub fn spi(&self) -> &Spi<DevMemMemoryMapper> {
+    pub fn spi(&self) -> &Spi<M> {
         &self.spi
     }
 
-    pub fn spi_mut(&mut self) -> &mut Spi<DevMemMemoryMapper> {
+    pub fn spi_mut(&mut self) -> &mut Spi<M> {
         &mut self.spi
     }
 
     pub fn core_type(&mut self) -> Option<CoreType> {
-        let regs = self.regs_mut();
+        let soc = self.soc_mut();
 
-        let gpo = regs.gpo() & 0x7FFF_FFFF;
-        regs.set_gpo(0);
-        let core_type: u32 = regs.gpi();
-        regs.set_gpo(gpo | 0x80000000);
+        let gpo = soc.regs().gpo() & 0x7FFF_FFFF;
+        soc.regs_mut().set_gpo(0);
+        soc.memory.sync();
+        let core_type: u32 = soc.regs().gpi();
+        soc.regs_mut().set_gpo(gpo | 0x80000000);
+        soc.memory.sync();
 
         if (core_type & 0xFFFFFF00) != 0x5CA62300 {
             error!("FPGA core type mismatch");
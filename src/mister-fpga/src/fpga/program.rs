@@ -1,11 +1,12 @@
 use crate::fpga::{FpgaError, MisterFpga};
+use cyclone_v::memory::MemoryMapper;
 
 pub trait Program {
-    fn load(&self, fpga: &mut MisterFpga) -> Result<(), FpgaError>;
+    fn load<M: MemoryMapper>(&self, fpga: &mut MisterFpga<M>) -> Result<(), FpgaError>;
 }
 
 impl Program for &[u8] {
-    fn load(&self, fpga: &mut MisterFpga) -> Result<(), FpgaError> {
+    fn load<M: MemoryMapper>(&self, fpga: &mut MisterFpga<M>) -> Result<(), FpgaError> {
         fpga.load_rbf_bytes(self)
     }
 }
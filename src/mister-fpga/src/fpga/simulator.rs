@@ -0,0 +1,201 @@
+//! A simulated MiSTer core, playing the FPGA side of the GPO/GPI protocol.
+//! This allows running the whole stack off-device (e.g. in tests), using a
+//! [`cyclone_v::SocFpga::simulated`] SoC.
+use cyclone_v::memory::{GpioHandler, SimulatedMemoryMapper};
+use cyclone_v::SocFpga;
+
+use crate::fpga::feature::SpiFeatureSet;
+use crate::fpga::spi::{SSPI_ACK, SSPI_DATA_MASK, SSPI_STROBE};
+use crate::fpga::{CoreInterfaceType, CoreType, MisterFpga};
+
+pub mod fake_core;
+
+/// The value of GPI when GPO is 0, without the core type.
+const CORE_TYPE_MAGIC: u32 = 0x5CA6_2300;
+
+/// The FPGA side of SPI commands. Receives the words sent by the HPS and
+/// returns the words to answer.
+pub trait SpiResponder: Send {
+    /// A new command was sent, with `features` enabled. Returns the word to
+    /// answer.
+    fn command(&mut self, features: SpiFeatureSet, command: u16) -> u16;
+
+    /// A word was sent as part of the current command. Returns the word to
+    /// answer.
+    fn write(&mut self, word: u16) -> u16;
+
+    /// The current command is done (its features were disabled).
+    fn end(&mut self) {}
+}
+
+/// A responder that answers 0 to everything.
+impl SpiResponder for () {
+    fn command(&mut self, _features: SpiFeatureSet, _command: u16) -> u16 {
+        0
+    }
+
+    fn write(&mut self, _word: u16) -> u16 {
+        0
+    }
+}
+
+/// A simulated core. Handles the core type query and the SPI handshake
+/// (strobe and acknowledge), and forwards the SPI words to a
+/// [`SpiResponder`].
+#[derive(Debug)]
+pub struct SimulatedCore<R: SpiResponder> {
+    core_type: u8,
+    wide: bool,
+    io_version: u8,
+    responder: R,
+
+    features: SpiFeatureSet,
+    in_command: bool,
+    strobe: bool,
+    response: u16,
+}
+
+impl<R: SpiResponder> SimulatedCore<R> {
+    /// Create a generic 16-bit core (IO version 1).
+    pub fn new(responder: R) -> Self {
+        Self {
+            core_type: CoreType::CoreTypeGeneric as u8,
+            wide: true,
+            io_version: 1,
+            responder,
+            features: SpiFeatureSet::NONE,
+            in_command: false,
+            strobe: false,
+            response: 0,
+        }
+    }
+
+    pub fn with_core_type(self, core_type: CoreType) -> Self {
+        Self {
+            core_type: core_type as u8,
+            ..self
+        }
+    }
+
+    pub fn with_interface_type(self, interface_type: CoreInterfaceType) -> Self {
+        Self {
+            wide: interface_type.is_wide(),
+            ..self
+        }
+    }
+
+    pub fn with_io_version(self, io_version: u8) -> Self {
+        Self { io_version, ..self }
+    }
+
+    /// Create a simulated SoC running this core, and an FPGA over it.
+    pub fn into_fpga(self) -> MisterFpga<SimulatedMemoryMapper>
+    where
+        R: 'static,
+    {
+        MisterFpga::from_soc(SocFpga::simulated(self))
+    }
+
+    fn strobe(&mut self, features: SpiFeatureSet, word: u16) -> u16 {
+        if self.in_command {
+            self.responder.write(word)
+        } else {
+            self.in_command = true;
+            self.responder.command(features, word)
+        }
+    }
+}
+
+impl<R: SpiResponder> GpioHandler for SimulatedCore<R> {
+    fn gpo_changed(&mut self, gpo: u32) -> u32 {
+        // Without the highest bit, the core answers with its type.
+        if gpo & 0x8000_0000 == 0 {
+            return CORE_TYPE_MAGIC | self.core_type as u32;
+        }
+
+        let features = SpiFeatureSet::from(gpo);
+        if features != self.features {
+            if self.in_command {
+                self.responder.end();
+                self.in_command = false;
+            }
+            self.features = features;
+        }
+
+        let strobe = gpo & SSPI_STROBE != 0;
+        if strobe && !self.strobe {
+            self.response = self.strobe(features, (gpo & SSPI_DATA_MASK) as u16);
+        }
+        self.strobe = strobe;
+
+        let mut gpi = (self.wide as u32) << 16
+            | ((self.io_version as u32) & 0b11) << 18
+            | self.response as u32;
+        if strobe {
+            gpi |= SSPI_ACK;
+        }
+        gpi
+    }
+}
+
+#[test]
+fn core_type() {
+    let mut fpga = SimulatedCore::new(())
+        .with_core_type(CoreType::CoreTypeGenericDualSdram)
+        .with_interface_type(CoreInterfaceType::SpiBus8Bit)
+        .with_io_version(2)
+        .into_fpga();
+
+    fpga.wait_for_ready();
+    assert_eq!(fpga.core_type(), Some(CoreType::CoreTypeGenericDualSdram));
+    assert_eq!(
+        fpga.core_interface_type(),
+        Some(CoreInterfaceType::SpiBus8Bit)
+    );
+    assert_eq!(fpga.core_io_version(), Some(2));
+}
+
+#[test]
+fn commands() {
+    #[derive(Default)]
+    struct Echo(Vec<Vec<u16>>);
+
+    impl SpiResponder for std::sync::Arc<std::sync::Mutex<Echo>> {
+        fn command(&mut self, _features: SpiFeatureSet, command: u16) -> u16 {
+            self.lock().unwrap().0.push(vec![command]);
+            command
+        }
+
+        fn write(&mut self, word: u16) -> u16 {
+            let mut echo = self.lock().unwrap();
+            echo.0.last_mut().unwrap().push(word);
+            word + 1
+        }
+    }
+
+    let echo = std::sync::Arc::new(std::sync::Mutex::new(Echo::default()));
+    let mut fpga = SimulatedCore::new(echo.clone()).into_fpga();
+
+    let mut out = 0;
+    fpga.spi_mut()
+        .command(crate::fpga::user_io::UserIoCommands::UserIoGetString)
+        .write_read(0x1234, &mut out);
+    assert_eq!(out, 0x1235);
+    fpga.spi_mut()
+        .command(crate::fpga::user_io::UserIoCommands::UserIoGetStatusBits)
+        .write(1)
+        .write(2);
+
+    assert_eq!(
+        echo.lock().unwrap().0,
+        vec![vec![0x14, 0x1234], vec![0x29, 1, 2]]
+    );
+}
+
+#[test]
+fn load_program() {
+    let mut fpga = SimulatedCore::new(()).into_fpga();
+
+    fpga.load(&[0u8; 64][..]).unwrap();
+    assert!(fpga.is_ready());
+}
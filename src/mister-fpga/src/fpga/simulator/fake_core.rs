@@ -0,0 +1,397 @@
+//! A fake core that can be scripted from tests. It answers the SPI commands
+//! the way a core using `hps_io` would, and records what the HPS sent.
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use cyclone_v::memory::SimulatedMemoryMapper;
+
+use crate::fpga::feature::SpiFeatureSet;
+use crate::fpga::file_io::Commands as FileIoCommands;
+use crate::fpga::simulator::{SimulatedCore, SpiResponder};
+use crate::fpga::user_io::UserIoCommands;
+use crate::fpga::{CoreInterfaceType, MisterFpga};
+use crate::types::StatusBitMap;
+
+/// The size of SD blocks requested by the fake core.
+pub const SD_BLOCK_SIZE: usize = 512;
+
+/// A request from the core to a mounted SD card, in blocks of
+/// [`SD_BLOCK_SIZE`] bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SdRequest {
+    /// The core reads `blocks` blocks at `lba`.
+    Read { disk: u8, lba: u32, blocks: u8 },
+
+    /// The core writes `data` at `lba`. The length of `data` should be a
+    /// multiple of the block size.
+    Write { disk: u8, lba: u32, data: Vec<u8> },
+}
+
+impl SdRequest {
+    /// The status word answered to `GetSdStat`.
+    fn status(&self) -> u16 {
+        let (disk, blocks, op) = match self {
+            SdRequest::Read { disk, blocks, .. } => (*disk, *blocks as usize, 1),
+            SdRequest::Write { disk, data, .. } => (*disk, data.len() / SD_BLOCK_SIZE, 2),
+        };
+
+        // Check bit, number of blocks minus 1, block size (128 << 2), disk, op.
+        0x8000
+            | (((blocks.max(1) - 1) as u16) & 0x3F) << 9
+            | 2 << 6
+            | ((disk as u16) & 0xF) << 2
+            | op
+    }
+
+    fn lba(&self) -> u32 {
+        match self {
+            SdRequest::Read { lba, .. } | SdRequest::Write { lba, .. } => *lba,
+        }
+    }
+}
+
+/// The data sent by the HPS for a [`SdRequest::Read`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SdReadData {
+    pub lba: u32,
+    pub data: Vec<u8>,
+}
+
+/// A file sent to the core.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileTransfer {
+    pub index: u8,
+    pub extension: String,
+    pub size: Option<u32>,
+    pub data: Vec<u8>,
+
+    /// Whether the HPS ended the transfer.
+    pub done: bool,
+}
+
+/// The command being received, with the number of words received after it.
+#[derive(Debug, Clone, Copy)]
+enum Command {
+    GetString(usize),
+    GetStatusBits(usize),
+    GetMenuMask,
+    SetStatusBits(usize),
+    GetSdStat(usize),
+    SdRead,
+    SdWrite(usize),
+    FileIndex,
+    FileInfo(usize),
+    FileTx(usize),
+    FileTxData,
+    Other,
+}
+
+#[derive(Debug, Default)]
+struct FakeCoreState {
+    config_string: Vec<u8>,
+    wide: bool,
+
+    status: StatusBitMap,
+    status_counter: u8,
+    status_history: Vec<StatusBitMap>,
+    menu_mask: u16,
+
+    sd_requests: VecDeque<SdRequest>,
+    sd_request: Option<SdRequest>,
+    sd_reads: Vec<SdReadData>,
+
+    file_index: u8,
+    file_extension: String,
+    files: Vec<FileTransfer>,
+
+    other_commands: Vec<(SpiFeatureSet, Vec<u16>)>,
+
+    command: Option<Command>,
+}
+
+impl FakeCoreState {
+    fn command(&mut self, features: SpiFeatureSet, word: u16) -> u16 {
+        let (command, response) = if features.io() {
+            match word & 0xFF {
+                c if c == UserIoCommands::UserIoGetString as u16 => (Command::GetString(0), 0),
+                c if c == UserIoCommands::UserIoGetStatusBits as u16 => (
+                    Command::GetStatusBits(0),
+                    0xA0 | (self.status_counter as u16 & 0x0F),
+                ),
+                c if c == UserIoCommands::UserIoGetMenuMask as u16 => (Command::GetMenuMask, 0),
+                c if c == UserIoCommands::UserIoSetStatus32Bits as u16 => {
+                    (Command::SetStatusBits(0), 0)
+                }
+                c if c == UserIoCommands::UserIoGetSdStat as u16 => {
+                    self.sd_request = self.sd_requests.pop_front();
+                    let status = self.sd_request.as_ref().map_or(0, SdRequest::status);
+                    (Command::GetSdStat(0), status)
+                }
+                0x17 => {
+                    let lba = self.sd_request.as_ref().map_or(0, SdRequest::lba);
+                    self.sd_reads.push(SdReadData {
+                        lba,
+                        data: Vec::new(),
+                    });
+                    (Command::SdRead, 0)
+                }
+                0x18 => (Command::SdWrite(0), 0),
+                _ => (Command::Other, 0),
+            }
+        } else if features.fpga() {
+            match word {
+                c if c == FileIoCommands::FileIndex as u16 => (Command::FileIndex, 0),
+                c if c == FileIoCommands::FileInfo as u16 => (Command::FileInfo(0), 0),
+                c if c == FileIoCommands::FileTx as u16 => (Command::FileTx(0), 0),
+                c if c == FileIoCommands::FileTxDat as u16 => (Command::FileTxData, 0),
+                _ => (Command::Other, 0),
+            }
+        } else {
+            (Command::Other, 0)
+        };
+
+        if let Command::Other = command {
+            self.other_commands.push((features, vec![word]));
+        }
+
+        self.command = Some(command);
+        response
+    }
+
+    fn write(&mut self, word: u16) -> u16 {
+        let Some(mut command) = self.command else {
+            return 0;
+        };
+
+        let response = match &mut command {
+            Command::GetString(i) => {
+                *i += 1;
+                self.config_string.get(*i - 1).copied().unwrap_or(0) as u16
+            }
+            Command::GetStatusBits(i) => {
+                *i += 1;
+                self.status.as_raw_slice().get(*i - 1).copied().unwrap_or(0)
+            }
+            Command::GetMenuMask => self.menu_mask,
+            Command::SetStatusBits(i) => {
+                if let Some(w) = self.status.as_mut_raw_slice().get_mut(*i) {
+                    *w = word;
+                }
+                *i += 1;
+                0
+            }
+            Command::GetSdStat(i) => {
+                *i += 1;
+                let lba = self.sd_request.as_ref().map_or(0, SdRequest::lba);
+                match *i {
+                    2 => lba as u16,
+                    3 => (lba >> 16) as u16,
+                    _ => 0,
+                }
+            }
+            Command::SdRead => {
+                let wide = self.wide;
+                if let Some(read) = self.sd_reads.last_mut() {
+                    push_word(&mut read.data, word, wide);
+                }
+                0
+            }
+            Command::SdWrite(i) => {
+                let data = match &self.sd_request {
+                    Some(SdRequest::Write { data, .. }) => data.as_slice(),
+                    _ => &[],
+                };
+                let response = if self.wide {
+                    let lo = data.get(*i * 2).copied().unwrap_or(0);
+                    let hi = data.get(*i * 2 + 1).copied().unwrap_or(0);
+                    u16::from_le_bytes([lo, hi])
+                } else {
+                    data.get(*i).copied().unwrap_or(0) as u16
+                };
+                *i += 1;
+                response
+            }
+            Command::FileIndex => {
+                self.file_index = word as u8;
+                0
+            }
+            Command::FileInfo(i) => {
+                // The extension is sent as ".EXT", 2 characters per word.
+                if *i == 0 {
+                    self.file_extension.clear();
+                }
+                for ch in word.to_be_bytes() {
+                    if ch != 0 && ch != b'.' {
+                        self.file_extension.push(ch as char);
+                    }
+                }
+                *i += 1;
+                0
+            }
+            Command::FileTx(i) => {
+                match *i {
+                    0 if word as u8 == 0 => {
+                        if let Some(file) = self.files.last_mut() {
+                            file.done = true;
+                        }
+                    }
+                    0 => self.files.push(FileTransfer {
+                        index: self.file_index,
+                        extension: self.file_extension.clone(),
+                        ..Default::default()
+                    }),
+                    1 => {
+                        if let Some(file) = self.files.last_mut() {
+                            file.size = Some(word as u32);
+                        }
+                    }
+                    2 => {
+                        if let Some(file) = self.files.last_mut() {
+                            file.size = file.size.map(|s| s | (word as u32) << 16);
+                        }
+                    }
+                    _ => {}
+                }
+                *i += 1;
+                0
+            }
+            Command::FileTxData => {
+                let wide = self.wide;
+                if let Some(file) = self.files.last_mut() {
+                    push_word(&mut file.data, word, wide);
+                }
+                0
+            }
+            Command::Other => {
+                if let Some((_, words)) = self.other_commands.last_mut() {
+                    words.push(word);
+                }
+                0
+            }
+        };
+
+        self.command = Some(command);
+        response
+    }
+
+    fn end(&mut self) {
+        match self.command.take() {
+            Some(Command::SetStatusBits(_)) => self.status_history.push(self.status),
+            Some(Command::SdRead) | Some(Command::SdWrite(_)) => self.sd_request = None,
+            _ => {}
+        }
+    }
+}
+
+fn push_word(data: &mut Vec<u8>, word: u16, wide: bool) {
+    if wide {
+        data.extend_from_slice(&word.to_le_bytes());
+    } else {
+        data.push(word as u8);
+    }
+}
+
+/// A fake core. This is a handle that can be cloned; one clone is given to
+/// the simulated FPGA and the others are used to script the core and
+/// inspect what it received.
+#[derive(Debug, Clone, Default)]
+pub struct FakeCore(Arc<Mutex<FakeCoreState>>);
+
+impl FakeCore {
+    /// Create a fake core on a 16-bit bus, with a config string.
+    pub fn new(config_string: &str) -> Self {
+        let this = Self::default();
+        {
+            let mut state = this.state();
+            state.config_string = config_string.as_bytes().to_vec();
+            state.wide = true;
+        }
+        this
+    }
+
+    pub fn with_interface_type(self, interface_type: CoreInterfaceType) -> Self {
+        self.state().wide = interface_type.is_wide();
+        self
+    }
+
+    fn state(&self) -> MutexGuard<'_, FakeCoreState> {
+        self.0.lock().unwrap()
+    }
+
+    /// Create a simulated FPGA running this core.
+    pub fn fpga(&self) -> MisterFpga<SimulatedMemoryMapper> {
+        let interface_type = if self.state().wide {
+            CoreInterfaceType::SpiBus16Bit
+        } else {
+            CoreInterfaceType::SpiBus8Bit
+        };
+
+        SimulatedCore::new(self.clone())
+            .with_interface_type(interface_type)
+            .into_fpga()
+    }
+
+    /// The status bits, as last sent by the HPS.
+    pub fn status_bits(&self) -> StatusBitMap {
+        self.state().status
+    }
+
+    /// All the status bits sent by the HPS, in order.
+    pub fn status_history(&self) -> Vec<StatusBitMap> {
+        self.state().status_history.clone()
+    }
+
+    /// Change the status bits from the core side (e.g. a core resetting an
+    /// option). The HPS sees the change on its next `GetStatusBits`.
+    pub fn set_status_bits(&self, bits: StatusBitMap) {
+        let mut state = self.state();
+        state.status = bits;
+        state.status_counter = (state.status_counter + 1) & 0x0F;
+    }
+
+    /// Change the mask of the hide and disable conditions of the menu.
+    pub fn set_menu_mask(&self, mask: u16) {
+        self.state().menu_mask = mask;
+    }
+
+    /// Queue a request to a mounted SD card. Requests are answered to
+    /// `GetSdStat` in order.
+    pub fn push_sd_request(&self, request: SdRequest) {
+        self.state().sd_requests.push_back(request);
+    }
+
+    /// The number of SD requests that were not sent to the HPS yet.
+    pub fn pending_sd_requests(&self) -> usize {
+        self.state().sd_requests.len()
+    }
+
+    /// The data sent by the HPS for every read request.
+    pub fn sd_reads(&self) -> Vec<SdReadData> {
+        self.state().sd_reads.clone()
+    }
+
+    /// The commands this fake core does not handle, with the features they
+    /// were sent with, the command word and all the words sent after it.
+    pub fn other_commands(&self) -> Vec<(SpiFeatureSet, Vec<u16>)> {
+        self.state().other_commands.clone()
+    }
+
+    /// The files sent to the core, in order.
+    pub fn files(&self) -> Vec<FileTransfer> {
+        self.state().files.clone()
+    }
+}
+
+impl SpiResponder for FakeCore {
+    fn command(&mut self, features: SpiFeatureSet, command: u16) -> u16 {
+        self.state().command(features, command)
+    }
+
+    fn write(&mut self, word: u16) -> u16 {
+        self.state().write(word)
+    }
+
+    fn end(&mut self) {
+        self.state().end()
+    }
+}
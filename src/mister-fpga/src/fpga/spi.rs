@@ -8,12 +8,12 @@ use tracing::trace;
 
 /// SPI is a 16-bit data bus where the lowest 16 bits are the data and the highest 16-bits
 /// are the control bits.
-const SSPI_DATA_MASK: u32 = 0x0000_FFFF;
+pub(crate) const SSPI_DATA_MASK: u32 = 0x0000_FFFF;
 
 /// This signal is sent to indicate new data.
-const SSPI_STROBE: u32 = 1 << 17;
+pub(crate) const SSPI_STROBE: u32 = 1 << 17;
 /// This signal is received to indicate that the data was read.
-const SSPI_ACK: u32 = 1 << 17;
+pub(crate) const SSPI_ACK: u32 = 1 << 17;
 
 pub mod feature;
 pub mod file_io;
@@ -238,6 +238,7 @@ impl<M: MemoryMapper> Spi<M> {
         let regs = self.soc_mut().regs_mut();
         let gpo = (regs.gpo() & SpiFeatureSet::ALL.as_u32()) | 0x8000_0000;
         regs.set_gpo(gpo | new_mask);
+        self.soc_mut().memory.sync();
     }
 
     #[inline]
@@ -261,6 +262,7 @@ impl<M: MemoryMapper> Spi<M> {
         let regs = self.soc_mut().regs_mut();
         let gpo: u32 = (regs.gpo() & SpiFeatureSet::ALL.as_u32()) | 0x8000_0000;
         regs.set_gpo(gpo & !new_mask);
+        self.soc_mut().memory.sync();
     }
 
     #[inline]
@@ -3,7 +3,7 @@ use crate::fpga::{IntoLowLevelSpiCommand, SpiCommand, SpiCommandExt};
 
 #[derive(Debug, Clone, Copy, PartialEq, strum::Display)]
 #[repr(u16)]
-enum Commands {
+pub(crate) enum Commands {
     FileTx = 0x53,
     FileTxDat = 0x54,
     FileIndex = 0x55,
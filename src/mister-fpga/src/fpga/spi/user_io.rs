@@ -7,7 +7,7 @@ use crate::keyboard::Ps2Scancode;
 use crate::types::StatusBitMap;
 use bitfield::bitfield;
 use chrono::{DateTime, Datelike, NaiveDateTime, Timelike};
-use cyclone_v::memory::{DevMemMemoryMapper, MemoryMapper};
+use cyclone_v::memory::MemoryMapper;
 use std::mem::transmute;
 use std::ops::BitOrAssign;
 use std::time::SystemTime;
@@ -36,7 +36,7 @@ pub(crate) enum UserIoCommands {
     UserIoButtonSwitch = 0x01,
     UserIoJoystick0 = 0x02,
     UserIoJoystick1 = 0x03,
-    // UserIoMouse = 0x04,
+    UserIoMouse = 0x04,
     UserIoKeyboard = 0x05,
     // UserIoKeyboardOsd = 0x06,
     UserIoJoystick2 = 0x10,
@@ -55,6 +55,9 @@ pub(crate) enum UserIoCommands {
 
     UserIoSetSdConf = 0x19,
 
+    /// Analog joystick (left stick), paddle or spinner.
+    UserIoAnalogStick = 0x1A,
+
     /// Set sd card status
     UserIoSetSdStat = 0x1C,
 
@@ -76,6 +79,9 @@ pub(crate) enum UserIoCommands {
 
     UserIoGetStatusBits = 0x29,
 
+    /// Get the mask used by the hide and disable conditions of the menu.
+    UserIoGetMenuMask = 0x2E,
+
     /// Set frame buffer for HPS output
     UserIoSetFramebuffer = 0x2F,
 
@@ -89,6 +95,9 @@ pub(crate) enum UserIoCommands {
 
     UserIoSetArCust = 0x3A,
 
+    /// Analog joystick (right stick).
+    UserIoAnalogStick2 = 0x3D,
+
     UserIoGetFbParams = 0x40,
 }
 
@@ -203,6 +212,147 @@ impl UserIoJoystick {
     }
 }
 
+/// The analog channel of a joystick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalogChannel {
+    /// The left analog stick, with X and Y between -128 and 127.
+    LeftStick,
+
+    /// The right analog stick, with X and Y between -128 and 127.
+    RightStick,
+
+    /// A paddle, with an absolute position between 0 and 255 in X.
+    Paddle,
+
+    /// A spinner, with a relative movement between -128 and 127 in X.
+    Spinner,
+}
+
+/// Send the value of an analog channel of a joystick.
+pub struct UserIoAnalogJoystick {
+    index: u8,
+    channel: AnalogChannel,
+    x: u8,
+    y: u8,
+    io_version: u8,
+}
+
+impl SpiCommand for UserIoAnalogJoystick {
+    #[inline]
+    fn execute<S: SpiCommandExt>(&mut self, spi: &mut S) -> Result<(), String> {
+        let (command, index) = match self.channel {
+            AnalogChannel::LeftStick => (UserIoCommands::UserIoAnalogStick, self.index),
+            AnalogChannel::RightStick => (UserIoCommands::UserIoAnalogStick2, self.index),
+            AnalogChannel::Paddle => (UserIoCommands::UserIoAnalogStick, self.index | 0x10),
+            AnalogChannel::Spinner => (UserIoCommands::UserIoAnalogStick, self.index | 0x20),
+        };
+
+        let mut command = spi.command(command);
+        command.write_b(index);
+
+        // Older cores take the values as 2 bytes.
+        if self.io_version != 0 {
+            command.write((self.x as u16) | (self.y as u16) << 8);
+        } else {
+            command.write_b(self.x).write_b(self.y);
+        }
+
+        Ok(())
+    }
+}
+
+impl UserIoAnalogJoystick {
+    #[inline]
+    pub fn stick(index: u8, right: bool, x: i8, y: i8) -> Self {
+        Self::new(
+            index,
+            if right {
+                AnalogChannel::RightStick
+            } else {
+                AnalogChannel::LeftStick
+            },
+            x as u8,
+            y as u8,
+        )
+    }
+
+    #[inline]
+    pub fn paddle(index: u8, value: u8) -> Self {
+        Self::new(index, AnalogChannel::Paddle, value, 0)
+    }
+
+    #[inline]
+    pub fn spinner(index: u8, delta: i8) -> Self {
+        Self::new(index, AnalogChannel::Spinner, delta as u8, 0)
+    }
+
+    fn new(index: u8, channel: AnalogChannel, x: u8, y: u8) -> Self {
+        if index > 5 {
+            panic!("Invalid joystick index");
+        }
+
+        Self {
+            index,
+            channel,
+            x,
+            y,
+            io_version: 1,
+        }
+    }
+
+    pub fn with_io_version(self, io_version: u8) -> Self {
+        Self { io_version, ..self }
+    }
+}
+
+/// Send a relative mouse movement and the state of its buttons. This is a
+/// PS/2 packet, with the wheel movement sent alongside it like USB mice do.
+pub struct UserIoMouse {
+    buttons: u8,
+    dx: i16,
+    dy: i16,
+    wheel: i8,
+}
+
+impl SpiCommand for UserIoMouse {
+    #[inline]
+    fn execute<S: SpiCommandExt>(&mut self, spi: &mut S) -> Result<(), String> {
+        // PS/2 mice have 9 bits of movement, with the sign in the status byte.
+        let dx = self.dx.clamp(-255, 255);
+        let dy = self.dy.clamp(-255, 255);
+
+        // Bit 3 is always set.
+        let mut status = (self.buttons as u16 & 0b111) | 0b1000;
+        if dx < 0 {
+            status |= 0x10;
+        }
+        if dy < 0 {
+            status |= 0x20;
+        }
+
+        spi.command(UserIoCommands::UserIoMouse)
+            .write(status | (self.wheel as u8 as u16) << 8)
+            .write(dx as u8 as u16)
+            .write(dy as u8 as u16);
+
+        Ok(())
+    }
+}
+
+impl UserIoMouse {
+    /// Create a mouse packet. `buttons` has left, right and middle in its lowest
+    /// 3 bits. A positive `dy` moves up, as in PS/2.
+    #[inline]
+    pub fn new(buttons: u8, dx: i16, dy: i16, wheel: i8) -> Self {
+        Self {
+            buttons,
+            dx,
+            dy,
+            wheel,
+        }
+    }
+}
+
 pub struct UserIoKeyboardKeyDown(u32);
 
 impl From<Ps2Scancode> for UserIoKeyboardKeyDown {
@@ -365,6 +515,18 @@ impl SpiCommand for GetStatusBits<'_> {
     }
 }
 
+/// Get the mask the core uses for the hide and disable conditions of its
+/// menu (`H`, `h`, `D` and `d` in the config string).
+pub struct GetMenuMask<'a>(pub &'a mut u16);
+
+impl SpiCommand for GetMenuMask<'_> {
+    fn execute<S: SpiCommandExt>(&mut self, spi: &mut S) -> Result<(), String> {
+        spi.command(UserIoCommands::UserIoGetMenuMask)
+            .write_read(0, self.0);
+        Ok(())
+    }
+}
+
 /// Send the status bits.
 pub struct SetStatusBits<'a>(pub &'a StatusBitMap);
 
@@ -511,6 +673,51 @@ impl SpiCommand for SetSdStat {
     }
 }
 
+/// Records the words sent in SPI commands, answering each with its number
+/// (starting at `0xA001`).
+#[cfg(test)]
+#[derive(Clone, Default)]
+struct WordRecorder(std::sync::Arc<std::sync::Mutex<Vec<u16>>>);
+
+#[cfg(test)]
+impl crate::fpga::simulator::SpiResponder for WordRecorder {
+    fn command(&mut self, _features: SpiFeatureSet, _command: u16) -> u16 {
+        0
+    }
+
+    fn write(&mut self, word: u16) -> u16 {
+        let mut words = self.0.lock().unwrap();
+        words.push(word);
+        0xA000 | words.len() as u16
+    }
+}
+
+#[test]
+fn sd_read_write_wide() {
+    use crate::fpga::simulator::SimulatedCore;
+
+    let recorder = WordRecorder::default();
+    let mut fpga = SimulatedCore::new(recorder.clone()).into_fpga();
+
+    // A word holds 2 bytes, little-endian. Transmuting the byte slice to
+    // words sent one word per byte instead, reading past the end of the
+    // data.
+    fpga.spi_mut()
+        .execute(SdRead::new(&[1, 2, 3], true, 0))
+        .unwrap();
+    assert_eq!(*recorder.0.lock().unwrap(), vec![0x0201, 0x0003]);
+
+    // Same when reading from the core, which wrote past the end of the
+    // buffer.
+    recorder.0.lock().unwrap().clear();
+    let mut data = vec![0; 3];
+    fpga.spi_mut()
+        .execute(SdWrite::new(&mut data, true, 0))
+        .unwrap();
+    assert_eq!(recorder.0.lock().unwrap().len(), 2);
+    assert_eq!(data, [0x01, 0xA0, 0x02]);
+}
+
 impl SetSdStat {
     pub const fn with_writable(self, writable: bool) -> Self {
         Self { writable, ..self }
@@ -683,7 +890,10 @@ impl SpiCommand for SdRead<'_> {
         let mut command = spi.command(UserIoSectorRead::Read(self.ack));
 
         if self.wide {
-            command.write_buffer_w(unsafe { transmute::<_, &[u16]>(self.data) });
+            // Words are sent little-endian, and a trailing odd byte is padded.
+            for chunk in self.data.chunks(2) {
+                command.write(u16::from_le_bytes([chunk[0], *chunk.get(1).unwrap_or(&0)]));
+            }
         } else {
             command.write_buffer_b(self.data);
         }
@@ -708,7 +918,10 @@ impl SpiCommand for SdWrite<'_> {
         let mut command = spi.command(UserIoSectorRead::Write(self.ack));
 
         if self.wide {
-            command.read_buffer_w(unsafe { transmute::<_, &mut [u16]>(self.data.as_mut_slice()) });
+            for chunk in self.data.chunks_mut(2) {
+                let word = command.write_get(0u16).to_le_bytes();
+                chunk.copy_from_slice(&word[..chunk.len()]);
+            }
         } else {
             command.read_buffer_b(self.data.as_mut_slice());
         }
@@ -737,8 +950,8 @@ impl SetMemorySize {
         Self(size)
     }
 
-    pub fn from_fpga() -> Result<Self, &'static str> {
-        Self::from_memory(DevMemMemoryMapper::create(0x1FFFF000, 0x1000)?)
+    pub fn from_fpga<M: MemoryMapper>() -> Result<Self, &'static str> {
+        Self::from_memory(M::create(0x1FFFF000, 0x1000)?)
     }
 
     pub fn from_memory<M: MemoryMapper>(mut mapper: M) -> Result<Self, &'static str> {
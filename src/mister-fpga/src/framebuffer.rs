@@ -1,15 +1,25 @@
+use std::time::{Duration, Instant};
+
 use bitfield::bitfield;
+use image::imageops::FilterType;
 use image::{DynamicImage, RgbImage};
 use simple_endian::BigEndian;
 use tracing::debug;
 
 use cyclone_v::memory::{DevMemMemoryMapper, MemoryMapper};
 
+use crate::config::aspect::AspectRatio;
+
 pub const FB_BASE_ADDRESS: usize = 0x2000_0000;
 pub const BUFFER_SIZE: usize = 2048 * 1024 * 3 * 4;
 
 pub const SCALER_FB_TYPE: u8 = 0x01;
 
+/// Time to sleep between two reads of the frame counters when waiting for a
+/// frame. A frame is ~16ms, so this keeps the latency well under a
+/// millisecond without spinning.
+pub const FRAME_POLL_INTERVAL: Duration = Duration::from_micros(100);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum ScalerPixelFormat {
@@ -26,6 +36,37 @@ pub enum ScalerPixelFormat {
     INVALID = 0xFF,
 }
 
+impl ScalerPixelFormat {
+    /// The number of bytes per pixel, or `None` if the format is invalid.
+    pub fn bytes_per_pixel(&self) -> Option<usize> {
+        match self {
+            ScalerPixelFormat::RGB16 => Some(2),
+            ScalerPixelFormat::RGB24 => Some(3),
+            ScalerPixelFormat::RGBA32 => Some(4),
+            ScalerPixelFormat::INVALID => None,
+        }
+    }
+
+    /// Decode a pixel into RGB888. `pixel` must be `bytes_per_pixel` long.
+    #[inline]
+    fn to_rgb(self, pixel: &[u8]) -> [u8; 3] {
+        match self {
+            ScalerPixelFormat::RGB16 => {
+                // Little endian, 5 bits of red, 6 of green and 5 of blue.
+                // The high bits are repeated in the low bits so the full
+                // range is used.
+                let p = u16::from_le_bytes([pixel[0], pixel[1]]);
+                let r = ((p >> 11) & 0x1F) as u8;
+                let g = ((p >> 5) & 0x3F) as u8;
+                let b = (p & 0x1F) as u8;
+                [r << 3 | r >> 2, g << 2 | g >> 4, b << 3 | b >> 2]
+            }
+            ScalerPixelFormat::RGB24 | ScalerPixelFormat::RGBA32 => [pixel[0], pixel[1], pixel[2]],
+            ScalerPixelFormat::INVALID => [0, 0, 0],
+        }
+    }
+}
+
 impl From<u8> for ScalerPixelFormat {
     fn from(value: u8) -> Self {
         match value {
@@ -157,45 +198,151 @@ impl FramebufferType {
     }
 }
 
+/// The frame counters of all buffers, at a point in time. Each buffer has
+/// its own counter which changes every time the scaler writes a frame to it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FrameCounters([u8; 3]);
+
+impl FrameCounters {
+    /// A single value for all counters, which changes with every frame.
+    pub fn counter(&self) -> u8 {
+        self.0.iter().fold(0u8, |acc, c| acc.wrapping_add(*c))
+    }
+
+    /// The number of buffers that were written to since `other`. With triple
+    /// buffering, this is the number of frames that were produced (up to 3).
+    pub fn frames_since(&self, other: &Self) -> u32 {
+        self.0
+            .iter()
+            .zip(other.0)
+            .filter(|(a, b)| **a != *b)
+            .count() as u32
+    }
+
+    /// The index of the buffer holding the newest frame, from the 3-bit frame
+    /// number the scaler writes in bits 7-5 of each counter. The other
+    /// buffers hold the one or two frames before it. Returns `None` if no
+    /// single buffer is ahead of the others (e.g. before the core starts).
+    pub fn newest(&self) -> Option<u8> {
+        let frame = |i: usize| self.0[i] >> 5;
+        let mut newest = (0..3).filter(|i| {
+            (0..3)
+                .filter(|j| j != i)
+                .all(|j| matches!(frame(*i).wrapping_sub(frame(j)) & 7, 1 | 2))
+        });
+
+        match (newest.next(), newest.next()) {
+            (Some(i), None) => Some(i as u8),
+            _ => None,
+        }
+    }
+}
+
+/// A frame observed after waiting for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameWait {
+    /// The frame counters observed.
+    pub counters: FrameCounters,
+
+    /// The number of frames that were produced since the last wait, but
+    /// were not observed. Only detectable when the core is triple buffered.
+    pub missed: u32,
+}
+
+impl FrameWait {
+    /// The frame counter observed. See [`FrameCounters::counter`].
+    pub fn counter(&self) -> u8 {
+        self.counters.counter()
+    }
+}
+
+/// The frame counter of the buffers a framebuffer doesn't have, which never
+/// changes.
+static NO_FRAME_COUNTER: u8 = 0;
+
+/// Pointers to the frame counter of each buffer. The counter is the lower
+/// byte of the header attributes.
+#[derive(Clone, Copy)]
+struct FrameCounterPtrs([*const u8; 3]);
+
+impl FrameCounterPtrs {
+    fn new<M: MemoryMapper>(framebuffer: &FpgaFramebuffer<M>) -> Self {
+        Self::with_type(framebuffer, framebuffer.ty_)
+    }
+
+    fn with_type<M: MemoryMapper>(
+        framebuffer: &FpgaFramebuffer<M>,
+        ty: Option<FramebufferType>,
+    ) -> Self {
+        let start = framebuffer.memory.as_ptr::<u8>();
+        // The first buffer is always there. Missing buffers (e.g. when single
+        // buffered) must not count as frames, so they never change.
+        let counter = |index| match ty.and_then(|ty| ty.offset_of(index)) {
+            Some(offset) => unsafe { start.add(offset).add(5) },
+            None => &NO_FRAME_COUNTER as *const u8,
+        };
+
+        Self([unsafe { start.add(5) }, counter(1), counter(2)])
+    }
+
+    #[inline]
+    fn read(&self) -> FrameCounters {
+        unsafe { FrameCounters(self.0.map(|ptr| ptr.read_volatile())) }
+    }
+
+    /// Wait until the counters differ from `since`, sleeping between reads.
+    /// Returns `None` if the timeout expired first.
+    fn wait(&self, since: FrameCounters, timeout: Option<Duration>) -> Option<FrameWait> {
+        let deadline = timeout.map(|t| Instant::now() + t);
+
+        loop {
+            let counters = self.read();
+            if counters != since {
+                return Some(FrameWait {
+                    counters,
+                    missed: counters.frames_since(&since).saturating_sub(1),
+                });
+            }
+
+            if deadline.is_some_and(|d| Instant::now() >= d) {
+                return None;
+            }
+            std::thread::sleep(FRAME_POLL_INTERVAL);
+        }
+    }
+}
+
 /// An iterator that waits a frame.
 pub struct FrameIter {
-    frame_counters: [*const u8; 3],
+    frame_counters: FrameCounterPtrs,
+    last: FrameCounters,
 }
 
 impl FrameIter {
     pub fn new<M: MemoryMapper>(framebuffer: &FpgaFramebuffer<M>) -> Self {
-        let header0 = framebuffer.offset_of(0).unwrap();
-        let header1 = framebuffer.offset_of(1).unwrap_or(header0);
-        let header2 = framebuffer.offset_of(2).unwrap_or(header0);
-
-        unsafe {
-            let ptr0 = framebuffer.memory.as_ptr::<u8>().add(header0).add(5);
-            let ptr1 = framebuffer.memory.as_ptr::<u8>().add(header1).add(5);
-            let ptr2 = framebuffer.memory.as_ptr::<u8>().add(header2).add(5);
-
-            let frame_counters = [ptr0, ptr1, ptr2];
-
-            Self { frame_counters }
+        let frame_counters = FrameCounterPtrs::new(framebuffer);
+        let last = frame_counters.read();
+        Self {
+            frame_counters,
+            last,
         }
     }
+
+    /// Forget the frames written since the last one returned, e.g. after
+    /// sleeping. The next call waits for a new frame instead of returning
+    /// right away, and does not report the skipped frames as missed.
+    pub fn resync(&mut self) {
+        self.last = self.frame_counters.read();
+    }
 }
 
 impl Iterator for FrameIter {
-    type Item = ();
+    type Item = FrameWait;
 
     fn next(&mut self) -> Option<Self::Item> {
-        unsafe {
-            let last: u8 = self.frame_counters.iter().map(|f| f.read_volatile()).sum();
-
-            loop {
-                let current: u8 = self.frame_counters.iter().map(|f| f.read_volatile()).sum();
-                if current != last {
-                    break;
-                }
-            }
-
-            Some(())
-        }
+        let wait = self.frame_counters.wait(self.last, None)?;
+        self.last = wait.counters;
+        Some(wait)
     }
 }
 
@@ -208,26 +355,38 @@ pub struct FpgaFramebuffer<M: MemoryMapper> {
 
 impl Default for FpgaFramebuffer<DevMemMemoryMapper> {
     fn default() -> Self {
+        Self::create().expect("Could not mmap framebuffer.")
+    }
+}
+
+impl<M: MemoryMapper> FpgaFramebuffer<M> {
+    fn new(memory: M) -> Result<Self, &'static str> {
+        Ok(Self { memory, ty_: None })
+    }
+
+    /// Map the framebuffer memory.
+    pub fn create() -> Result<Self, &'static str> {
         // In MiSTer there is an alignment of the address to the page size.
         // We know the page size in advance, so we don't need to calculate
         // it.
         let address = FB_BASE_ADDRESS;
         let size = BUFFER_SIZE;
-        let mapper =
-            DevMemMemoryMapper::create(address, size).expect("Could not mmap framebuffer.");
+        let mapper = M::create(address, size)?;
 
-        Self::new(mapper).unwrap()
+        Self::new(mapper)
     }
-}
 
-impl<M: MemoryMapper> FpgaFramebuffer<M> {
-    fn new(memory: M) -> Result<Self, &'static str> {
-        Ok(Self { memory, ty_: None })
+    /// Find whether the core is triple buffered, and where its buffers are.
+    /// This needs to be called once the core is running, before using
+    /// [`FrameIter`] or [`Self::take_screenshot_of`].
+    pub fn update_type_from_core(&mut self) {
+        self.ty_ = self.detect_type();
     }
 
-    pub(crate) fn update_type_from_core(&mut self) {
+    /// Find the type of framebuffer from the headers in memory.
+    fn detect_type(&self) -> Option<FramebufferType> {
         let first = unsafe { self.header_offset(0) };
-        self.ty_ = if !first
+        if !first
             .map(|h| h.attributes().triple_buffered())
             .unwrap_or_default()
         {
@@ -262,6 +421,18 @@ impl<M: MemoryMapper> FpgaFramebuffer<M> {
             .and_then(|offset| unsafe { self.header_offset(offset) })
     }
 
+    /// Read the current frame counters of all buffers.
+    pub fn frame_counters(&self) -> FrameCounters {
+        FrameCounterPtrs::new(self).read()
+    }
+
+    /// Block until a frame is written after `since` was read, or until the
+    /// timeout expires (in which case `None` is returned). The thread sleeps
+    /// between reads of the counters instead of spinning.
+    pub fn wait_frame(&self, since: FrameCounters, timeout: Option<Duration>) -> Option<FrameWait> {
+        FrameCounterPtrs::new(self).wait(since, timeout)
+    }
+
     fn first_header(&self) -> FbHeader {
         unsafe { self.header_offset(0).unwrap() }
     }
@@ -275,41 +446,195 @@ impl<M: MemoryMapper> FpgaFramebuffer<M> {
         Ok(())
     }
 
+    /// The index of the buffer holding the last completed frame, for a
+    /// framebuffer of type `ty`. This does not wait for a frame; when the
+    /// newest buffer cannot be told from the counters, the first buffer is
+    /// used.
+    fn last_frame_index(&self, ty: FramebufferType) -> u8 {
+        if let FramebufferType::Single = ty {
+            return 0;
+        }
+
+        FrameCounterPtrs::with_type(self, Some(ty))
+            .read()
+            .newest()
+            .unwrap_or(0)
+    }
+
+    /// Take a screenshot of the last completed frame, in RGB888.
     pub fn take_screenshot(&self) -> Result<DynamicImage, String> {
-        // Bytes are in big endian, but ARM is in little endian.
-        let header = self.first_header();
+        let ty = self
+            .ty_
+            .or_else(|| self.detect_type())
+            .unwrap_or(FramebufferType::Single);
+        let index = self.last_frame_index(ty);
+        self.read_buffer(ty, index)
+    }
+
+    /// Take a screenshot of the frame in buffer `index` (e.g. the buffer
+    /// that changed after waiting for a frame), in RGB888.
+    pub fn take_screenshot_of(&self, index: u8) -> Result<DynamicImage, String> {
+        self.read_buffer(self.ty_.unwrap_or(FramebufferType::Single), index)
+    }
+
+    fn read_buffer(&self, ty: FramebufferType, index: u8) -> Result<DynamicImage, String> {
+        let offset = ty.offset_of(index).ok_or("Invalid framebuffer index.")?;
+        let header = unsafe { self.header_offset(offset) }.ok_or("No framebuffer header.")?;
 
-        debug!("Header data: {:?}", header);
+        debug!(index, "Header data: {:?}", header);
 
+        let format = header.scaler_pixel_format();
+        let bpp = format
+            .bytes_per_pixel()
+            .ok_or("Invalid Scaler PixelFormat.")?;
         let height = header.height() as usize;
         let width = header.width() as usize;
         let line = header.line() as usize;
-        let start = self.memory.as_ptr::<u8>();
+        if width == 0 || height == 0 {
+            return Err("Empty framebuffer.".to_string());
+        }
+        if line < width * bpp {
+            return Err("Invalid framebuffer line length.".to_string());
+        }
+
+        let start = offset + header.header_len() as usize;
+        if start + line * height > self.memory.len() {
+            return Err("Framebuffer too large.".to_string());
+        }
         let fb = unsafe {
-            std::slice::from_raw_parts(start.add(header.header_len() as usize), line * height * 3)
+            std::slice::from_raw_parts(self.memory.as_ptr::<u8>().add(start), line * height)
         };
 
-        // TODO: add support for RGBA and RGB565.
-        let mut img = match header.scaler_pixel_format() {
-            ScalerPixelFormat::RGB16 => {
-                return Err("RGB565 not supported.".to_string());
+        let mut data = Vec::with_capacity(width * height * 3);
+        for row in fb.chunks_exact(line) {
+            for pixel in row[..width * bpp].chunks_exact(bpp) {
+                data.extend_from_slice(&format.to_rgb(pixel));
             }
-            ScalerPixelFormat::RGB24 => RgbImage::new(width as u32, height as u32),
-            ScalerPixelFormat::RGBA32 => {
-                return Err("RGBA32 not supported.".to_string());
-            }
-            ScalerPixelFormat::INVALID => {
-                return Err("Invalid Scaler PixelFormat.".to_string());
-            }
-        };
-
-        for y in 0..height {
-            let line = &fb[y * line..y * line + width * 3];
-            img.get_mut(y * width * 3..y * width * 3 + width * 3)
-                .unwrap()
-                .copy_from_slice(line);
         }
 
+        let img = RgbImage::from_raw(width as u32, height as u32, data)
+            .ok_or("Could not create the image.")?;
         Ok(DynamicImage::ImageRgb8(img))
     }
 }
+
+/// Scale an image horizontally so it has the given display aspect ratio,
+/// keeping its height. Returns the image unchanged if the aspect ratio is
+/// invalid.
+pub fn scale_to_aspect_ratio(image: DynamicImage, aspect_ratio: AspectRatio) -> DynamicImage {
+    if aspect_ratio.horizontal == 0 || aspect_ratio.vertical == 0 {
+        return image;
+    }
+
+    let height = image.height();
+    let width = (height as u64 * aspect_ratio.horizontal as u64 / aspect_ratio.vertical as u64)
+        .clamp(1, u32::MAX as u64) as u32;
+    if width == image.width() {
+        return image;
+    }
+    image.resize_exact(width, height, FilterType::Triangle)
+}
+
+#[cfg(test)]
+fn test_framebuffer(
+    format: ScalerPixelFormat,
+    width: u16,
+    height: u16,
+    line: u16,
+    pixels: &[u8],
+) -> FpgaFramebuffer<cyclone_v::memory::BufferMemoryMapper> {
+    let mut memory = cyclone_v::memory::BufferMemoryMapper::new(4096);
+    let header_len = 16u16;
+    let mut header = vec![SCALER_FB_TYPE, format as u8];
+    for word in [header_len, 0, width, height, line, width, height] {
+        header.extend_from_slice(&word.to_be_bytes());
+    }
+    memory.as_mut_range(0..header.len()).copy_from_slice(&header);
+    let start = header_len as usize;
+    memory
+        .as_mut_range(start..start + pixels.len())
+        .copy_from_slice(pixels);
+
+    FpgaFramebuffer::new(memory).unwrap()
+}
+
+#[test]
+fn screenshot_formats() {
+    // Red and blue pixels, with padding at the end of the lines.
+    let rgb565 = [0x00, 0xF8, 0x1F, 0x00, 0xFF, 0xFF];
+    let fb = test_framebuffer(ScalerPixelFormat::RGB16, 2, 2, 6, &[rgb565, rgb565].concat());
+    let img = fb.take_screenshot().unwrap().to_rgb8();
+    assert_eq!(img.dimensions(), (2, 2));
+    assert_eq!(img.get_pixel(0, 1).0, [255, 0, 0]);
+    assert_eq!(img.get_pixel(1, 1).0, [0, 0, 255]);
+
+    let rgba = [1, 2, 3, 255, 4, 5, 6, 255];
+    let fb = test_framebuffer(ScalerPixelFormat::RGBA32, 2, 1, 8, &rgba);
+    let img = fb.take_screenshot().unwrap().to_rgb8();
+    assert_eq!(img.into_raw(), vec![1, 2, 3, 4, 5, 6]);
+
+    let fb = test_framebuffer(ScalerPixelFormat::RGB24, 1, 1, 3, &[7, 8, 9]);
+    let img = fb.take_screenshot().unwrap().to_rgb8();
+    assert_eq!(img.into_raw(), vec![7, 8, 9]);
+
+    let fb = test_framebuffer(ScalerPixelFormat::INVALID, 1, 1, 3, &[0, 0, 0]);
+    assert!(fb.take_screenshot().is_err());
+}
+
+#[test]
+fn newest_frame_counter() {
+    let frames = |a: u8, b: u8, c: u8| FrameCounters([a << 5, b << 5, c << 5]);
+
+    assert_eq!(frames(3, 4, 2).newest(), Some(1));
+    assert_eq!(frames(3, 1, 2).newest(), Some(0));
+    // Frame numbers wrap around after 7.
+    assert_eq!(frames(6, 7, 0).newest(), Some(2));
+    // Other attribute bits are ignored.
+    assert_eq!(FrameCounters([0x10, 0x30, 0x50]).newest(), Some(2));
+    // No frame written yet.
+    assert_eq!(frames(0, 0, 0).newest(), None);
+}
+
+#[test]
+fn frame_iter_resync_after_sleep() {
+    use cyclone_v::memory::SimulatedMemoryMapper;
+
+    let mut fb = FpgaFramebuffer::<SimulatedMemoryMapper>::create().unwrap();
+    fb.update_type_from_core();
+
+    // Write a frame the way the scaler does, by changing its counter.
+    let counter = fb.memory.as_mut_ptr::<u8>() as usize + 5;
+    let new_frame = move || unsafe {
+        let ptr = counter as *mut u8;
+        ptr.write_volatile(ptr.read_volatile().wrapping_add(1));
+    };
+
+    let mut frames = FrameIter::new(&fb);
+
+    // Frames written while sleeping are skipped.
+    new_frame();
+    std::thread::sleep(Duration::from_millis(10));
+    new_frame();
+    frames.resync();
+
+    let scaler = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(20));
+        new_frame();
+    });
+    let start = Instant::now();
+    let wait = frames.next().unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(20));
+    assert_eq!(wait.missed, 0);
+    scaler.join().unwrap();
+}
+
+#[test]
+fn screenshot_aspect_ratio() {
+    let img = DynamicImage::ImageRgb8(RgbImage::new(256, 240));
+    let scaled = scale_to_aspect_ratio(img, AspectRatio::new(4, 3));
+    assert_eq!((scaled.width(), scaled.height()), (320, 240));
+
+    let img = DynamicImage::ImageRgb8(RgbImage::new(256, 240));
+    let scaled = scale_to_aspect_ratio(img, AspectRatio::zero());
+    assert_eq!((scaled.width(), scaled.height()), (256, 240));
+}
@@ -5,6 +5,7 @@ pub mod core_info;
 pub mod fpga;
 pub mod framebuffer;
 pub mod keyboard;
+pub mod mra;
 pub mod osd;
 pub mod savestate;
 pub mod types;
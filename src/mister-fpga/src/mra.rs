@@ -0,0 +1,687 @@
+//! Parses MiSTer Arcade ROM (`.mra`) files. An MRA file is an XML file that
+//! names the core (RBF) to load for an arcade game, how to assemble its ROM
+//! from MAME zip files, its DIP switches and its buttons.
+//! See this documentation for more information:
+//! https://github.com/MiSTer-devel/MRA-Tools_MiSTer/blob/master/README.md
+use std::io::ErrorKind;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use tracing::warn;
+
+use one_fpga::archive;
+
+/// Parse a number, decimal or hexadecimal with a `0x` prefix.
+fn parse_number(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => value.parse(),
+    }
+    .map_err(|e| format!("Invalid number '{value}': {e}"))
+}
+
+/// Parse bytes written in hexadecimal, e.g. `00 FF 12`.
+fn parse_hex_bytes(value: &str) -> Result<Vec<u8>, String> {
+    let hex = value
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>();
+    hex::decode(&hex).map_err(|e| format!("Invalid data '{hex}': {e}"))
+}
+
+fn split_list(value: &str, separator: char) -> Vec<String> {
+    value
+        .split(separator)
+        .map(|s| s.trim().to_string())
+        .collect()
+}
+
+fn number_attribute(node: roxmltree::Node, name: &str) -> Result<Option<u64>, String> {
+    node.attribute(name).map(parse_number).transpose()
+}
+
+/// A DIP switch, which sets a range of bits to one of its values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dip {
+    pub name: String,
+
+    /// The bits of this switch in the 64 bits of DIP switches.
+    pub bits: Range<u8>,
+
+    /// The name of each choice.
+    pub choices: Vec<String>,
+
+    /// The value of each choice. Defaults to the index of the choice.
+    pub values: Vec<u64>,
+}
+
+impl Dip {
+    fn from_node(node: roxmltree::Node) -> Result<Self, String> {
+        let name = node.attribute("name").unwrap_or_default().to_string();
+        let bits = node
+            .attribute("bits")
+            .ok_or_else(|| format!("No bits for DIP switch '{name}'"))?
+            .split(',')
+            .map(parse_number)
+            .collect::<Result<Vec<_>, _>>()?;
+        let bits = match bits.as_slice() {
+            [bit] if *bit < 64 => (*bit as u8)..(*bit as u8 + 1),
+            // Bit ranges are inclusive.
+            [start, end] if start <= end && *end < 64 => (*start as u8)..(*end as u8 + 1),
+            _ => return Err(format!("Invalid bits for DIP switch '{name}'")),
+        };
+
+        let choices = split_list(node.attribute("ids").unwrap_or_default(), ',');
+        let values = match node.attribute("values") {
+            Some(values) => values
+                .split(',')
+                .map(parse_number)
+                .collect::<Result<Vec<_>, _>>()?,
+            None => (0..choices.len() as u64).collect(),
+        };
+        if values.len() != choices.len() {
+            return Err(format!("Invalid values for DIP switch '{name}'"));
+        }
+
+        Ok(Self {
+            name,
+            bits,
+            choices,
+            values,
+        })
+    }
+
+    fn mask(&self) -> u64 {
+        let len = self.bits.len() as u32;
+        u64::MAX.checked_shr(64 - len).unwrap_or(0) << self.bits.start
+    }
+}
+
+/// The DIP switches of a game (the `<switches>` element).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Switches {
+    pub dips: Vec<Dip>,
+
+    /// The default value of all the switches.
+    pub default: u64,
+}
+
+impl Switches {
+    fn from_node(node: roxmltree::Node) -> Result<Self, String> {
+        // The default is a list of bytes, the first one being the lowest.
+        let mut default = 0;
+        if let Some(bytes) = node.attribute("default") {
+            for (i, byte) in bytes
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|b| !b.is_empty())
+                .take(8)
+                .enumerate()
+            {
+                let byte = u8::from_str_radix(byte, 16)
+                    .map_err(|e| format!("Invalid DIP switch default '{byte}': {e}"))?;
+                default |= (byte as u64) << (i * 8);
+            }
+        }
+
+        let dips = node
+            .children()
+            .filter(|n| n.has_tag_name("dip"))
+            .map(Dip::from_node)
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { dips, default })
+    }
+}
+
+/// The current state of the DIP switches of a game.
+#[derive(Debug, Clone)]
+pub struct DipSwitches {
+    name: String,
+    switches: Switches,
+    value: u64,
+}
+
+impl DipSwitches {
+    /// Create the DIP switches of a game, set to their defaults. The name
+    /// is used to save them.
+    pub fn new(name: impl Into<String>, switches: Switches) -> Self {
+        Self {
+            name: name.into(),
+            value: switches.default,
+            switches,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn dips(&self) -> &[Dip] {
+        &self.switches.dips
+    }
+
+    /// The value of all the switches, as sent to the core.
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    pub fn set_value(&mut self, value: u64) {
+        self.value = value;
+    }
+
+    pub fn reset(&mut self) {
+        self.value = self.switches.default;
+    }
+
+    /// The index of the current choice of a switch, if its bits match one
+    /// of its values.
+    pub fn choice(&self, index: usize) -> Option<usize> {
+        let dip = self.switches.dips.get(index)?;
+        let value = (self.value & dip.mask()) >> dip.bits.start;
+        dip.values.iter().position(|v| *v == value)
+    }
+
+    /// Select the next choice of a switch, going back to the first one
+    /// after the last.
+    pub fn next_choice(&mut self, index: usize) {
+        let Some(dip) = self.switches.dips.get(index) else {
+            return;
+        };
+        if dip.values.is_empty() {
+            return;
+        }
+
+        let next = self.choice(index).map_or(0, |c| (c + 1) % dip.values.len());
+        let mask = dip.mask();
+        self.value = (self.value & !mask) | ((dip.values[next] << dip.bits.start) & mask);
+    }
+}
+
+/// The buttons of a game (the `<buttons>` element).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Buttons {
+    /// The name of each button of the core.
+    pub names: Vec<String>,
+
+    /// The default mapping of each button, as SNES button names.
+    pub default: Vec<String>,
+}
+
+impl Buttons {
+    fn from_node(node: roxmltree::Node) -> Self {
+        Self {
+            names: split_list(node.attribute("names").unwrap_or_default(), ','),
+            default: split_list(node.attribute("default").unwrap_or_default(), ','),
+        }
+    }
+}
+
+/// A part of a ROM, which is either a file from a zip or inline data.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Part {
+    name: Option<String>,
+    zip: Option<String>,
+    crc: Option<u32>,
+    offset: usize,
+    length: Option<usize>,
+    repeat: usize,
+    data: Vec<u8>,
+}
+
+impl Part {
+    fn from_node(node: roxmltree::Node) -> Result<Self, String> {
+        let data = match node.attribute("name") {
+            Some(_) => Vec::new(),
+            None => parse_hex_bytes(node.text().unwrap_or_default())?,
+        };
+
+        Ok(Self {
+            name: node.attribute("name").map(str::to_string),
+            zip: node.attribute("zip").map(str::to_string),
+            crc: node
+                .attribute("crc")
+                .map(|crc| u32::from_str_radix(crc.trim(), 16))
+                .transpose()
+                .map_err(|e| format!("Invalid CRC: {e}"))?,
+            offset: number_attribute(node, "offset")?.unwrap_or(0) as usize,
+            length: number_attribute(node, "length")?.map(|l| l as usize),
+            repeat: number_attribute(node, "repeat")?.unwrap_or(1) as usize,
+            data,
+        })
+    }
+
+    fn read(&self, zips: &[String], dirs: &[&Path]) -> Result<Vec<u8>, String> {
+        let Some(name) = &self.name else {
+            return Ok(self.data.repeat(self.repeat));
+        };
+
+        let own_zips;
+        let zips = match &self.zip {
+            Some(zip) => {
+                own_zips = split_list(zip, '|');
+                &own_zips
+            }
+            None => zips,
+        };
+        let data = find_in_zips(name, zips, dirs)?;
+        if let Some(crc) = self.crc {
+            let actual = crc32fast::hash(&data);
+            if actual != crc {
+                warn!(%name, "CRC mismatch: expected {crc:08X}, got {actual:08X}");
+            }
+        }
+
+        let start = self.offset.min(data.len());
+        let end = self
+            .length
+            .map_or(data.len(), |length| (start + length).min(data.len()));
+        Ok(data[start..end].repeat(self.repeat))
+    }
+}
+
+/// Find a file in the first zip (from a list of directories) that has it.
+fn find_in_zips(name: &str, zips: &[String], dirs: &[&Path]) -> Result<Vec<u8>, String> {
+    for zip in zips {
+        for dir in dirs {
+            let path = dir.join(zip);
+            if !path.is_file() {
+                continue;
+            }
+            match archive::read(&path, name) {
+                Ok(data) => return Ok(data),
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => return Err(format!("Could not read {}: {e}", path.display())),
+            }
+        }
+    }
+    Err(format!("Could not find {name} in {}", zips.join(", ")))
+}
+
+/// An element of a ROM, assembled in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RomItem {
+    Part(Part),
+
+    /// Parts whose bytes are interleaved in words of `width` bytes. Each
+    /// part has a map of the bytes it fills in each word (see
+    /// [`parse_map`]).
+    Interleave {
+        width: usize,
+        parts: Vec<(Part, Vec<Option<usize>>)>,
+    },
+}
+
+/// Parse the `map` of a part in an interleave. It has one digit per byte of
+/// the output word, the rightmost being the first byte. A digit is the
+/// 1-based index of the byte of the part that goes there, or 0 if the part
+/// does not fill it. Without a map, a part fills the whole word in order.
+fn parse_map(map: Option<&str>, width: usize) -> Result<Vec<Option<usize>>, String> {
+    let Some(map) = map else {
+        return Ok((0..width).map(Some).collect());
+    };
+    if map.len() != width {
+        return Err(format!("Invalid interleave map '{map}'"));
+    }
+
+    map.chars()
+        .rev()
+        .map(|c| match c.to_digit(10) {
+            Some(0) => Ok(None),
+            Some(d) => Ok(Some(d as usize - 1)),
+            None => Err(format!("Invalid interleave map '{map}'")),
+        })
+        .collect()
+}
+
+/// Interleave the data of parts, see [`RomItem::Interleave`].
+fn interleave(width: usize, parts: &[(Vec<u8>, Vec<Option<usize>>)]) -> Vec<u8> {
+    let units = parts
+        .iter()
+        .map(|(data, map)| {
+            let unit = map.iter().flatten().max().map_or(0, |i| i + 1);
+            (unit, data.len().checked_div(unit).unwrap_or(0))
+        })
+        .collect::<Vec<_>>();
+    let count = units.iter().map(|(_, count)| *count).min().unwrap_or(0);
+
+    let mut result = vec![0; width * count];
+    for (word, output) in result.chunks_exact_mut(width).enumerate() {
+        for ((data, map), (unit, _)) in parts.iter().zip(units.iter()) {
+            for (byte, index) in output.iter_mut().zip(map.iter()) {
+                if let Some(index) = index {
+                    *byte = data[word * unit + index];
+                }
+            }
+        }
+    }
+    result
+}
+
+/// A patch applied to a ROM once it is assembled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Patch {
+    offset: usize,
+    data: Vec<u8>,
+}
+
+/// A ROM of a game (the `<rom>` element), sent to the core on its index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rom {
+    pub index: u8,
+
+    /// The zip files the parts are read from, by default.
+    pub zips: Vec<String>,
+
+    items: Vec<RomItem>,
+    patches: Vec<Patch>,
+}
+
+impl Rom {
+    fn from_node(node: roxmltree::Node) -> Result<Self, String> {
+        let mut items = Vec::new();
+        let mut patches = Vec::new();
+
+        for child in node.children().filter(|n| n.is_element()) {
+            match child.tag_name().name() {
+                "part" => items.push(RomItem::Part(Part::from_node(child)?)),
+                "interleave" => {
+                    let output = number_attribute(child, "output")?.unwrap_or(8) as usize;
+                    if output == 0 || !output.is_multiple_of(8) {
+                        return Err(format!("Invalid interleave output {output}"));
+                    }
+                    let width = output / 8;
+                    let parts = child
+                        .children()
+                        .filter(|n| n.has_tag_name("part"))
+                        .map(|part| {
+                            Ok((
+                                Part::from_node(part)?,
+                                parse_map(part.attribute("map"), width)?,
+                            ))
+                        })
+                        .collect::<Result<_, String>>()?;
+                    items.push(RomItem::Interleave { width, parts });
+                }
+                "patch" => patches.push(Patch {
+                    offset: number_attribute(child, "offset")?.unwrap_or(0) as usize,
+                    data: parse_hex_bytes(child.text().unwrap_or_default())?,
+                }),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            index: number_attribute(node, "index")?.unwrap_or(0) as u8,
+            zips: node
+                .attribute("zip")
+                .map(|zip| split_list(zip, '|'))
+                .unwrap_or_default(),
+            items,
+            patches,
+        })
+    }
+
+    /// Assemble the ROM, reading the zip files from the first directory
+    /// that has them.
+    pub fn build(&self, dirs: &[&Path]) -> Result<Vec<u8>, String> {
+        let mut result = Vec::new();
+        for item in &self.items {
+            match item {
+                RomItem::Part(part) => result.extend(part.read(&self.zips, dirs)?),
+                RomItem::Interleave { width, parts } => {
+                    let parts = parts
+                        .iter()
+                        .map(|(part, map)| Ok((part.read(&self.zips, dirs)?, map.clone())))
+                        .collect::<Result<Vec<_>, String>>()?;
+                    result.extend(interleave(*width, &parts));
+                }
+            }
+        }
+
+        for patch in &self.patches {
+            let end = patch
+                .offset
+                .checked_add(patch.data.len())
+                .filter(|end| *end <= result.len())
+                .ok_or_else(|| format!("Patch at {:#x} is out of the ROM", patch.offset))?;
+            result[patch.offset..end].copy_from_slice(&patch.data);
+        }
+        Ok(result)
+    }
+}
+
+/// A MiSTer Arcade ROM file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mra {
+    /// The name of the game.
+    pub name: String,
+
+    /// The MAME set name of the game.
+    pub set_name: Option<String>,
+
+    /// The name of the core, without its date suffix and extension.
+    pub rbf: String,
+
+    pub switches: Option<Switches>,
+    pub buttons: Option<Buttons>,
+    pub roms: Vec<Rom>,
+}
+
+impl Mra {
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, String> {
+        std::fs::read_to_string(path.as_ref())
+            .map_err(|e| e.to_string())?
+            .parse()
+    }
+
+    /// A name that identifies the game, e.g. to save its DIP switches.
+    pub fn key(&self) -> &str {
+        self.set_name.as_deref().unwrap_or(&self.name)
+    }
+
+    /// Find the core of the game in a list of directories. Cores can have a
+    /// date suffix (e.g. `pacman_20230101.rbf`), in which case the latest
+    /// one is used.
+    pub fn find_rbf(&self, dirs: &[&Path]) -> Option<PathBuf> {
+        let rbf = self.rbf.to_lowercase();
+        let prefix = format!("{rbf}_");
+
+        dirs.iter().find_map(|dir| {
+            std::fs::read_dir(dir)
+                .ok()?
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| {
+                    let is_rbf = path
+                        .extension()
+                        .is_some_and(|ext| ext.eq_ignore_ascii_case("rbf"));
+                    let stem = path
+                        .file_stem()
+                        .map(|s| s.to_string_lossy().to_lowercase())
+                        .unwrap_or_default();
+                    is_rbf && (stem == rbf || stem.starts_with(&prefix))
+                })
+                .max_by_key(|path| path.file_name().map(|n| n.to_string_lossy().to_lowercase()))
+        })
+    }
+}
+
+impl FromStr for Mra {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let document = roxmltree::Document::parse(s).map_err(|e| e.to_string())?;
+        let root = document.root_element();
+        if !root.has_tag_name("misterromdescription") {
+            return Err("Not an MRA file".to_string());
+        }
+
+        let text = |name: &str| {
+            root.children()
+                .find(|n| n.has_tag_name(name))
+                .and_then(|n| n.text())
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+        };
+
+        Ok(Self {
+            name: text("name").unwrap_or_default(),
+            set_name: text("setname"),
+            rbf: text("rbf").ok_or("No core (rbf) in MRA file")?,
+            switches: root
+                .children()
+                .find(|n| n.has_tag_name("switches"))
+                .map(Switches::from_node)
+                .transpose()?,
+            buttons: root
+                .children()
+                .find(|n| n.has_tag_name("buttons"))
+                .map(Buttons::from_node),
+            roms: root
+                .children()
+                .filter(|n| n.has_tag_name("rom"))
+                .map(Rom::from_node)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+const TEST_MRA: &str = r#"<misterromdescription>
+    <name>Test Game (World)</name>
+    <setname>testgame</setname>
+    <rbf>testcore</rbf>
+    <switches default="0A,FF">
+        <dip bits="0,1" name="Lives" ids="1,2,3" values="3,0,1"/>
+        <dip bits="3" name="Demo Sounds" ids="Off,On"/>
+    </switches>
+    <buttons names="Fire,Jump,Start,Coin" default="A,B,Start,Select"/>
+    <rom index="0" zip="testgame.zip|parent.zip">
+        <part name="main.bin" crc="00000000"/>
+        <interleave output="16">
+            <part name="even.bin" map="01"/>
+            <part name="odd.bin" map="10"/>
+        </interleave>
+        <part name="main.bin" offset="1" length="2" repeat="2"/>
+        <part repeat="3">FF</part>
+        <patch offset="1">AB CD</patch>
+    </rom>
+    <rom index="1"><part>11 22</part></rom>
+</misterromdescription>"#;
+
+#[test]
+fn mra_parse() {
+    let mra = Mra::from_str(TEST_MRA).unwrap();
+    assert_eq!(mra.name, "Test Game (World)");
+    assert_eq!(mra.key(), "testgame");
+    assert_eq!(mra.rbf, "testcore");
+    assert_eq!(mra.roms.len(), 2);
+    assert_eq!(mra.roms[0].zips, ["testgame.zip", "parent.zip"]);
+    assert_eq!(mra.roms[1].index, 1);
+    assert_eq!(mra.roms[1].build(&[]).unwrap(), [0x11, 0x22]);
+
+    // Patches past the end of the ROM (even overflowing) are errors.
+    let patched = Mra::from_str(
+        r#"<misterromdescription><rbf>core</rbf><rom index="0"><part>00</part>
+        <patch offset="0xFFFFFFFFFFFFFFFF">01 02</patch></rom></misterromdescription>"#,
+    )
+    .unwrap();
+    assert!(patched.roms[0].build(&[]).is_err());
+
+    let buttons = mra.buttons.unwrap();
+    assert_eq!(buttons.names, ["Fire", "Jump", "Start", "Coin"]);
+    assert_eq!(buttons.default, ["A", "B", "Start", "Select"]);
+
+    let switches = mra.switches.unwrap();
+    assert_eq!(switches.default, 0xFF0A);
+    assert_eq!(switches.dips[0].bits, 0..2);
+    assert_eq!(switches.dips[1].bits, 3..4);
+    assert_eq!(switches.dips[1].values, [0, 1]);
+
+    assert!(Mra::from_str("<misterromdescription/>").is_err());
+    assert!(Mra::from_str("<other><rbf>core</rbf></other>").is_err());
+}
+
+#[test]
+fn mra_dip_switches() {
+    let switches = Mra::from_str(TEST_MRA).unwrap().switches.unwrap();
+    let mut dips = DipSwitches::new("testgame", switches);
+
+    // The default (0x0A) has no lives value (2), and demo sounds on.
+    assert_eq!(dips.choice(0), None);
+    assert_eq!(dips.choice(1), Some(1));
+
+    // Unknown values go to the first choice.
+    dips.next_choice(0);
+    assert_eq!(dips.choice(0), Some(0));
+    assert_eq!(dips.value(), 0xFF0B);
+    dips.next_choice(0);
+    assert_eq!(dips.choice(0), Some(1));
+    assert_eq!(dips.value(), 0xFF08);
+    dips.next_choice(1);
+    assert_eq!(dips.value(), 0xFF00);
+
+    dips.set_value(0xFF01);
+    assert_eq!(dips.choice(0), Some(2));
+
+    dips.reset();
+    assert_eq!(dips.value(), 0xFF0A);
+}
+
+#[test]
+fn mra_build_rom() {
+    use std::fs::File;
+    use std::io::Write;
+
+    let root = tempdir::TempDir::new("mra").unwrap();
+    let mut zip = zip::ZipWriter::new(File::create(root.path().join("parent.zip")).unwrap());
+    let options = zip::write::FileOptions::default();
+    for (name, data) in [
+        ("main.bin", &[1u8, 2, 3, 4][..]),
+        ("even.bin", &[0x10, 0x11]),
+        ("odd.bin", &[0x20, 0x21, 0x22]),
+    ] {
+        zip.start_file(name, options).unwrap();
+        zip.write_all(data).unwrap();
+    }
+    zip.finish().unwrap();
+
+    let mra = Mra::from_str(TEST_MRA).unwrap();
+    let rom = mra.roms[0].build(&[root.path()]).unwrap();
+    assert_eq!(
+        rom,
+        [
+            1, 0xAB, 0xCD, 4, // main.bin, patched.
+            0x10, 0x20, 0x11, 0x21, // Interleaved.
+            2, 3, 2, 3, // Offset, length and repeat.
+            0xFF, 0xFF, 0xFF, // Data.
+        ]
+    );
+
+    let missing = tempdir::TempDir::new("mra").unwrap();
+    assert!(mra.roms[0].build(&[missing.path()]).is_err());
+}
+
+#[test]
+fn mra_find_rbf() {
+    let root = tempdir::TempDir::new("mra").unwrap();
+    for name in [
+        "testcore_20230101.rbf",
+        "TestCore_20240101.rbf",
+        "testcore2_20250101.rbf",
+        "testcore_20250101.txt",
+    ] {
+        std::fs::write(root.path().join(name), b"").unwrap();
+    }
+
+    let mra = Mra::from_str(TEST_MRA).unwrap();
+    assert_eq!(
+        mra.find_rbf(&[Path::new("/nonexistent"), root.path()]),
+        Some(root.path().join("TestCore_20240101.rbf"))
+    );
+}
@@ -5,6 +5,7 @@
 //! method to send the data to the FPGA itself. It does not keep any internal
 //! buffers, and is light weigh.
 use crate::fpga::{osd_io, MisterFpga};
+use cyclone_v::memory::MemoryMapper;
 use embedded_graphics::image::GetPixel;
 use embedded_graphics::pixelcolor::BinaryColor;
 use embedded_graphics::prelude::*;
@@ -51,7 +52,7 @@ impl OsdDisplay {
     /// Send the buffer to the OSD.
     pub fn send<B: GetPixel<Color = BinaryColor> + OriginDimensions>(
         &self,
-        fpga: &mut MisterFpga,
+        fpga: &mut MisterFpga<impl MemoryMapper>,
         buffer: &B,
     ) {
         let size = buffer.size();
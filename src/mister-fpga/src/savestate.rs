@@ -1,12 +1,10 @@
 use crate::config_string::Config;
-use cyclone_v::memory::{DevMemMemoryMapper, MemoryMapper};
+use cyclone_v::memory::MemoryMapper;
 use one_fpga::core::Error;
 use std::io::{Read, Write};
 use std::ptr::NonNull;
 use std::slice;
 
-const DEFAULT_MISTER_SAVESTATE_SLOTS: u32 = 4;
-
 pub struct SaveStateManager<M: MemoryMapper> {
     /// Memory Mapper. The Manager needs to own it to avoid it being dropped
     /// prematurely.
@@ -15,22 +13,24 @@ pub struct SaveStateManager<M: MemoryMapper> {
     /// The number of savestate slots.
     nb_slots: u32,
 
+    /// The size of a slot in memory, in bytes.
+    slot_size: usize,
+
     /// The savestate slots.
     slots: Vec<SaveState>,
 }
 
-impl SaveStateManager<DevMemMemoryMapper> {
+impl<M: MemoryMapper> SaveStateManager<M> {
     pub fn from_config_string(config: &Config) -> Option<Self> {
         let (base, size) = config.settings().save_state?;
-        let nb_slots = DEFAULT_MISTER_SAVESTATE_SLOTS;
+        let nb_slots = config.settings().nb_save_state_slots() as u32;
 
         // The memory setup is:
         //   0x00: u32 change detector.     A value that changes when the savestate changes.
         //   0x04: u32 size                 Size of the savestate, in 32-bits words.
         //   0x08..0x08 + (size * 4)        The savestate data.
 
-        let mut memory =
-            DevMemMemoryMapper::create(base.as_usize(), size * (nb_slots as usize)).unwrap();
+        let mut memory = M::create(base.as_usize(), size * (nb_slots as usize)).unwrap();
 
         let slots = (0..nb_slots)
             .map(|i| {
@@ -41,6 +41,7 @@ impl SaveStateManager<DevMemMemoryMapper> {
 
         Some(Self {
             nb_slots,
+            slot_size: size,
             _memory: memory,
             slots,
         })
@@ -62,6 +63,12 @@ impl<M: MemoryMapper> SaveStateManager<M> {
     pub fn nb_slots(&self) -> usize {
         self.nb_slots as usize
     }
+
+    /// The size of a slot in memory, in bytes.
+    #[inline]
+    pub fn slot_size(&self) -> usize {
+        self.slot_size
+    }
 }
 
 #[repr(C)]
@@ -75,6 +75,35 @@ impl StatusBitMap {
         self.0.as_raw_mut_slice()
     }
 
+    /// The status bits as little endian bytes, like MiSTer `.CFG` files.
+    /// This is 8 bytes, or 16 bytes if there are extra bits set.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let words = if self.has_extra() { 8 } else { 4 };
+        self.as_raw_slice()[..words]
+            .iter()
+            .flat_map(|w| w.to_le_bytes())
+            .collect()
+    }
+
+    /// Read status bits written by [`Self::to_bytes`]. Missing bytes are
+    /// zeroes and extra bytes are ignored.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        let mut result = Self::new();
+        for (word, chunk) in result.as_mut_raw_slice().iter_mut().zip(bytes.chunks(2)) {
+            *word = u16::from_le_bytes([chunk[0], chunk.get(1).copied().unwrap_or(0)]);
+        }
+        result
+    }
+
+    /// Copy the bits set in `mask` from another bit map, keeping the others.
+    pub fn copy_masked(&mut self, from: &StatusBitMap, mask: &StatusBitMap) {
+        let from = from.as_raw_slice();
+        let mask = mask.as_raw_slice();
+        for (i, word) in self.as_mut_raw_slice().iter_mut().enumerate() {
+            *word = (*word & !mask[i]) | (from[i] & mask[i]);
+        }
+    }
+
     pub fn has_extra(&self) -> bool {
         self.0.as_raw_slice()[4..].iter().any(|x| *x != 0)
     }
@@ -195,3 +224,28 @@ fn status_bits() {
     assert_eq!(status_bits.get_range(32..34), 3);
     assert_eq!(status_bits.get_range(64..67), 3);
 }
+
+#[test]
+fn status_bits_bytes() {
+    let mut status_bits = StatusBitMap::new();
+    status_bits.set_range(1..4, 0b101);
+    status_bits.set(63, true);
+
+    let bytes = status_bits.to_bytes();
+    assert_eq!(bytes, [0x0A, 0, 0, 0, 0, 0, 0, 0x80]);
+    assert_eq!(StatusBitMap::from_bytes(&bytes), status_bits);
+
+    status_bits.set(100, true);
+    assert_eq!(status_bits.to_bytes().len(), 16);
+    assert_eq!(
+        StatusBitMap::from_bytes(&status_bits.to_bytes()),
+        status_bits
+    );
+
+    let mut mask = StatusBitMap::new();
+    mask.set_range(0..3, 0b111);
+    let mut copy = StatusBitMap::new();
+    copy.set(5, true);
+    copy.copy_masked(&status_bits, &mask);
+    assert_eq!(copy.get_range(0..8), 0b100010);
+}
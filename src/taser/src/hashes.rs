@@ -0,0 +1,67 @@
+//! Hashes of frames captured during a TAS run. A reference list is recorded
+//! once with `--record-hashes`, then `--verify` compares a new run against
+//! it to detect regressions in cores.
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sha1::Digest;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrameHash {
+    pub frame: usize,
+    pub hash: String,
+}
+
+/// A list of frame hashes, sorted by frame number.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct FrameHashes {
+    /// The TAS file the hashes were recorded with, for information.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tas: Option<String>,
+
+    frames: Vec<FrameHash>,
+}
+
+impl FrameHashes {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        let mut hashes: Self = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid hashes file {}: {}", path.display(), e))?;
+        hashes.frames.sort_by_key(|f| f.frame);
+        Ok(hashes)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, content)
+            .map_err(|e| format!("Could not write {}: {}", path.display(), e))
+    }
+
+    pub fn push(&mut self, frame: usize, hash: String) {
+        self.frames.push(FrameHash { frame, hash });
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Map of frame numbers to their expected hash.
+    pub fn as_map(&self) -> BTreeMap<usize, &str> {
+        self.frames
+            .iter()
+            .map(|f| (f.frame, f.hash.as_str()))
+            .collect()
+    }
+}
+
+/// Hash the content of a frame. The dimensions are part of the hash so that
+/// a resolution change is always detected.
+pub fn hash_frame(width: u32, height: u32, data: &[u8]) -> String {
+    let mut hasher = sha1::Sha1::new();
+    hasher.update(width.to_le_bytes());
+    hasher.update(height.to_le_bytes());
+    hasher.update(data);
+    crate::hex(&hasher.finalize())
+}
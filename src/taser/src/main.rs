@@ -1,18 +1,26 @@
-use std::io::{BufReader, Read};
+use std::collections::HashMap;
+use std::io::{BufReader, Read, Seek};
 use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use clap::Parser;
 use clap_verbosity_flag::Level as VerbosityLevel;
 use clap_verbosity_flag::Verbosity;
-use tracing::{debug, error, info, trace, Level};
+use sha1::Digest;
+use tracing::{debug, error, info, trace, warn, Level};
 use tracing_subscriber::fmt::Subscriber;
 
-use fce_movie_format::{FceInputButton, FceInputGamepad};
 use mister_fpga::config::Config;
-use mister_fpga::core::buttons::{ButtonMap, MisterFpgaButtons};
+use mister_fpga::config_string;
+use mister_fpga::core::buttons::ButtonMap;
 use mister_fpga::core::MisterFpgaCore;
 use mister_fpga::fpga::user_io::UserIoButtonSwitch;
+use one_fpga::inputs::gamepad::ButtonSet;
+use one_fpga::movie::{Movie, MovieCommand, RomChecksum};
+
+use crate::hashes::{hash_frame, FrameHashes};
+
+mod hashes;
 
 /// `taser` is a simple command-line interface to the GoLEm Mister core
 /// library. It is intended to be used as a standalone application, or as a
@@ -35,6 +43,26 @@ struct Flags {
     #[clap(long)]
     skip_tas_check: bool,
 
+    /// Verify the frames of the TAS run against a list of hashes recorded
+    /// with `--record-hashes`. Exits with an error on the first frame that
+    /// differs.
+    #[clap(long, requires = "tas", conflicts_with = "record_hashes")]
+    verify: Option<PathBuf>,
+
+    /// Record the hashes of frames of the TAS run to a file, to be used
+    /// with `--verify`.
+    #[clap(long, requires = "tas")]
+    record_hashes: Option<PathBuf>,
+
+    /// Comma separated list of frames to hash when recording.
+    #[clap(long, value_delimiter = ',', conflicts_with = "hash_every")]
+    hash_frames: Vec<usize>,
+
+    /// Hash a frame every N frames when recording. Defaults to every 60
+    /// frames if `--hash-frames` is not specified.
+    #[clap(long)]
+    hash_every: Option<usize>,
+
     /// Set the volume of the core before start (from 0 to 255). Default is muted.
     #[clap(long, default_value = "0")]
     volume: u8,
@@ -75,6 +103,38 @@ fn main() {
         .init();
 
     debug!(?opts);
+
+    let movie = opts.tas.as_ref().map(|tas| {
+        let movie = load_movie(tas).expect("Could not read TAS file.");
+        info!(metadata = ?movie.metadata(), "TAS file loaded");
+        movie
+    });
+    if let Some(movie) = &movie {
+        if let Err(e) = verify_rom_checksum(movie.as_ref(), &opts.rom) {
+            if opts.skip_tas_check {
+                warn!("{}", e);
+                warn!("Playing the TAS anyway (--skip-tas-check).");
+            } else {
+                error!("{}", e);
+                error!("Use --skip-tas-check to play the TAS anyway.");
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let expected_hashes = opts.verify.as_ref().map(|path| {
+        let hashes = FrameHashes::load(path).unwrap_or_else(|e| {
+            error!("{}", e);
+            std::process::exit(1);
+        });
+        info!(
+            frames = hashes.len(),
+            "Verifying against {}",
+            path.display()
+        );
+        hashes
+    });
+
     let mut fpga = mister_fpga::fpga::MisterFpga::init().unwrap();
 
     let mut bytes = Vec::new();
@@ -107,12 +167,31 @@ fn main() {
     let video_info = core.video_info().unwrap();
     info!(?video_info, "Video initialized");
 
-    if let Some(tas) = opts.tas {
+    if let Some(movie) = movie {
         // Showtime!
         core.soft_reset();
 
         let port0 = *core.gamepad(0).unwrap();
-        let frames = read_frames(&tas, port0).expect("Could not read TAS file.");
+        let frames = read_frames(movie.as_ref(), port0);
+        let actions = command_actions(core.config(), &frames).unwrap_or_else(|e| {
+            error!("{}", e);
+            std::process::exit(1);
+        });
+        debug!(?actions, "TAS commands mapping");
+
+        let expected = expected_hashes.as_ref().map(|h| h.as_map());
+        let hash_every = opts.hash_every.unwrap_or(60).max(1);
+        let mut recorded = opts.record_hashes.as_ref().map(|_| FrameHashes {
+            tas: opts.tas.as_ref().map(|p| p.display().to_string()),
+            ..Default::default()
+        });
+        let recording = recorded.is_some();
+        let should_hash = |frame: usize| match &expected {
+            Some(expected) => expected.contains_key(&frame),
+            None if !opts.hash_frames.is_empty() => opts.hash_frames.contains(&frame),
+            None => recording && frame % hash_every == 0,
+        };
+        let mut verified = 0;
 
         let trace_is_enabled = tracing::enabled!(Level::TRACE);
 
@@ -140,18 +219,21 @@ fn main() {
             let _ = frame_it.next();
         }
 
-        let mut next = std::time::Instant::now();
-        next += wait_start;
-        while std::time::Instant::now() < next {}
+        std::thread::sleep(wait_start);
+        frame_it.resync();
 
         let start = std::time::Instant::now();
         let mut last = start;
+        let mut missed_frames = 0;
 
-        for (frame, (p0, p1)) in frames.into_iter().enumerate() {
-            let _ = frame_it.next();
+        for (frame, (commands, ports)) in frames.into_iter().enumerate() {
+            let wait = frame_it.next().expect("Frame iterator never ends.");
+            if wait.missed > 0 {
+                warn!(?frame, missed = wait.missed, "Missed frames (lag).");
+                missed_frames += wait.missed;
+            }
 
-            let wait_to_inner_frame = std::time::Instant::now() + wait_inner_frame;
-            while std::time::Instant::now() < wait_to_inner_frame {}
+            std::thread::sleep(wait_inner_frame);
 
             if trace_is_enabled && frame != 0 && frame % TRACE_EVERY_N_FRAMES == 0 {
                 let elapsed = last.elapsed();
@@ -162,6 +244,8 @@ fn main() {
 
                 trace!(
                     ?frame,
+                    counter = wait.counter(),
+                    ?missed_frames,
                     ?elapsed,
                     ?per_frame,
                     ?fps,
@@ -172,72 +256,241 @@ fn main() {
                 last = std::time::Instant::now();
             }
 
-            if let Some(p0) = p0 {
-                core.send_gamepad(0, p0);
+            if should_hash(frame) {
+                let hash = match core.take_screenshot() {
+                    Ok(image) => hash_frame(image.width(), image.height(), image.as_bytes()),
+                    Err(e) => {
+                        error!(?frame, "Could not capture the frame: {}", e);
+                        std::process::exit(1);
+                    }
+                };
+
+                if let Some(expected) = expected.as_ref().and_then(|e| e.get(&frame)) {
+                    if *expected != hash {
+                        error!(?frame, %expected, actual = %hash, "Frame does not match.");
+                        println!("First divergent frame: {}", frame);
+                        std::process::exit(1);
+                    }
+                    verified += 1;
+                }
+                if let Some(recorded) = recorded.as_mut() {
+                    recorded.push(frame, hash);
+                }
+            }
+
+            for command in commands {
+                match actions[&command] {
+                    CommandAction::SoftReset => {
+                        debug!(?frame, ?command, "Resetting the core");
+                        core.soft_reset();
+                    }
+                    CommandAction::StatusPulse(bit) => {
+                        debug!(?frame, ?command, bit, "Triggering status bit");
+                        core.status_pulse(bit as usize);
+                    }
+                }
             }
-            if let Some(p1) = p1 {
-                core.send_gamepad(1, p1);
+
+            for (i, port) in ports.into_iter().enumerate() {
+                if let Some(map) = port {
+                    core.send_gamepad(i as u8, map);
+                }
+            }
+        }
+
+        info!(?missed_frames, elapsed = ?start.elapsed(), "TAS playback done.");
+
+        if let Some(expected) = expected {
+            if verified != expected.len() {
+                error!(
+                    verified,
+                    expected = expected.len(),
+                    "TAS ended before all frames could be verified."
+                );
+                std::process::exit(1);
+            }
+            info!(verified, "All frames match.");
+        }
+        if let (Some(recorded), Some(path)) = (recorded, &opts.record_hashes) {
+            if let Err(e) = recorded.save(path) {
+                error!("{}", e);
+                std::process::exit(1);
             }
+            info!(
+                frames = recorded.len(),
+                "Hashes written to {}",
+                path.display()
+            );
         }
     } else {
         info!("No TAS file provided, running the core indefinitely.");
-        loop {}
-    }
-}
-
-fn fce_button_to_mister(button: FceInputButton) -> MisterFpgaButtons {
-    match button {
-        FceInputButton::A => MisterFpgaButtons::A,
-        FceInputButton::B => MisterFpgaButtons::B,
-        FceInputButton::Select => MisterFpgaButtons::Back,
-        FceInputButton::Start => MisterFpgaButtons::Start,
-        FceInputButton::Up => MisterFpgaButtons::DpadUp,
-        FceInputButton::Down => MisterFpgaButtons::DpadDown,
-        FceInputButton::Left => MisterFpgaButtons::DpadLeft,
-        FceInputButton::Right => MisterFpgaButtons::DpadRight,
+        loop {
+            std::thread::park();
+        }
     }
 }
 
-fn fce_gamepad_to_button_map(gamepad: FceInputGamepad, mut map: ButtonMap) -> ButtonMap {
+fn button_set_to_button_map(buttons: ButtonSet, mut map: ButtonMap) -> ButtonMap {
     map.clear();
-    for button in gamepad.buttons() {
-        map.press(fce_button_to_mister(button));
+    for button in buttons.iter() {
+        map.down(button.as_repr());
     }
     map
 }
 
-fn read_frames(
-    tas_file: impl AsRef<Path>,
-    base_map: ButtonMap,
-) -> Result<Vec<(Option<ButtonMap>, Option<ButtonMap>)>, &'static str> {
-    // let base_map = ButtonMap::new();
+/// Load a TAS file, detecting its format from its content.
+fn load_movie(tas_file: impl AsRef<Path>) -> Result<Box<dyn Movie>, String> {
     let tas = tas_file.as_ref();
-    match tas.extension().map(|e| e.to_str().unwrap_or_default()) {
-        Some("fm2") => {
+    let mut file = std::fs::File::open(tas).map_err(|e| e.to_string())?;
+
+    let mut magic = [0u8; 4];
+    let len = file.read(&mut magic).map_err(|e| e.to_string())?;
+    file.rewind().map_err(|e| e.to_string())?;
+
+    match &magic[..len] {
+        // BK2 files are zip archives.
+        b"PK\x03\x04" => {
+            info!("Reading BK2 file: {}", tas.display());
+            let bk2 = bk2_format::Bk2File::load(file).map_err(|e| e.to_string())?;
+            Ok(Box::new(bk2))
+        }
+        // FM2 files always start with their version.
+        b"vers" => {
             info!("Reading FM2 file: {}", tas.display());
-            // Read the file and decode it.
-            let file = std::fs::File::open(&tas).expect("Could not open TAS file");
-            let fm = fce_movie_format::FceFile::load_stream(BufReader::new(file)).unwrap();
-
-            let frames = fm.frames().map(|f| {
-                let p0 = f
-                    .port0
-                    .as_ref()
-                    .and_then(|p| p.as_gamepad())
-                    .map(|buttons| fce_gamepad_to_button_map(*buttons, base_map.clone()));
-                let p1 = f
-                    .port1
-                    .as_ref()
-                    .and_then(|p| p.as_gamepad())
-                    .map(|buttons| fce_gamepad_to_button_map(*buttons, base_map.clone()));
-                (p0, p1)
-            });
-
-            Ok(frames.collect())
+            let fm = fce_movie_format::FceFile::load_stream(BufReader::new(file))
+                .map_err(|e| e.to_string())?;
+            Ok(Box::new(fm))
         }
         _ => {
             error!("Unsupported TAS file format.");
-            Err("Unsupported TAS file format.")
+            Err("Unsupported TAS file format.".to_string())
+        }
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The part of a ROM file that emulators hash. For iNES files, the header
+/// (and trainer, if any) is not part of the checksum.
+fn rom_payload(data: &[u8]) -> &[u8] {
+    if data.len() >= 16 && data.starts_with(b"NES\x1A") {
+        let trainer = if data[6] & 0x04 != 0 { 512 } else { 0 };
+        data.get(16 + trainer..).unwrap_or_default()
+    } else {
+        data
+    }
+}
+
+/// Verify that the ROM matches the checksum the movie was recorded with.
+fn verify_rom_checksum(movie: &dyn Movie, rom: &Path) -> Result<(), String> {
+    let metadata = movie.metadata();
+    let Some(expected) = metadata.rom_checksum else {
+        warn!("TAS file has no ROM checksum, cannot verify the ROM.");
+        return Ok(());
+    };
+
+    let data = std::fs::read(rom).map_err(|e| format!("Could not read ROM: {}", e))?;
+    let payload = rom_payload(&data);
+    let (algorithm, expected, actual) = match expected {
+        RomChecksum::Md5(md5) => ("MD5", hex(&md5), hex(&md5::compute(payload).0)),
+        RomChecksum::Sha1(sha1) => ("SHA1", hex(&sha1), hex(&sha1::Sha1::digest(payload))),
+    };
+
+    if expected == actual {
+        info!(algorithm, checksum = %actual, "ROM checksum matches the TAS file.");
+        Ok(())
+    } else {
+        Err(format!(
+            "ROM checksum does not match the TAS file. Movie ROM: {:?}, \
+             expected {} {}, ROM {:?} has {}.",
+            metadata.rom_filename.unwrap_or_default(),
+            algorithm,
+            expected,
+            rom.display(),
+            actual,
+        ))
+    }
+}
+
+/// How a movie command is executed on the core.
+#[derive(Debug, Copy, Clone)]
+enum CommandAction {
+    SoftReset,
+    StatusPulse(u8),
+}
+
+/// Map the movie commands to the triggers of the core's config string.
+/// Returns an error if the movie uses a command that the core does not
+/// expose, as the movie would desync.
+fn command_actions(
+    config: &config_string::Config,
+    frames: &[Frame],
+) -> Result<HashMap<MovieCommand, CommandAction>, String> {
+    let mut actions = HashMap::new();
+    actions.insert(MovieCommand::SoftReset, CommandAction::SoftReset);
+
+    // MiSTer cores have no power switch. Use a dedicated trigger if the core
+    // has one, otherwise a reset is the closest thing.
+    let power = config
+        .find_trigger_by_label(&["Power", "Power Cycle", "Hard Reset", "Cold Reset"])
+        .map_or(CommandAction::SoftReset, CommandAction::StatusPulse);
+    actions.insert(MovieCommand::PowerCycle, power);
+
+    // Triggers are matched by their whole label, as other triggers can have
+    // similar labels (e.g. ejecting the disk, or "Power LED").
+    let triggers = [
+        (
+            MovieCommand::FdsDiskInsert,
+            config.find_trigger_by_label(&["Insert Disk", "Disk Insert", "FDS Insert Disk"]),
+        ),
+        (
+            MovieCommand::FdsDiskSelect,
+            config.find_trigger_by_label(&[
+                "Change Disk Side",
+                "Disk Side",
+                "Change Disk",
+                "Swap Disk",
+                "Disk Swap",
+            ]),
+        ),
+        (
+            MovieCommand::VsInsertCoin,
+            config.find_trigger_by_label(&["Insert Coin", "Coin"]),
+        ),
+    ];
+    for (command, bit) in triggers {
+        if let Some(bit) = bit {
+            actions.insert(command, CommandAction::StatusPulse(bit));
         }
     }
+
+    if let Some(command) = frames
+        .iter()
+        .flat_map(|(commands, _)| commands)
+        .find(|c| !actions.contains_key(*c))
+    {
+        return Err(format!(
+            "Core has no trigger for the {command:?} TAS command."
+        ));
+    }
+
+    Ok(actions)
+}
+
+type Frame = (Vec<MovieCommand>, Vec<Option<ButtonMap>>);
+
+fn read_frames(movie: &dyn Movie, base_map: ButtonMap) -> Vec<Frame> {
+    movie
+        .frames()
+        .map(|f| {
+            let ports = f
+                .ports
+                .into_iter()
+                .map(|p| p.map(|buttons| button_set_to_button_map(buttons, base_map)))
+                .collect();
+            (f.commands, ports)
+        })
+        .collect()
 }